@@ -0,0 +1,288 @@
+//! 通过HTTP调用go-cqhttp的API
+
+use super::limit::RateLimiter;
+use super::mode::{CallMode, Failed, Outcome};
+use super::params::Action;
+use super::service::Service;
+use super::APIResponse;
+use crate::Result;
+use async_trait::async_trait;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+
+/// go-cqhttp的[HTTP API](https://docs.go-cqhttp.org/guide/quick_start.html#%E5%9F%BA%E7%A1%80%E6%95%99%E7%A8%8B)客户端
+///
+/// 调用方式可以对整个客户端设置（`mode`），也可以对单次调用设置（`with_mode`, `call_with`）：
+///
+/// ```no_run
+/// # async fn f() -> rust_gocqhttp::error::Result<()> {
+/// use rust_gocqhttp::api::client::HttpClient;
+/// use rust_gocqhttp::api::mode::CallMode;
+/// use rust_gocqhttp::api::params::DeleteMsg;
+/// use rust_gocqhttp::api::GoCqhttpAPI;
+///
+/// let client = HttpClient::new("http://127.0.0.1:5700");
+/// // 异步调用，`Ok(())`表示已提交
+/// client
+///     .with_mode(CallMode::Async)
+///     .delete_msg(DeleteMsg::new(1))
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct HttpClient {
+    client: reqwest::Client,
+    base_url: String,
+    access_token: Option<String>,
+    headers: HeaderMap,
+    mode: CallMode,
+    limiter: Option<RateLimiter>,
+}
+
+impl HttpClient {
+    /// `base_url`为go-cqhttp HTTP服务的地址，例如`http://127.0.0.1:5700`
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self::with_client(base_url, reqwest::Client::new())
+    }
+
+    /// 使用自定义的`reqwest::Client`，例如设置了默认请求头、代理或超时的客户端
+    pub fn with_client(base_url: impl Into<String>, client: reqwest::Client) -> Self {
+        Self {
+            client,
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            access_token: None,
+            headers: HeaderMap::new(),
+            mode: CallMode::Normal,
+            limiter: None,
+        }
+    }
+
+    /// 设置access token
+    pub fn access_token(mut self, access_token: impl Into<String>) -> Self {
+        self.access_token = Some(access_token.into());
+        self
+    }
+
+    /// 每次请求都附加的请求头，例如反向代理要求的认证信息
+    pub fn header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.insert(name, value);
+        self
+    }
+
+    /// 设置客户端的默认调用方式
+    pub fn mode(mut self, mode: CallMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// 设置发送消息的限速，默认不限速。克隆的客户端共享同一个限速器
+    pub fn rate_limit(mut self, limiter: RateLimiter) -> Self {
+        self.limiter = Some(limiter);
+        self
+    }
+
+    /// 限速器，用于查看排队中的发送数量
+    pub fn limiter(&self) -> Option<&RateLimiter> {
+        self.limiter.as_ref()
+    }
+
+    /// 返回使用`mode`调用的客户端，与原客户端共享连接池
+    ///
+    /// 通过`GoCqhttpAPI`的方法以非`Normal`方式调用时，返回`()`的API在提交后返回`Ok(())`，
+    /// 需要返回数据的API返回`mode::Submitted`错误。需要明确区分时使用`call_with`
+    pub fn with_mode(&self, mode: CallMode) -> Self {
+        self.clone().mode(mode)
+    }
+
+    /// 以客户端的默认调用方式调用`action`
+    pub async fn call(&self, action: &str, params: Value) -> Result<Outcome<Value>> {
+        self.call_with(self.mode, action, params).await
+    }
+
+    /// 以`mode`调用`action`，`params`中值为`null`的字段会被省略，`status`为`failed`时返回`mode::Failed`错误
+    ///
+    /// 设置了限速时，发送消息的调用会排队等待。客户端本身不重试，需要重试时用`RetryPolicy`包装客户端
+    pub async fn call_with(
+        &self,
+        mode: CallMode,
+        action: &str,
+        params: Value,
+    ) -> Result<Outcome<Value>> {
+        if let Some(limiter) = &self.limiter {
+            limiter.acquire_for(action, &params).await;
+        }
+        let resp = self.request(mode, action, params).await?;
+        if resp.is_failed() {
+            return Err(Box::new(Failed {
+                action: action.to_string(),
+                retcode: resp.retcode,
+                message: resp.message,
+                wording: resp.wording,
+            }));
+        }
+        Ok(if resp.is_async() {
+            Outcome::Submitted
+        } else {
+            Outcome::Completed(resp.data.unwrap_or(Value::Null))
+        })
+    }
+
+    /// 调用任意API，用于`GoCqhttpAPI`中没有的API，例如新版本或其他OneBot实现新增的API
+    ///
+    /// `params`需要序列化为JSON对象，调用方式和返回值同`GoCqhttpAPI`的方法
+    pub async fn call_action<P, R>(&self, action: &str, params: &P) -> Result<R>
+    where
+        P: Serialize + ?Sized,
+        R: DeserializeOwned,
+    {
+        self.action(action, serde_json::to_value(params)?).await
+    }
+
+    /// 以客户端的默认调用方式调用`params`对应的API
+    pub async fn execute<A: Action + Send>(&self, params: A) -> Result<A::Output> {
+        self.action(A::NAME, serde_json::to_value(&params)?).await
+    }
+
+    /// 以客户端的默认调用方式调用任意API，返回原始响应
+    ///
+    /// 只有HTTP请求失败时返回错误，`status`为`failed`的响应也会原样返回。
+    /// 设置了限速时同样排队等待
+    pub async fn call_raw(&self, action: &str, params: Value) -> Result<APIResponse<Value>> {
+        if let Some(limiter) = &self.limiter {
+            limiter.acquire_for(action, &params).await;
+        }
+        self.request(self.mode, action, params).await
+    }
+
+    /// `params`中值为`null`的字段会被省略
+    async fn request(
+        &self,
+        mode: CallMode,
+        action: &str,
+        mut params: Value,
+    ) -> Result<APIResponse<Value>> {
+        if let Value::Object(map) = &mut params {
+            map.retain(|_, v| !v.is_null());
+        }
+        let url = format!("{}/{}", self.base_url, mode.endpoint(action));
+        let mut request = self
+            .client
+            .post(url)
+            .headers(self.headers.clone())
+            .json(&params);
+        if let Some(token) = &self.access_token {
+            request = request.bearer_auth(token);
+        }
+        APIResponse::from_http_raw(request.send().await?).await
+    }
+
+    async fn action<R: DeserializeOwned>(&self, action: &str, params: Value) -> Result<R> {
+        self.call(action, params).await?.into_result(action)
+    }
+}
+
+#[async_trait]
+impl Service for HttpClient {
+    /// 以客户端的默认调用方式调用
+    async fn call(&self, action: &str, params: Value) -> Result<Outcome<Value>> {
+        HttpClient::call(self, action, params).await
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::HttpClient;
+    use crate::api::limit::{Quota, RateLimiter};
+    use crate::api::mode::Failed;
+    use crate::api::params::DeleteMsg;
+    use crate::api::APIStatus;
+    use crate::api::GoCqhttpAPI;
+    use crate::testing::FakeGoCqhttp;
+    use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+    use std::time::{Duration, Instant};
+
+    #[tokio::test]
+    async fn test_call_action() {
+        #[derive(Serialize)]
+        struct SetReaction {
+            message_id: i32,
+            code: &'static str,
+        }
+
+        #[derive(Deserialize)]
+        struct Reaction {
+            count: i32,
+        }
+
+        let fake = FakeGoCqhttp::start().await.unwrap();
+        fake.respond("set_reaction", json!({ "count": 2 }));
+        let client = fake.client();
+        let params = SetReaction {
+            message_id: 1,
+            code: "76",
+        };
+        let reaction: Reaction = client.call_action("set_reaction", &params).await.unwrap();
+        assert_eq!(reaction.count, 2);
+        assert_eq!(
+            fake.calls_to("set_reaction"),
+            [json!({ "message_id": 1, "code": "76" })]
+        );
+        fake.fail("set_reaction", 100, "no");
+        let e = client
+            .call_action::<_, Reaction>("set_reaction", &params)
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(e.downcast_ref::<Failed>().unwrap().retcode, 100);
+    }
+
+    #[tokio::test]
+    async fn test_call_raw() {
+        let fake = FakeGoCqhttp::start().await.unwrap();
+        let limiter = RateLimiter::new().per_group(Quota::new(1, Duration::from_millis(50)));
+        let client = fake.client().rate_limit(limiter);
+        fake.fail("send_group_msg", 100, "no");
+        let params = json!({ "group_id": 1, "message": "hi", "auto_escape": null });
+        let start = Instant::now();
+        let resp = client
+            .call_raw("send_group_msg", params.clone())
+            .await
+            .unwrap();
+        assert!(matches!(resp.status, APIStatus::Failed));
+        assert_eq!((resp.retcode, resp.wording.as_str()), (100, "no"));
+        // 失败的响应原样返回，不重试；同一个群的第二次发送需要排队
+        client.call_raw("send_group_msg", params).await.unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(50));
+        assert_eq!(
+            fake.calls_to("send_group_msg"),
+            vec![json!({ "group_id": 1, "message": "hi" }); 2]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_headers() {
+        let fake = FakeGoCqhttp::start().await.unwrap();
+        let mut defaults = HeaderMap::new();
+        defaults.insert("x-proxy-auth", HeaderValue::from_static("p"));
+        let inner = reqwest::Client::builder()
+            .default_headers(defaults)
+            .build()
+            .unwrap();
+        let client = HttpClient::with_client(fake.url(), inner)
+            .access_token("t")
+            .header(
+                HeaderName::from_static("x-trace"),
+                HeaderValue::from_static("1"),
+            );
+        client.delete_msg(DeleteMsg::new(1)).await.unwrap();
+        let headers = &fake.calls()[0].headers;
+        assert_eq!(headers["x-proxy-auth"], "p");
+        assert_eq!(headers["x-trace"], "1");
+        assert_eq!(headers["authorization"], "Bearer t");
+    }
+}
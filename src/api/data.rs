@@ -1,8 +1,9 @@
-use crate::message::{Message, MessageType};
+use crate::message::Message;
 use serde::{Deserialize, Deserializer, Serialize};
+use std::fmt;
 
 /// `get_login_info`API的响应数据结构
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct LoginInfo {
     #[serde(default)]
     /// QQ号
@@ -13,7 +14,7 @@ pub struct LoginInfo {
 }
 
 /// `ModelShowVariants.variants`字段的元素类型
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct ModelShowVariant {
     #[serde(default)]
     /// 在线机型名
@@ -24,7 +25,7 @@ pub struct ModelShowVariant {
 }
 
 /// `get_model_show`API的响应数据结构
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct ModelShowVariants {
     #[serde(default)]
     /// 在线机型列表
@@ -32,7 +33,7 @@ pub struct ModelShowVariants {
 }
 
 /// `ClientDevices.clients`字段的元素类型
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct ClientDevice {
     #[serde(default)]
     /// 客户端ID
@@ -46,7 +47,7 @@ pub struct ClientDevice {
 }
 
 /// `get_online_clients`API的响应数据结构
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct ClientDevices {
     #[serde(default)]
     /// 在线客户端列表
@@ -54,6 +55,7 @@ pub struct ClientDevices {
 }
 
 /// `StrangerInfo.sex`字段的类型
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum Sex {
     /// 男性
     Male,
@@ -73,13 +75,22 @@ impl<T: AsRef<str>> From<T> for Sex {
     }
 }
 
-impl ToString for Sex {
-    fn to_string(&self) -> String {
-        match self {
-            Sex::Male => "male".to_string(),
-            Sex::Female => "female".to_string(),
-            Sex::Unknown => "unknown".to_string(),
-        }
+impl fmt::Display for Sex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Sex::Male => "male",
+            Sex::Female => "female",
+            Sex::Unknown => "unknown",
+        })
+    }
+}
+
+impl Serialize for Sex {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
     }
 }
 
@@ -92,8 +103,18 @@ impl<'de> Deserialize<'de> for Sex {
     }
 }
 
+/// 消息的会话类型，用于`send_forward_msg`等API的`message_type`字段
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChatType {
+    /// 私聊
+    Private,
+    /// 群聊
+    Group,
+}
+
 /// `get_stranger_info`API的响应数据结构
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct StrangerInfo {
     #[serde(default)]
     /// QQ号
@@ -118,7 +139,7 @@ pub struct StrangerInfo {
 }
 
 /// `get_friend_list`API的响应数据结构
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Friend {
     #[serde(default)]
     /// QQ号
@@ -132,7 +153,7 @@ pub struct Friend {
 }
 
 /// `get_unidirectional_friend_list`API的响应数据结构
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct UnidirectionalFriend {
     #[serde(default)]
     /// QQ号
@@ -146,7 +167,7 @@ pub struct UnidirectionalFriend {
 }
 
 /// `send_private_msg`, `send_group_msg`API的响应数据结构
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct MessageID {
     #[serde(default)]
     /// 消息ID
@@ -154,7 +175,7 @@ pub struct MessageID {
 }
 
 /// `Msg.sender`字段的类型
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct Sender {
     #[serde(default)]
     /// 发送者昵称
@@ -162,10 +183,19 @@ pub struct Sender {
     #[serde(default)]
     /// 发送者QQ号
     pub user_id: i64,
+    #[serde(default)]
+    /// 群名片／备注，仅群消息
+    pub card: Option<String>,
+    #[serde(default)]
+    /// 群角色，仅群消息
+    pub role: Option<GroupRole>,
+    #[serde(default)]
+    /// 临时会话的来源群号，仅私聊消息事件
+    pub group_id: Option<i64>,
 }
 
-/// `get_msg`API的响应数据结构
-#[derive(Deserialize)]
+/// 消息记录，`get_msg`API的响应数据结构，也是`get_group_msg_history`API返回的消息
+#[derive(Serialize, Deserialize)]
 pub struct Msg {
     #[serde(default)]
     /// 是否是群消息
@@ -179,23 +209,41 @@ pub struct Msg {
     #[serde(default)]
     /// 消息真实id
     pub real_id: i32,
+    #[serde(default)]
+    /// 消息序号，用于`get_group_msg_history`API翻页
+    pub message_seq: i64,
     /// 消息类型
-    pub message_type: MessageType,
+    pub message_type: ChatType,
+    #[serde(default)]
+    /// 消息子类型，仅`get_group_msg_history`API返回
+    pub sub_type: String,
+    #[serde(default)]
+    /// 发送者QQ号，仅`get_group_msg_history`API返回，`get_msg`API见`sender.user_id`
+    pub user_id: i64,
     /// 发送者
     pub sender: Sender,
     /// 发送时间
-    pub time: i32,
-    #[serde(default)]
+    pub time: i64,
     /// 消息内容
-    pub message: String,
+    pub message: Message,
     #[serde(default)]
     /// 消息内容
     pub raw_message: String,
 }
 
+/// `get_group_msg_history`API的响应数据结构
+#[derive(Serialize, Deserialize)]
+pub struct MsgHistory {
+    #[serde(default)]
+    /// 消息记录，按时间从旧到新排列
+    pub messages: Vec<Msg>,
+}
+
 /// `get_forward_msg`API的响应数据结构
-#[derive(Deserialize)]
-pub struct ForwardMessage {
+///
+/// 发送合并转发时使用`message::forward::ForwardMessage`
+#[derive(Serialize, Deserialize)]
+pub struct ForwardedMessage {
     /// 消息内容
     pub content: Message,
     /// 发送者
@@ -206,7 +254,7 @@ pub struct ForwardMessage {
 }
 
 /// API的响应数据结构
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct ForwardMessageID {
     #[serde(default)]
     /// 消息ID
@@ -217,7 +265,7 @@ pub struct ForwardMessageID {
 }
 
 /// API的响应数据结构
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct Image {
     #[serde(default)]
     /// 图片源文件大小
@@ -231,7 +279,7 @@ pub struct Image {
 }
 
 /// API的响应数据结构
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct CanSend {
     #[serde(default)]
     /// 是或否
@@ -239,7 +287,7 @@ pub struct CanSend {
 }
 
 /// API的响应数据结构
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct TextDetection {
     #[serde(default)]
     /// 文本
@@ -253,7 +301,7 @@ pub struct TextDetection {
 }
 
 /// `ocr_image`API的响应数据结构
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct OCRImage {
     #[serde(default)]
     /// OCR结果
@@ -264,7 +312,7 @@ pub struct OCRImage {
 }
 
 /// `get_record`API的响应数据结构
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct Record {
     #[serde(default)]
     /// 转换后的语音文件路径, 如`/home/somebody/cqhttp/data/record/0B38145AA44505000B38145AA4450500.mp3`
@@ -274,7 +322,7 @@ pub struct Record {
 /// `get_group_info`, `get_group_list`API的响应数据结构
 ///
 /// 如果机器人尚未加入群, `group_create_time`, `group_level`, `max_member_count`和`member_count`将会为0
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct GroupInfo {
     #[serde(default)]
     /// 群号
@@ -300,6 +348,7 @@ pub struct GroupInfo {
 }
 
 /// 群角色
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum GroupRole {
     /// 群主
     Owner,
@@ -322,13 +371,22 @@ impl<T: AsRef<str>> From<T> for GroupRole {
     }
 }
 
-impl ToString for GroupRole {
-    fn to_string(&self) -> String {
-        match self {
-            GroupRole::Owner => "owner".to_string(),
-            GroupRole::Admin => "admin".to_string(),
-            GroupRole::Member => "member".to_string(),
-        }
+impl fmt::Display for GroupRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            GroupRole::Owner => "owner",
+            GroupRole::Admin => "admin",
+            GroupRole::Member => "member",
+        })
+    }
+}
+
+impl Serialize for GroupRole {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
     }
 }
 
@@ -341,7 +399,7 @@ impl<'de> Deserialize<'de> for GroupRole {
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct GroupMemberInfo {
     #[serde(default)]
     /// 群号
@@ -390,6 +448,7 @@ pub struct GroupMemberInfo {
     pub shut_up_timestamp: i64,
 }
 
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum GroupHonorType {
     Talkative,
     Performer,
@@ -399,16 +458,25 @@ pub enum GroupHonorType {
     All,
 }
 
-impl ToString for GroupHonorType {
-    fn to_string(&self) -> String {
-        match self {
-            GroupHonorType::Talkative => "talkative".to_string(),
-            GroupHonorType::Performer => "performer".to_string(),
-            GroupHonorType::Legend => "legend".to_string(),
-            GroupHonorType::StrongNewbie => "strong_newbie".to_string(),
-            GroupHonorType::Emotion => "emotion".to_string(),
-            GroupHonorType::All => "all".to_string(),
-        }
+impl fmt::Display for GroupHonorType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            GroupHonorType::Talkative => "talkative",
+            GroupHonorType::Performer => "performer",
+            GroupHonorType::Legend => "legend",
+            GroupHonorType::StrongNewbie => "strong_newbie",
+            GroupHonorType::Emotion => "emotion",
+            GroupHonorType::All => "all",
+        })
+    }
+}
+
+impl Serialize for GroupHonorType {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
     }
 }
 
@@ -425,7 +493,7 @@ impl<T: AsRef<str>> From<T> for GroupHonorType {
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct CurrentTalkativeWinner {
     #[serde(default)]
     /// QQ号
@@ -441,7 +509,7 @@ pub struct CurrentTalkativeWinner {
     pub day_count: i32,
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct GroupHonorWinner {
     #[serde(default)]
     /// QQ号
@@ -457,7 +525,7 @@ pub struct GroupHonorWinner {
     pub description: String,
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct GroupHonorInfo {
     #[serde(default)]
     /// 群号
@@ -481,7 +549,7 @@ pub struct GroupHonorInfo {
     pub emotion_list: Vec<GroupHonorWinner>,
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct InvitedRequest {
     #[serde(default)]
     /// 请求ID
@@ -506,7 +574,7 @@ pub struct InvitedRequest {
     pub actor: i64,
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct JoinRequest {
     #[serde(default)]
     /// 请求ID
@@ -534,7 +602,7 @@ pub struct JoinRequest {
     pub actor: i64,
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct GroupSystemMsg {
     #[serde(default)]
     /// 邀请消息列表
@@ -545,7 +613,7 @@ pub struct GroupSystemMsg {
 }
 
 /// `get_essence_msg_list`API的响应数据结构
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct EssenceMsg {
     #[serde(default)]
     /// 发送者QQ号
@@ -571,7 +639,7 @@ pub struct EssenceMsg {
 }
 
 /// `get_group_at_all_remain`API的响应数据结构
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct GroupAtAllRemain {
     #[serde(default)]
     /// 是否可以 @全体成员
@@ -585,17 +653,17 @@ pub struct GroupAtAllRemain {
 }
 
 /// [群消息(anonymous字段)](https://docs.go-cqhttp.org/event/#%E7%BE%A4%E6%B6%88%E6%81%AF)，`set_group_anonymous_ban`API的其中一个可选参数
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct AnonymousGroupMsg {
     /// 匿名用户 ID
-    id: i64,
+    pub id: i64,
     /// 匿名用户名称
-    name: String,
+    pub name: String,
     /// 匿名用户flag, 在调用禁言API时需要传入
-    flag: String,
+    pub flag: String,
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct GroupNoticeImage {
     #[serde(default)]
     /// 图片高度
@@ -608,7 +676,7 @@ pub struct GroupNoticeImage {
     pub id: String,
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct GroupNoticeMessage {
     #[serde(default)]
     /// 公告内容
@@ -619,7 +687,7 @@ pub struct GroupNoticeMessage {
 }
 
 /// `get_group_notice`API的响应数据结构
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct GroupNotice {
     #[serde(default)]
     /// 公告发表者
@@ -632,7 +700,7 @@ pub struct GroupNotice {
 }
 
 /// `get_group_file_system_info`API的响应数据结构
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct GroupFileSystemInfo {
     #[serde(default)]
     /// 文件总数
@@ -648,7 +716,7 @@ pub struct GroupFileSystemInfo {
     pub total_space: i64,
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct File {
     #[serde(default)]
     /// 群号
@@ -685,7 +753,7 @@ pub struct File {
     pub uploader_name: String,
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct Folder {
     #[serde(default)]
     /// 群号
@@ -711,7 +779,7 @@ pub struct Folder {
 }
 
 /// `get_group_root_files`API的响应数据结构
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct GroupFiles {
     #[serde(default)]
     /// 文件列表
@@ -722,7 +790,7 @@ pub struct GroupFiles {
 }
 
 /// `get_group_file_url`API的响应数据结构
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct GroupFileUrl {
     #[serde(default)]
     /// 文件下载链接
@@ -730,7 +798,7 @@ pub struct GroupFileUrl {
 }
 
 /// `get_cookies`API的响应数据结构
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct Cookies {
     #[serde(default)]
     /// Cookies
@@ -738,7 +806,7 @@ pub struct Cookies {
 }
 
 /// `get_csrf_token`API的响应数据结构
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct CSRFToken {
     #[serde(default)]
     /// CSRF Token
@@ -746,7 +814,7 @@ pub struct CSRFToken {
 }
 
 /// `get_credentials`API的响应数据结构
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct Credentials {
     #[serde(default)]
     /// Cookies
@@ -757,7 +825,7 @@ pub struct Credentials {
 }
 
 /// `get_version_info`API的响应数据结构
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct VersionInfo {
     #[serde(default)]
     /// 应用标识, 固定值go-cqhttp
@@ -804,7 +872,7 @@ pub struct VersionInfo {
 }
 
 /// `Status.stat`字段的类型
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct Statistics {
     #[serde(default)]
     /// 收到的数据包总数
@@ -833,7 +901,7 @@ pub struct Statistics {
 }
 
 /// `get_status`API的响应数据结构
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct Status {
     #[serde(default)]
     /// 原CQHTTP字段, 恒定为true
@@ -858,7 +926,7 @@ pub struct Status {
 }
 
 /// `download_file`API的响应数据结构
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct DownloadedFile {
     #[serde(default)]
     /// 下载文件的绝对路径
@@ -885,6 +953,19 @@ impl From<i64> for UrlSafetyLevel {
     }
 }
 
+impl Serialize for UrlSafetyLevel {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_i64(match self {
+            UrlSafetyLevel::Safe => 1,
+            UrlSafetyLevel::Unknown => 2,
+            UrlSafetyLevel::Dangerous => 3,
+        })
+    }
+}
+
 impl<'de> Deserialize<'de> for UrlSafetyLevel {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -895,14 +976,14 @@ impl<'de> Deserialize<'de> for UrlSafetyLevel {
 }
 
 /// `check_url_safely`API的响应数据结构
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct UrlSafety {
     /// 安全等级
     pub level: UrlSafetyLevel,
 }
 
 /// `get_word_slices`API的响应数据结构
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct WordSlices {
     #[serde(default)]
     /// 分词结果
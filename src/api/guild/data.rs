@@ -0,0 +1,363 @@
+use crate::message::Message;
+use serde::{Deserialize, Serialize};
+
+/// `get_guild_service_profile`API的响应数据结构
+#[derive(Serialize, Deserialize)]
+pub struct GuildServiceProfile {
+    #[serde(default)]
+    /// 昵称
+    pub nickname: String,
+    #[serde(default)]
+    /// 自身的ID
+    pub tiny_id: String,
+    #[serde(default)]
+    /// 头像链接
+    pub avatar_url: String,
+}
+
+/// `get_guild_list`API的响应数据结构
+#[derive(Serialize, Deserialize)]
+pub struct GuildInfo {
+    #[serde(default)]
+    /// 频道ID
+    pub guild_id: String,
+    #[serde(default)]
+    /// 频道名称
+    pub guild_name: String,
+    #[serde(default)]
+    /// 频道显示ID, 公测后可能作为搜索ID使用
+    pub guild_display_id: String,
+}
+
+/// `get_guild_meta_by_guest`API的响应数据结构
+#[derive(Serialize, Deserialize)]
+pub struct GuildMeta {
+    #[serde(default)]
+    /// 频道ID
+    pub guild_id: String,
+    #[serde(default)]
+    /// 频道名称
+    pub guild_name: String,
+    #[serde(default)]
+    /// 频道简介
+    pub guild_profile: String,
+    #[serde(default)]
+    /// 创建时间
+    pub create_time: i64,
+    #[serde(default)]
+    /// 频道人数上限
+    pub max_member_count: i64,
+    #[serde(default)]
+    /// 频道BOT数上限
+    pub max_robot_count: i64,
+    #[serde(default)]
+    /// 频道管理员人数上限
+    pub max_admin_count: i64,
+    #[serde(default)]
+    /// 已加入人数
+    pub member_count: i64,
+    #[serde(default)]
+    /// 创建者ID
+    pub owner_id: String,
+}
+
+/// `ChannelInfo.slow_modes`字段的元素类型
+#[derive(Serialize, Deserialize)]
+pub struct SlowModeInfo {
+    #[serde(default)]
+    /// 慢速模式Key
+    pub slow_mode_key: i32,
+    #[serde(default)]
+    /// 慢速模式说明
+    pub slow_mode_text: String,
+    #[serde(default)]
+    /// 周期内发言频率限制
+    pub speak_frequency: i32,
+    #[serde(default)]
+    /// 单位周期时间, 单位秒
+    pub slow_mode_circle: i32,
+}
+
+/// `get_guild_channel_list`API的响应数据结构，也用于子频道相关的通知事件
+#[derive(Serialize, Deserialize)]
+pub struct ChannelInfo {
+    #[serde(default)]
+    /// 所属频道ID
+    pub owner_guild_id: String,
+    #[serde(default)]
+    /// 子频道ID
+    pub channel_id: String,
+    #[serde(default)]
+    /// 子频道类型，1为文字频道，2为语音频道，5为直播频道，7为主题频道
+    pub channel_type: i32,
+    #[serde(default)]
+    /// 子频道名称
+    pub channel_name: String,
+    #[serde(default)]
+    /// 创建时间
+    pub create_time: i64,
+    #[serde(default)]
+    /// 创建者ID
+    pub creator_tiny_id: String,
+    #[serde(default)]
+    /// 发言权限类型
+    pub talk_permission: i32,
+    #[serde(default)]
+    /// 可视性类型
+    pub visible_type: i32,
+    #[serde(default)]
+    /// 当前启用的慢速模式Key
+    pub current_slow_mode: i32,
+    #[serde(default)]
+    /// 频道内可用慢速模式类型列表
+    pub slow_modes: Vec<SlowModeInfo>,
+}
+
+/// `GuildMemberList.members`字段的元素类型
+#[derive(Serialize, Deserialize)]
+pub struct GuildMemberInfo {
+    #[serde(default)]
+    /// 成员ID
+    pub tiny_id: String,
+    #[serde(default)]
+    /// 成员头衔
+    pub title: String,
+    #[serde(default)]
+    /// 成员昵称
+    pub nickname: String,
+    #[serde(default)]
+    /// 所在权限组ID
+    pub role_id: String,
+    #[serde(default)]
+    /// 所在权限组名称
+    pub role_name: String,
+}
+
+/// `get_guild_member_list`API的响应数据结构
+#[derive(Serialize, Deserialize)]
+pub struct GuildMemberList {
+    #[serde(default)]
+    /// 成员列表
+    pub members: Vec<GuildMemberInfo>,
+    #[serde(default)]
+    /// 是否最终页
+    pub finished: bool,
+    #[serde(default)]
+    /// 翻页Token
+    pub next_token: String,
+}
+
+/// `GuildMemberProfile.roles`字段的元素类型
+#[derive(Serialize, Deserialize)]
+pub struct RoleInfo {
+    #[serde(default)]
+    /// 身份组ID
+    pub role_id: String,
+    #[serde(default)]
+    /// 身份组名称
+    pub role_name: String,
+}
+
+/// `get_guild_member_profile`API的响应数据结构
+#[derive(Serialize, Deserialize)]
+pub struct GuildMemberProfile {
+    #[serde(default)]
+    /// 用户ID
+    pub tiny_id: String,
+    #[serde(default)]
+    /// 用户昵称
+    pub nickname: String,
+    #[serde(default)]
+    /// 头像地址
+    pub avatar_url: String,
+    #[serde(default)]
+    /// 加入时间
+    pub join_time: i64,
+    #[serde(default)]
+    /// 加入的所有身份组
+    pub roles: Vec<RoleInfo>,
+}
+
+/// 频道消息的发送者
+#[derive(Serialize, Deserialize)]
+pub struct GuildSender {
+    #[serde(default)]
+    /// 发送者ID
+    pub tiny_id: String,
+    #[serde(default)]
+    /// 发送者昵称
+    pub nickname: String,
+}
+
+/// 频道消息的表情贴
+#[derive(Serialize, Deserialize)]
+pub struct ReactionInfo {
+    #[serde(default)]
+    /// 表情ID
+    pub emoji_id: String,
+    #[serde(default)]
+    /// 表情对应数值ID
+    pub emoji_index: i32,
+    #[serde(default)]
+    /// 表情类型
+    pub emoji_type: i32,
+    #[serde(default)]
+    /// 表情名字
+    pub emoji_name: String,
+    #[serde(default)]
+    /// 当前表情被贴数量
+    pub count: i32,
+    #[serde(default)]
+    /// BOT是否点击
+    pub clicked: bool,
+}
+
+/// `get_guild_msg`API的响应数据结构
+#[derive(Serialize, Deserialize)]
+pub struct GuildMsg {
+    #[serde(default)]
+    /// 频道ID
+    pub guild_id: String,
+    #[serde(default)]
+    /// 子频道ID
+    pub channel_id: String,
+    #[serde(default)]
+    /// 消息ID
+    pub message_id: String,
+    #[serde(default)]
+    /// 消息序号
+    pub message_seq: i64,
+    #[serde(default)]
+    /// 消息来源，`channel`为子频道消息，`direct`为私信
+    pub message_source: String,
+    /// 发送者
+    pub sender: GuildSender,
+    #[serde(default)]
+    /// 发送时间
+    pub time: i64,
+    /// 消息内容
+    pub message: Message,
+    #[serde(default)]
+    /// 表情贴，仅在不使用缓存时返回
+    pub reactions: Vec<ReactionInfo>,
+}
+
+/// `send_guild_channel_msg`API的响应数据结构
+#[derive(Serialize, Deserialize)]
+pub struct GuildMessageID {
+    #[serde(default)]
+    /// 消息ID
+    pub message_id: String,
+}
+
+/// `FeedInfo.poster_info`字段的类型
+#[derive(Serialize, Deserialize)]
+pub struct PosterInfo {
+    #[serde(default)]
+    /// 发帖人ID
+    pub tiny_id: String,
+    #[serde(default)]
+    /// 发帖人昵称
+    pub nickname: String,
+    #[serde(default)]
+    /// 发帖人头像
+    pub icon_url: String,
+}
+
+/// `FeedResource`中的媒体
+#[derive(Serialize, Deserialize)]
+pub struct FeedMedia {
+    #[serde(default)]
+    /// 文件ID
+    pub file_id: String,
+    #[serde(default)]
+    /// 模式
+    pub pattern_id: String,
+    #[serde(default)]
+    /// 地址
+    pub url: String,
+    #[serde(default)]
+    /// 高度
+    pub height: i32,
+    #[serde(default)]
+    /// 宽度
+    pub width: i32,
+}
+
+/// `FeedInfo.resource`字段的类型
+#[derive(Serialize, Deserialize)]
+pub struct FeedResource {
+    #[serde(default)]
+    /// 帖子附带的图片列表
+    pub images: Vec<FeedMedia>,
+    #[serde(default)]
+    /// 帖子附带的视频列表
+    pub videos: Vec<FeedMedia>,
+}
+
+/// `get_topic_channel_feeds`API的响应数据结构
+#[derive(Serialize, Deserialize)]
+pub struct FeedInfo {
+    #[serde(default)]
+    /// 帖子ID
+    pub id: String,
+    #[serde(default)]
+    /// 子频道ID
+    pub channel_id: String,
+    #[serde(default)]
+    /// 频道ID
+    pub guild_id: String,
+    #[serde(default)]
+    /// 发帖时间
+    pub create_time: i64,
+    #[serde(default)]
+    /// 帖子标题
+    pub title: String,
+    #[serde(default)]
+    /// 帖子副标题
+    pub sub_title: String,
+    /// 发帖人信息
+    pub poster_info: PosterInfo,
+    /// 媒体资源信息
+    pub resource: FeedResource,
+    #[serde(default)]
+    /// 帖子内容，结构较为复杂，保留原始JSON
+    pub contents: Vec<serde_json::Value>,
+}
+
+/// `get_guild_roles`API的响应数据结构
+#[derive(Serialize, Deserialize)]
+pub struct GuildRole {
+    #[serde(default)]
+    /// 身份组ID
+    pub role_id: String,
+    #[serde(default)]
+    /// 身份组名称
+    pub role_name: String,
+    #[serde(default)]
+    /// 身份组颜色，ARGB
+    pub argb_color: u32,
+    #[serde(default)]
+    /// 是否在成员列表中单独展示
+    pub independent: bool,
+    #[serde(default)]
+    /// 身份组成员数
+    pub member_count: i32,
+    #[serde(default)]
+    /// 身份组成员数上限
+    pub max_count: i32,
+    #[serde(default)]
+    /// BOT是否拥有该身份组
+    pub owned: bool,
+    #[serde(default)]
+    /// 是否被禁用
+    pub disabled: bool,
+}
+
+/// `create_guild_role`API的响应数据结构
+#[derive(Serialize, Deserialize)]
+pub struct GuildRoleID {
+    #[serde(default)]
+    /// 新创建的身份组ID
+    pub role_id: String,
+}
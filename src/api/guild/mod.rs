@@ -0,0 +1,63 @@
+//! [频道](https://docs.go-cqhttp.org/guild/)相关的API
+//!
+//! 频道中的ID（频道ID、子频道ID、用户的`tiny_id`等）均为字符串
+
+pub mod data;
+pub mod params;
+
+use crate::Result;
+use async_trait::async_trait;
+use data::*;
+use params::*;
+
+/// [GoCqhttp 频道API](https://docs.go-cqhttp.org/guild/api/)
+#[async_trait]
+pub trait GuildAPI {
+    /// [获取频道系统内BOT的资料](https://docs.go-cqhttp.org/guild/api/#%E8%8E%B7%E5%8F%96%E9%A2%91%E9%81%93%E7%B3%BB%E7%BB%9F%E5%86%85bot%E7%9A%84%E8%B5%84%E6%96%99)
+    async fn get_guild_service_profile(&self) -> Result<GuildServiceProfile>;
+
+    /// [获取频道列表](https://docs.go-cqhttp.org/guild/api/#%E8%8E%B7%E5%8F%96%E9%A2%91%E9%81%93%E5%88%97%E8%A1%A8)
+    async fn get_guild_list(&self) -> Result<Vec<GuildInfo>>;
+
+    /// [通过访客获取频道元数据](https://docs.go-cqhttp.org/guild/api/#%E9%80%9A%E8%BF%87%E8%AE%BF%E5%AE%A2%E8%8E%B7%E5%8F%96%E9%A2%91%E9%81%93%E5%85%83%E6%95%B0%E6%8D%AE)
+    async fn get_guild_meta_by_guest(&self, params: GetGuildMetaByGuest) -> Result<GuildMeta>;
+
+    /// [获取子频道列表](https://docs.go-cqhttp.org/guild/api/#%E8%8E%B7%E5%8F%96%E5%AD%90%E9%A2%91%E9%81%93%E5%88%97%E8%A1%A8)
+    async fn get_guild_channel_list(&self, params: GetGuildChannelList)
+        -> Result<Vec<ChannelInfo>>;
+
+    /// [获取频道成员列表](https://docs.go-cqhttp.org/guild/api/#%E8%8E%B7%E5%8F%96%E9%A2%91%E9%81%93%E6%88%90%E5%91%98%E5%88%97%E8%A1%A8)
+    ///
+    /// 首次调用时不设置`next_token`，之后传入上一次返回的`next_token`，直到`finished`为`true`
+    async fn get_guild_member_list(&self, params: GetGuildMemberList) -> Result<GuildMemberList>;
+
+    /// [单独获取频道成员信息](https://docs.go-cqhttp.org/guild/api/#%E5%8D%95%E7%8B%AC%E8%8E%B7%E5%8F%96%E9%A2%91%E9%81%93%E6%88%90%E5%91%98%E4%BF%A1%E6%81%AF)
+    async fn get_guild_member_profile(
+        &self,
+        params: GetGuildMemberProfile,
+    ) -> Result<GuildMemberProfile>;
+
+    /// [获取频道消息](https://docs.go-cqhttp.org/guild/api/#%E8%8E%B7%E5%8F%96%E9%A2%91%E9%81%93%E6%B6%88%E6%81%AF)
+    async fn get_guild_msg(&self, params: GetGuildMsg) -> Result<GuildMsg>;
+
+    /// [发送信息到子频道](https://docs.go-cqhttp.org/guild/api/#%E5%8F%91%E9%80%81%E4%BF%A1%E6%81%AF%E5%88%B0%E5%AD%90%E9%A2%91%E9%81%93)
+    async fn send_guild_channel_msg(&self, params: SendGuildChannelMsg) -> Result<GuildMessageID>;
+
+    /// [获取话题频道帖子](https://docs.go-cqhttp.org/guild/api/#%E8%8E%B7%E5%8F%96%E8%AF%9D%E9%A2%98%E9%A2%91%E9%81%93%E5%B8%96%E5%AD%90)
+    async fn get_topic_channel_feeds(&self, params: GetTopicChannelFeeds) -> Result<Vec<FeedInfo>>;
+
+    /// [获取频道身份组列表](https://docs.go-cqhttp.org/guild/api/#%E8%8E%B7%E5%8F%96%E9%A2%91%E9%81%93%E8%BA%AB%E4%BB%BD%E7%BB%84%E5%88%97%E8%A1%A8)
+    async fn get_guild_roles(&self, params: GetGuildRoles) -> Result<Vec<GuildRole>>;
+
+    /// [创建频道身份组](https://docs.go-cqhttp.org/guild/api/#%E5%88%9B%E5%BB%BA%E9%A2%91%E9%81%93%E8%BA%AB%E4%BB%BD%E7%BB%84)
+    async fn create_guild_role(&self, params: CreateGuildRole) -> Result<GuildRoleID>;
+
+    /// [修改频道身份组](https://docs.go-cqhttp.org/guild/api/#%E4%BF%AE%E6%94%B9%E9%A2%91%E9%81%93%E8%BA%AB%E4%BB%BD%E7%BB%84)
+    async fn update_guild_role(&self, params: UpdateGuildRole) -> Result<()>;
+
+    /// [删除频道身份组](https://docs.go-cqhttp.org/guild/api/#%E5%88%A0%E9%99%A4%E9%A2%91%E9%81%93%E8%BA%AB%E4%BB%BD%E7%BB%84)
+    async fn delete_guild_role(&self, params: DeleteGuildRole) -> Result<()>;
+
+    /// [设置用户在频道中的身份组](https://docs.go-cqhttp.org/guild/api/#%E8%AE%BE%E7%BD%AE%E7%94%A8%E6%88%B7%E5%9C%A8%E9%A2%91%E9%81%93%E4%B8%AD%E7%9A%84%E8%BA%AB%E4%BB%BD%E7%BB%84)
+    async fn set_guild_member_role(&self, params: SetGuildMemberRole) -> Result<()>;
+}
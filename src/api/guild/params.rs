@@ -0,0 +1,166 @@
+//! 频道API的参数，用法同`api::params`
+
+use super::data::*;
+use crate::api::params::{params, Action};
+use crate::message::Message;
+use serde::Serialize;
+
+params! {
+    /// [获取频道系统内BOT的资料](https://docs.go-cqhttp.org/guild/api/#%E8%8E%B7%E5%8F%96%E9%A2%91%E9%81%93%E7%B3%BB%E7%BB%9F%E5%86%85bot%E7%9A%84%E8%B5%84%E6%96%99)
+    #[derive(Default)]
+    GetGuildServiceProfile("get_guild_service_profile") -> GuildServiceProfile {
+        required {}
+    }
+
+    /// [获取频道列表](https://docs.go-cqhttp.org/guild/api/#%E8%8E%B7%E5%8F%96%E9%A2%91%E9%81%93%E5%88%97%E8%A1%A8)
+    #[derive(Default)]
+    GetGuildList("get_guild_list") -> Vec<GuildInfo> {
+        required {}
+    }
+
+    /// [通过访客获取频道元数据](https://docs.go-cqhttp.org/guild/api/#%E9%80%9A%E8%BF%87%E8%AE%BF%E5%AE%A2%E8%8E%B7%E5%8F%96%E9%A2%91%E9%81%93%E5%85%83%E6%95%B0%E6%8D%AE)
+    GetGuildMetaByGuest("get_guild_meta_by_guest") -> GuildMeta {
+        required {
+            /// 频道ID
+            guild_id: String,
+        }
+    }
+
+    /// [获取子频道列表](https://docs.go-cqhttp.org/guild/api/#%E8%8E%B7%E5%8F%96%E5%AD%90%E9%A2%91%E9%81%93%E5%88%97%E8%A1%A8)
+    GetGuildChannelList("get_guild_channel_list") -> Vec<ChannelInfo> {
+        required {
+            /// 频道ID
+            guild_id: String,
+        }
+        optional {
+            /// 是否无视缓存
+            no_cache: bool,
+        }
+    }
+
+    /// [获取频道成员列表](https://docs.go-cqhttp.org/guild/api/#%E8%8E%B7%E5%8F%96%E9%A2%91%E9%81%93%E6%88%90%E5%91%98%E5%88%97%E8%A1%A8)
+    GetGuildMemberList("get_guild_member_list") -> GuildMemberList {
+        required {
+            /// 频道ID
+            guild_id: String,
+        }
+        optional {
+            /// 翻页Token，首次调用时不设置，之后传入上一次返回的`next_token`
+            next_token: String,
+        }
+    }
+
+    /// [单独获取频道成员信息](https://docs.go-cqhttp.org/guild/api/#%E5%8D%95%E7%8B%AC%E8%8E%B7%E5%8F%96%E9%A2%91%E9%81%93%E6%88%90%E5%91%98%E4%BF%A1%E6%81%AF)
+    GetGuildMemberProfile("get_guild_member_profile") -> GuildMemberProfile {
+        required {
+            /// 频道ID
+            guild_id: String,
+            /// 用户ID
+            user_id: String,
+        }
+    }
+
+    /// [获取频道消息](https://docs.go-cqhttp.org/guild/api/#%E8%8E%B7%E5%8F%96%E9%A2%91%E9%81%93%E6%B6%88%E6%81%AF)
+    GetGuildMsg("get_guild_msg") -> GuildMsg {
+        required {
+            /// 消息ID
+            message_id: String,
+        }
+        optional {
+            /// 是否不使用缓存
+            no_cache: bool,
+        }
+    }
+
+    /// [发送信息到子频道](https://docs.go-cqhttp.org/guild/api/#%E5%8F%91%E9%80%81%E4%BF%A1%E6%81%AF%E5%88%B0%E5%AD%90%E9%A2%91%E9%81%93)
+    SendGuildChannelMsg("send_guild_channel_msg") -> GuildMessageID {
+        required {
+            /// 频道ID
+            guild_id: String,
+            /// 子频道ID
+            channel_id: String,
+            /// 消息内容
+            message: Message,
+        }
+    }
+
+    /// [获取话题频道帖子](https://docs.go-cqhttp.org/guild/api/#%E8%8E%B7%E5%8F%96%E8%AF%9D%E9%A2%98%E9%A2%91%E9%81%93%E5%B8%96%E5%AD%90)
+    GetTopicChannelFeeds("get_topic_channel_feeds") -> Vec<FeedInfo> {
+        required {
+            /// 频道ID
+            guild_id: String,
+            /// 子频道ID
+            channel_id: String,
+        }
+    }
+
+    /// [获取频道身份组列表](https://docs.go-cqhttp.org/guild/api/#%E8%8E%B7%E5%8F%96%E9%A2%91%E9%81%93%E8%BA%AB%E4%BB%BD%E7%BB%84%E5%88%97%E8%A1%A8)
+    GetGuildRoles("get_guild_roles") -> Vec<GuildRole> {
+        required {
+            /// 频道ID
+            guild_id: String,
+        }
+    }
+
+    /// [创建频道身份组](https://docs.go-cqhttp.org/guild/api/#%E5%88%9B%E5%BB%BA%E9%A2%91%E9%81%93%E8%BA%AB%E4%BB%BD%E7%BB%84)
+    CreateGuildRole("create_guild_role") -> GuildRoleID {
+        required {
+            /// 频道ID
+            guild_id: String,
+            /// 身份组名称
+            name: String,
+        }
+        optional {
+            /// 身份组颜色，ARGB
+            color: i64,
+            /// 是否在成员列表中单独展示
+            independent: bool,
+            /// 初始成员ID列表
+            initial_users: Vec<String>,
+        }
+    }
+
+    /// [修改频道身份组](https://docs.go-cqhttp.org/guild/api/#%E4%BF%AE%E6%94%B9%E9%A2%91%E9%81%93%E8%BA%AB%E4%BB%BD%E7%BB%84)
+    UpdateGuildRole("update_guild_role") -> () {
+        required {
+            /// 频道ID
+            guild_id: String,
+            /// 身份组ID
+            role_id: String,
+        }
+        optional {
+            /// 身份组名称
+            name: String,
+            /// 身份组颜色，ARGB
+            color: i64,
+            /// 是否在成员列表中单独展示
+            independent: bool,
+        }
+    }
+
+    /// [删除频道身份组](https://docs.go-cqhttp.org/guild/api/#%E5%88%A0%E9%99%A4%E9%A2%91%E9%81%93%E8%BA%AB%E4%BB%BD%E7%BB%84)
+    DeleteGuildRole("delete_guild_role") -> () {
+        required {
+            /// 频道ID
+            guild_id: String,
+            /// 身份组ID
+            role_id: String,
+        }
+    }
+
+    /// [设置用户在频道中的身份组](https://docs.go-cqhttp.org/guild/api/#%E8%AE%BE%E7%BD%AE%E7%94%A8%E6%88%B7%E5%9C%A8%E9%A2%91%E9%81%93%E4%B8%AD%E7%9A%84%E8%BA%AB%E4%BB%BD%E7%BB%84)
+    SetGuildMemberRole("set_guild_member_role") -> () {
+        required {
+            /// 频道ID
+            guild_id: String,
+            /// 身份组ID
+            role_id: String,
+        }
+        optional {
+            /// `true`为添加身份组，`false`为移除
+            set: bool,
+            /// 用户ID列表
+            users: Vec<String>,
+        }
+    }
+}
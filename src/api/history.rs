@@ -0,0 +1,122 @@
+//! 按`message_seq`向前翻页获取群消息历史记录
+
+use super::data::Msg;
+use super::params::GetGroupMsgHistory;
+use super::GoCqhttpAPI;
+use crate::Result;
+use futures::stream::{self, Stream};
+use std::collections::VecDeque;
+use std::future::Future;
+
+/// 从`message_seq`（`None`表示最新的消息）开始，由新到旧逐条返回群`group_id`的消息记录
+///
+/// 每次调用`get_group_msg_history`获取一页，直到没有更早的消息。请求出错时返回错误并结束
+pub fn group_msg_history<A>(
+    api: &A,
+    group_id: i64,
+    message_seq: Option<i64>,
+) -> impl Stream<Item = Result<Msg>> + '_
+where
+    A: GoCqhttpAPI + ?Sized,
+{
+    pages(message_seq, move |seq| async move {
+        let params = GetGroupMsgHistory {
+            group_id,
+            message_seq: seq,
+        };
+        Ok(api.get_group_msg_history(params).await?.messages)
+    })
+}
+
+struct State<F> {
+    fetch: F,
+    /// 下一页的起始序号，`None`表示已经结束
+    next: Option<Option<i64>>,
+    /// 当前页中尚未返回的消息，由新到旧
+    buffer: VecDeque<Msg>,
+}
+
+fn pages<F, Fut>(message_seq: Option<i64>, fetch: F) -> impl Stream<Item = Result<Msg>>
+where
+    F: FnMut(Option<i64>) -> Fut,
+    Fut: Future<Output = Result<Vec<Msg>>>,
+{
+    let state = State {
+        fetch,
+        next: Some(message_seq),
+        buffer: VecDeque::new(),
+    };
+    stream::unfold(state, |mut state| async move {
+        loop {
+            if let Some(msg) = state.buffer.pop_front() {
+                return Some((Ok(msg), state));
+            }
+            let seq = state.next?;
+            let mut page = match (state.fetch)(seq).await {
+                Ok(page) => page,
+                Err(e) => {
+                    state.next = None;
+                    return Some((Err(e), state));
+                }
+            };
+            // 某些版本的go-cqhttp会包含起始序号对应的消息，翻页时需要跳过已返回的消息
+            if let Some(seq) = seq {
+                page.retain(|msg| msg.message_seq <= seq);
+            }
+            page.sort_by_key(|msg| std::cmp::Reverse(msg.message_seq));
+            state.next = match page.last() {
+                Some(oldest) if oldest.message_seq > 1 => Some(Some(oldest.message_seq - 1)),
+                _ => None,
+            };
+            state.buffer.extend(page);
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::pages;
+    use crate::api::data::MsgHistory;
+    use futures::StreamExt;
+
+    fn page(seqs: std::ops::RangeInclusive<i64>) -> MsgHistory {
+        let messages: Vec<String> = seqs
+            .map(|seq| {
+                format!(
+                    r#"{{"message_type":"group","message_seq":{},"sender":{{"user_id":1}},"time":0,"message":"{}"}}"#,
+                    seq, seq
+                )
+            })
+            .collect();
+        serde_json::from_str(&format!(r#"{{"messages":[{}]}}"#, messages.join(","))).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_group_msg_history_pages() {
+        let mut requested = Vec::new();
+        let seqs: Vec<i64> = pages(None, |seq| {
+            requested.push(seq);
+            let page = match seq {
+                None => page(4..=6),
+                Some(s) => page(1.max(s - 2)..=s),
+            };
+            async move { Ok(page.messages) }
+        })
+        .map(|msg| msg.unwrap().message_seq)
+        .collect()
+        .await;
+        assert_eq!(seqs, vec![6, 5, 4, 3, 2, 1]);
+        assert_eq!(requested, vec![None, Some(3)]);
+    }
+
+    #[tokio::test]
+    async fn test_group_msg_history_error() {
+        let results: Vec<_> = pages(Some(10), |_| async {
+            Err(std::io::Error::other("boom").into())
+        })
+        .collect()
+        .await;
+        assert_eq!(results.len(), 1);
+        assert!(results[0].is_err());
+    }
+}
@@ -0,0 +1,350 @@
+//! 发送消息的限速，避免短时间内大量发送触发风控
+//!
+//! 与go-cqhttp的[限速调用](https://docs.go-cqhttp.org/api/#%E9%99%90%E9%80%9F%E8%B0%83%E7%94%A8)不同，
+//! 这里的限速在客户端进行，可以分别限制每个群、每个私聊对象和全局的发送频率。超出频率的发送会排队等待，而不是被丢弃
+
+use super::mode::Outcome;
+use super::service::{Layer, Service};
+use super::target::Target;
+use crate::Result;
+use async_trait::async_trait;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// 令牌桶的容量和恢复速度：最多连续发送`burst`条，之后每`interval`恢复一条
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Quota {
+    burst: u32,
+    interval: Duration,
+}
+
+impl Quota {
+    /// `burst`为0时按1处理
+    pub fn new(burst: u32, interval: Duration) -> Self {
+        Self {
+            burst: burst.max(1),
+            interval,
+        }
+    }
+
+    pub fn burst(&self) -> u32 {
+        self.burst
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+}
+
+/// 令牌桶，以桶被填满的时刻表示：`full_at`之前桶中的令牌数为`burst - (full_at - now) / interval`
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    full_at: Instant,
+}
+
+impl Bucket {
+    /// 可以取出一个令牌的最早时刻
+    fn available_at(&self, quota: &Quota) -> Instant {
+        let tolerance = quota.interval * (quota.burst - 1);
+        self.full_at.checked_sub(tolerance).unwrap_or(self.full_at)
+    }
+
+    /// 在`at`取出一个令牌
+    fn take(&mut self, quota: &Quota, at: Instant) {
+        self.full_at = self.full_at.max(at) + quota.interval;
+    }
+}
+
+/// 排队中的发送数量
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct QueueMetrics {
+    /// 排队中的发送总数
+    pub total: usize,
+    /// 每个群中排队的发送数，不含为0的群
+    pub groups: HashMap<i64, usize>,
+    /// 每个私聊对象排队的发送数，不含为0的对象
+    pub users: HashMap<i64, usize>,
+}
+
+impl QueueMetrics {
+    fn change(&mut self, target: &Target, increase: bool) {
+        let count = match target {
+            Target::Group { group_id } => Some(self.groups.entry(*group_id).or_default()),
+            Target::Private { user_id, .. } => Some(self.users.entry(*user_id).or_default()),
+            Target::GuildChannel { .. } => None,
+        };
+        if increase {
+            self.total += 1;
+            if let Some(count) = count {
+                *count += 1;
+            }
+        } else {
+            self.total -= 1;
+            if let Some(count) = count {
+                *count -= 1;
+            }
+            self.groups.retain(|_, n| *n > 0);
+            self.users.retain(|_, n| *n > 0);
+        }
+    }
+}
+
+#[derive(Default)]
+struct State {
+    global: Option<Bucket>,
+    groups: HashMap<i64, Bucket>,
+    users: HashMap<i64, Bucket>,
+    metrics: QueueMetrics,
+}
+
+#[derive(Debug, Default, Clone)]
+struct Quotas {
+    global: Option<Quota>,
+    group: Option<Quota>,
+    private: Option<Quota>,
+}
+
+/// 发送消息的令牌桶限速器，克隆后共享同一组令牌桶
+///
+/// ```
+/// use rust_gocqhttp::api::limit::{Quota, RateLimiter};
+/// use std::time::Duration;
+///
+/// // 全局每秒最多5条，每个群连续3条后每2秒1条
+/// let limiter = RateLimiter::new()
+///     .global(Quota::new(5, Duration::from_millis(200)))
+///     .per_group(Quota::new(3, Duration::from_secs(2)));
+/// assert_eq!(limiter.metrics().total, 0);
+/// ```
+#[derive(Clone, Default)]
+pub struct RateLimiter {
+    quotas: Quotas,
+    state: Arc<Mutex<State>>,
+}
+
+impl RateLimiter {
+    /// 不限速的限速器，通过`global`, `per_group`, `per_user`设置限制
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 所有发送共享的限制
+    pub fn global(mut self, quota: Quota) -> Self {
+        self.quotas.global = Some(quota);
+        self
+    }
+
+    /// 每个群单独的限制
+    pub fn per_group(mut self, quota: Quota) -> Self {
+        self.quotas.group = Some(quota);
+        self
+    }
+
+    /// 每个私聊对象单独的限制
+    pub fn per_user(mut self, quota: Quota) -> Self {
+        self.quotas.private = Some(quota);
+        self
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 当前排队中的发送数量
+    pub fn metrics(&self) -> QueueMetrics {
+        self.state().metrics.clone()
+    }
+
+    /// 为发送到`target`的消息预留令牌，返回可以发送的时刻
+    ///
+    /// 预留按调用顺序进行，所以同一个目标的消息按调用顺序发送
+    fn reserve(&self, target: &Target) -> Instant {
+        let now = Instant::now();
+        let mut state = self.state();
+        let state = &mut *state;
+        // 已经填满的桶与不存在的桶等价
+        state.groups.retain(|_, b| b.full_at > now);
+        state.users.retain(|_, b| b.full_at > now);
+        let empty = Bucket { full_at: now };
+        let mut buckets: Vec<(&mut Bucket, &Quota)> = Vec::with_capacity(2);
+        if let Some(quota) = &self.quotas.global {
+            buckets.push((state.global.get_or_insert(empty), quota));
+        }
+        match (target, &self.quotas.group, &self.quotas.private) {
+            (Target::Group { group_id }, Some(quota), _) => {
+                buckets.push((state.groups.entry(*group_id).or_insert(empty), quota));
+            }
+            (Target::Private { user_id, .. }, _, Some(quota)) => {
+                buckets.push((state.users.entry(*user_id).or_insert(empty), quota));
+            }
+            _ => {}
+        }
+        let at = buckets
+            .iter()
+            .map(|(bucket, quota)| bucket.available_at(quota))
+            .fold(now, Instant::max);
+        for (bucket, quota) in buckets {
+            bucket.take(quota, at);
+        }
+        at
+    }
+
+    /// 等待直到可以向`target`发送一条消息
+    pub async fn acquire(&self, target: &Target) {
+        let at = self.reserve(target);
+        if at <= Instant::now() {
+            return;
+        }
+        self.state().metrics.change(target, true);
+        let _queued = Queued {
+            limiter: self,
+            target,
+        };
+        tokio::time::sleep_until(at.into()).await;
+    }
+
+    /// 如果`action`是发送消息的API，等待直到可以发送
+    pub async fn acquire_for(&self, action: &str, params: &Value) {
+        if let Some(target) = send_target(action, params) {
+            self.acquire(&target).await;
+        }
+    }
+}
+
+impl<S> Layer<S> for RateLimiter {
+    type Service = Limited<S>;
+
+    fn layer(&self, inner: S) -> Limited<S> {
+        Limited {
+            inner,
+            limiter: self.clone(),
+        }
+    }
+}
+
+/// 作为中间件使用时，`RateLimiter`包装后的`Service`
+pub struct Limited<S> {
+    inner: S,
+    limiter: RateLimiter,
+}
+
+#[async_trait]
+impl<S: Service> Service for Limited<S> {
+    async fn call(&self, action: &str, params: Value) -> Result<Outcome<Value>> {
+        self.limiter.acquire_for(action, &params).await;
+        self.inner.call(action, params).await
+    }
+}
+
+/// 排队中的发送，离开队列（包括被取消）时更新统计
+struct Queued<'a> {
+    limiter: &'a RateLimiter,
+    target: &'a Target,
+}
+
+impl Drop for Queued<'_> {
+    fn drop(&mut self) {
+        self.limiter.state().metrics.change(self.target, false);
+    }
+}
+
+/// 发送消息的API的发送目标，不是发送消息的API时返回`None`
+pub fn send_target(action: &str, params: &Value) -> Option<Target> {
+    let user_id = params["user_id"].as_i64();
+    let group_id = params["group_id"].as_i64();
+    let private = |user_id: Option<i64>| {
+        Some(Target::Private {
+            user_id: user_id?,
+            via_group: group_id,
+        })
+    };
+    match action {
+        "send_private_msg" | "send_private_forward_msg" => private(user_id),
+        "send_group_msg" | "send_group_forward_msg" => Some(Target::group(group_id?)),
+        "send_msg" | "send_forward_msg" => match params["message_type"].as_str() {
+            Some("private") => private(user_id),
+            Some("group") => Some(Target::group(group_id?)),
+            _ if group_id.is_some() => Some(Target::group(group_id?)),
+            _ => private(user_id),
+        },
+        "send_guild_channel_msg" => Some(Target::guild_channel(
+            params["guild_id"].as_str()?,
+            params["channel_id"].as_str()?,
+        )),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{send_target, Quota, RateLimiter};
+    use crate::api::target::Target;
+    use serde_json::json;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_send_target() {
+        assert_eq!(
+            send_target("send_msg", &json!({ "group_id": 1, "message": "a" })),
+            Some(Target::group(1))
+        );
+        assert_eq!(
+            send_target("send_private_msg", &json!({ "user_id": 2, "group_id": 1 })),
+            Some(Target::temp(2, 1))
+        );
+        assert_eq!(send_target("get_status", &json!({})), None);
+    }
+
+    #[test]
+    fn test_quota_zero_burst() {
+        let quota = Quota::new(0, Duration::from_millis(100));
+        assert_eq!(quota.burst(), 1);
+        let limiter = RateLimiter::new().per_group(quota);
+        let now = Instant::now();
+        assert!(limiter.reserve(&Target::group(1)) <= now + Duration::from_millis(50));
+        assert!(limiter.reserve(&Target::group(1)) >= now + quota.interval());
+    }
+
+    #[test]
+    fn test_rate_limiter_reserve() {
+        let interval = Duration::from_secs(10);
+        let limiter = RateLimiter::new()
+            .global(Quota::new(3, interval))
+            .per_group(Quota::new(2, interval));
+        let now = Instant::now();
+        let group = Target::group(1);
+        // 群1连续2条后需要等待，全局仍有余量
+        assert!(limiter.reserve(&group) <= now + Duration::from_millis(100));
+        assert!(limiter.reserve(&group) <= now + Duration::from_millis(100));
+        assert!(limiter.reserve(&group) >= now + interval);
+        // 全局的3个令牌已被预留完
+        assert!(limiter.reserve(&Target::group(2)) >= now + interval);
+        // 私聊没有单独限制，只受全局限制
+        assert!(limiter.reserve(&Target::private(3)) >= now + interval * 2);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limiter_queue() {
+        let limiter = RateLimiter::new().per_user(Quota::new(1, Duration::from_millis(50)));
+        let target = Target::private(1);
+        limiter.acquire(&target).await;
+        let start = Instant::now();
+        let waiting = tokio::spawn({
+            let limiter = limiter.clone();
+            async move {
+                limiter.acquire(&Target::private(1)).await;
+                limiter.acquire(&Target::private(1)).await;
+            }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let metrics = limiter.metrics();
+        assert_eq!(metrics.total, 1);
+        assert_eq!(metrics.users[&1], 1);
+        waiting.await.unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(90));
+        assert_eq!(limiter.metrics().total, 0);
+        assert!(limiter.metrics().users.is_empty());
+    }
+}
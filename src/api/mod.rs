@@ -1,13 +1,24 @@
+pub mod client;
 pub mod data;
+pub mod guild;
+pub mod history;
+pub mod limit;
+pub mod mode;
+pub mod params;
+pub mod retry;
+pub mod roster;
+pub mod sandbox;
+pub mod service;
+pub mod target;
 
-use crate::message::cq_code::code::Node;
-use crate::message::{Message, MessageType};
 use crate::Result;
 use async_trait::async_trait;
 use data::*;
+use params::*;
 use reqwest::{Response, StatusCode};
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Deserializer};
+use std::fmt;
 use std::io::{Error, ErrorKind};
 
 /// [GoCqhttp API](https://docs.go-cqhttp.org/api/#api)
@@ -17,29 +28,22 @@ pub trait GoCqhttpAPI {
     async fn get_login_info(&self) -> Result<LoginInfo>;
 
     /// [设置登录号资料](https://docs.go-cqhttp.org/api/#%E8%AE%BE%E7%BD%AE%E7%99%BB%E5%BD%95%E5%8F%B7%E8%B5%84%E6%96%99)
-    async fn set_qq_profile(
-        &self,
-        nickname: String,
-        company: String,
-        email: String,
-        college: String,
-        personal_note: String,
-    ) -> Result<()>;
+    async fn set_qq_profile(&self, params: SetQqProfile) -> Result<()>;
 
     /// [获取企点账号信息](https://docs.go-cqhttp.org/api/#%E8%8E%B7%E5%8F%96%E4%BC%81%E7%82%B9%E8%B4%A6%E5%8F%B7%E4%BF%A1%E6%81%AF)
     async fn qidian_get_account_info(&self) -> Result<String>;
 
     /// [获取在线机型](https://docs.go-cqhttp.org/api/#%E8%8E%B7%E5%8F%96%E5%9C%A8%E7%BA%BF%E6%9C%BA%E5%9E%8B)
-    async fn get_model_show(&self) -> Result<ModelShowVariants>;
+    async fn get_model_show(&self, params: GetModelShow) -> Result<ModelShowVariants>;
 
     /// [设置在线机型](https://docs.go-cqhttp.org/api/#%E8%AE%BE%E7%BD%AE%E5%9C%A8%E7%BA%BF%E6%9C%BA%E5%9E%8B)
-    async fn set_model_show(&self, model: String, model_show: String) -> Result<()>;
+    async fn set_model_show(&self, params: SetModelShow) -> Result<()>;
 
     /// [获取当前账号在线客户端列表](https://docs.go-cqhttp.org/api/#%E8%8E%B7%E5%8F%96%E5%BD%93%E5%89%8D%E8%B4%A6%E5%8F%B7%E5%9C%A8%E7%BA%BF%E5%AE%A2%E6%88%B7%E7%AB%AF%E5%88%97%E8%A1%A8)
-    async fn get_online_clients(&self, no_cache: bool) -> Result<ClientDevices>;
+    async fn get_online_clients(&self, params: GetOnlineClients) -> Result<ClientDevices>;
 
     /// [获取陌生人信息](https://docs.go-cqhttp.org/api/#%E8%8E%B7%E5%8F%96%E9%99%8C%E7%94%9F%E4%BA%BA%E4%BF%A1%E6%81%AF)
-    async fn get_stranger_info(&self, user_id: i64, no_cache: bool) -> Result<StrangerInfo>;
+    async fn get_stranger_info(&self, params: GetStrangerInfo) -> Result<StrangerInfo>;
 
     /// [获取好友列表](https://docs.go-cqhttp.org/api/#%E8%8E%B7%E5%8F%96%E5%A5%BD%E5%8F%8B%E5%88%97%E8%A1%A8)
     async fn get_friend_list(&self) -> Result<Vec<Friend>>;
@@ -48,277 +52,208 @@ pub trait GoCqhttpAPI {
     async fn get_unidirectional_friend_list(&self) -> Result<Vec<UnidirectionalFriend>>;
 
     /// [删除好友](https://docs.go-cqhttp.org/api/#%E5%88%A0%E9%99%A4%E5%A5%BD%E5%8F%8B)
-    async fn delete_friend(&self, user_id: i64) -> Result<()>;
+    async fn delete_friend(&self, params: DeleteFriend) -> Result<()>;
 
     /// [删除单向好友](https://docs.go-cqhttp.org/api/#%E5%88%A0%E9%99%A4%E5%8D%95%E5%90%91%E5%A5%BD%E5%8F%8B)
-    async fn delete_unidirectional_friend(&self, user_id: i64) -> Result<()>;
+    async fn delete_unidirectional_friend(&self, params: DeleteUnidirectionalFriend) -> Result<()>;
 
     /// [发送私聊消息](https://docs.go-cqhttp.org/api/#%E5%8F%91%E9%80%81%E7%A7%81%E8%81%8A%E6%B6%88%E6%81%AF)
-    async fn send_private_msg(
-        &self,
-        user_id: i64,
-        group_id: i64,
-        message: Message,
-        auto_escape: bool,
-    ) -> Result<MessageID>;
+    async fn send_private_msg(&self, params: SendPrivateMsg) -> Result<MessageID>;
 
     /// [发送群聊消息](https://docs.go-cqhttp.org/api/#%E5%8F%91%E9%80%81%E7%BE%A4%E8%81%8A%E6%B6%88%E6%81%AF)
-    async fn send_group_msg(
-        &self,
-        group_id: i64,
-        message: Message,
-        auto_escape: bool,
-    ) -> Result<MessageID>;
+    async fn send_group_msg(&self, params: SendGroupMsg) -> Result<MessageID>;
 
     /// [发送消息](https://docs.go-cqhttp.org/api/#%E5%8F%91%E9%80%81%E6%B6%88%E6%81%AF)
-    async fn send_msg(
-        &self,
-        message_type: MessageType,
-        user_id: i64,
-        group_id: i64,
-        message: Message,
-        auto_escape: bool,
-    ) -> Result<MessageID>;
+    async fn send_msg(&self, params: SendMsg) -> Result<MessageID>;
 
     /// [获取消息](https://docs.go-cqhttp.org/api/#%E8%8E%B7%E5%8F%96%E6%B6%88%E6%81%AF)
     ///
     /// **注意**：该API要求go-cqhttp版本在v0.9.37及以上，不满足版本要求将导致反序列化出错
     ///
     /// 提示：运行时不会检查版本号
-    async fn get_msg(&self, message_id: i32) -> Result<Msg>;
+    async fn get_msg(&self, params: GetMsg) -> Result<Msg>;
 
     /// [撤回消息](https://docs.go-cqhttp.org/api/#%E6%92%A4%E5%9B%9E%E6%B6%88%E6%81%AF)
-    async fn delete_msg(&self, message_id: i32) -> Result<()>;
+    async fn delete_msg(&self, params: DeleteMsg) -> Result<()>;
 
     /// [标记消息已读](https://docs.go-cqhttp.org/api/#%E6%A0%87%E8%AE%B0%E6%B6%88%E6%81%AF%E5%B7%B2%E8%AF%BB)
-    async fn mark_msg_as_read(&self, message_id: i32) -> Result<()>;
+    async fn mark_msg_as_read(&self, params: MarkMsgAsRead) -> Result<()>;
 
     /// [获取合并转发内容](https://docs.go-cqhttp.org/api/#%E8%8E%B7%E5%8F%96%E5%90%88%E5%B9%B6%E8%BD%AC%E5%8F%91%E5%86%85%E5%AE%B9)
-    async fn get_forward_msg(&self, message_id: String) -> Result<Vec<ForwardMessage>>;
+    async fn get_forward_msg(&self, params: GetForwardMsg) -> Result<Vec<ForwardedMessage>>;
 
     /// [发送合并转发(群聊)](https://docs.go-cqhttp.org/api/#%E5%8F%91%E9%80%81%E5%90%88%E5%B9%B6%E8%BD%AC%E5%8F%91-%E7%BE%A4%E8%81%8A)
-    async fn send_group_forward_msg(
+    async fn send_group_forward_msg(&self, params: SendGroupForwardMsg)
+        -> Result<ForwardMessageID>;
+
+    /// [发送合并转发(好友)](https://docs.go-cqhttp.org/api/#%E5%8F%91%E9%80%81%E5%90%88%E5%B9%B6%E8%BD%AC%E5%8F%91-%E5%A5%BD%E5%8F%8B)
+    async fn send_private_forward_msg(
         &self,
-        group_id: i64,
-        messages: Node,
+        params: SendPrivateForwardMsg,
     ) -> Result<ForwardMessageID>;
 
-    /// [发送合并转发(好友)](https://docs.go-cqhttp.org/api/#%E5%8F%91%E9%80%81%E5%90%88%E5%B9%B6%E8%BD%AC%E5%8F%91-%E5%A5%BD%E5%8F%8B)
-    async fn send_private_forward_msg(&self) -> Result<ForwardMessageID>;
+    /// [发送合并转发](https://docs.go-cqhttp.org/api/#%E5%8F%91%E9%80%81%E5%90%88%E5%B9%B6%E8%BD%AC%E5%8F%91)
+    async fn send_forward_msg(&self, params: SendForwardMsg) -> Result<ForwardMessageID>;
 
     /// [获取群消息历史记录](https://docs.go-cqhttp.org/api/#%E8%8E%B7%E5%8F%96%E7%BE%A4%E6%B6%88%E6%81%AF%E5%8E%86%E5%8F%B2%E8%AE%B0%E5%BD%95)
-    async fn get_group_msg_history(&self, message_seq: i64, group_id: i64) -> Result<Vec<Message>>;
+    ///
+    /// 返回`message_seq`及之前的消息，未设置`message_seq`时从最新的消息开始。
+    /// 向前翻页可以使用`history::group_msg_history`
+    async fn get_group_msg_history(&self, params: GetGroupMsgHistory) -> Result<MsgHistory>;
 
     /// [获取图片信息](https://docs.go-cqhttp.org/api/#%E8%8E%B7%E5%8F%96%E5%9B%BE%E7%89%87%E4%BF%A1%E6%81%AF)
-    async fn get_image(&self, file: String) -> Result<Image>;
+    async fn get_image(&self, params: GetImage) -> Result<Image>;
 
     /// [检查是否可以发送图片](https://docs.go-cqhttp.org/api/#%E6%A3%80%E6%9F%A5%E6%98%AF%E5%90%A6%E5%8F%AF%E4%BB%A5%E5%8F%91%E9%80%81%E5%9B%BE%E7%89%87)
     async fn can_send_image(&self) -> Result<CanSend>;
 
     /// [图片OCR](https://docs.go-cqhttp.org/api/#%E5%9B%BE%E7%89%87-ocr)
-    async fn ocr_image(&self, image: String) -> Result<OCRImage>;
+    async fn ocr_image(&self, params: OcrImage) -> Result<OCRImage>;
 
     /// [获取语音](https://docs.go-cqhttp.org/api/#%E8%8E%B7%E5%8F%96%E8%AF%AD%E9%9F%B3)
-    async fn get_record(&self, file: String, out_format: String) -> Result<Record>;
+    async fn get_record(&self, params: GetRecord) -> Result<Record>;
 
     /// [检查是否可以发送语音](https://docs.go-cqhttp.org/api/#%E6%A3%80%E6%9F%A5%E6%98%AF%E5%90%A6%E5%8F%AF%E4%BB%A5%E5%8F%91%E9%80%81%E8%AF%AD%E9%9F%B3)
     async fn can_send_record(&self) -> Result<CanSend>;
 
     /// [处理加好友请求](https://docs.go-cqhttp.org/api/#%E5%A4%84%E7%90%86%E5%8A%A0%E5%A5%BD%E5%8F%8B%E8%AF%B7%E6%B1%82)
-    async fn set_friend_add_request(
-        &self,
-        flag: String,
-        approve: bool,
-        remark: String,
-    ) -> Result<()>;
+    async fn set_friend_add_request(&self, params: SetFriendAddRequest) -> Result<()>;
 
     /// [处理加群请求／邀请](https://docs.go-cqhttp.org/api/#%E5%A4%84%E7%90%86%E5%8A%A0%E7%BE%A4%E8%AF%B7%E6%B1%82-%E9%82%80%E8%AF%B7)
-    async fn set_group_add_request(
-        &self,
-        flag: String,
-        sub_type: String,
-        approve: bool,
-        reason: String,
-    ) -> Result<()>;
+    async fn set_group_add_request(&self, params: SetGroupAddRequest) -> Result<()>;
 
     /// [获取群信息](https://docs.go-cqhttp.org/api/#%E8%8E%B7%E5%8F%96%E7%BE%A4%E4%BF%A1%E6%81%AF)
     ///
     /// **注意**：在 go-cqhttp-v0.9.40之前的版本中，该API不能获取陌生群消息
-    async fn get_group_info(&self, group_id: i64, no_cache: bool) -> Result<GroupInfo>;
+    async fn get_group_info(&self, params: GetGroupInfo) -> Result<GroupInfo>;
 
     /// [获取群列表](https://docs.go-cqhttp.org/api/#%E8%8E%B7%E5%8F%96%E7%BE%A4%E5%88%97%E8%A1%A8)
-    async fn get_group_list(&self, no_cache: bool) -> Result<Vec<GroupInfo>>;
+    async fn get_group_list(&self, params: GetGroupList) -> Result<Vec<GroupInfo>>;
 
     /// [获取群成员信息](https://docs.go-cqhttp.org/api/#%E8%8E%B7%E5%8F%96%E7%BE%A4%E6%88%90%E5%91%98%E4%BF%A1%E6%81%AF)
-    async fn get_group_member_info(
-        &self,
-        group_id: i64,
-        user_id: i64,
-        no_cache: bool,
-    ) -> Result<GroupMemberInfo>;
+    async fn get_group_member_info(&self, params: GetGroupMemberInfo) -> Result<GroupMemberInfo>;
 
     /// [获取群成员列表](https://docs.go-cqhttp.org/api/#%E8%8E%B7%E5%8F%96%E7%BE%A4%E6%88%90%E5%91%98%E5%88%97%E8%A1%A8)
     async fn get_group_member_list(
         &self,
-        group_id: i64,
-        no_cache: bool,
+        params: GetGroupMemberList,
     ) -> Result<Vec<GroupMemberInfo>>;
 
     /// [获取群荣誉信息](https://docs.go-cqhttp.org/api/#%E8%8E%B7%E5%8F%96%E7%BE%A4%E8%8D%A3%E8%AA%89%E4%BF%A1%E6%81%AF)
-    async fn get_group_honor_info(
-        &self,
-        group_id: i64,
-        honor_type: GroupHonorType,
-    ) -> Result<GroupHonorInfo>;
+    async fn get_group_honor_info(&self, params: GetGroupHonorInfo) -> Result<GroupHonorInfo>;
 
     /// [获取群系统消息](https://docs.go-cqhttp.org/api/#%E8%8E%B7%E5%8F%96%E7%BE%A4%E7%B3%BB%E7%BB%9F%E6%B6%88%E6%81%AF)
     ///
     /// **注意**：在 go-cqhttp-v0.9.40 之前的版本中，无法获取被过滤的群系统消息
-    async fn get_group_system_msg(&self, group_id: i64) -> Result<GroupSystemMsg>;
+    async fn get_group_system_msg(&self) -> Result<GroupSystemMsg>;
 
     /// [获取精华消息列表](https://docs.go-cqhttp.org/api/#%E8%8E%B7%E5%8F%96%E7%B2%BE%E5%8D%8E%E6%B6%88%E6%81%AF%E5%88%97%E8%A1%A8)
-    async fn get_essence_msg_list(&self, group_id: i64) -> Result<Vec<EssenceMsg>>;
+    async fn get_essence_msg_list(&self, params: GetEssenceMsgList) -> Result<Vec<EssenceMsg>>;
 
     /// [获取群 @全体成员 剩余次数](https://docs.go-cqhttp.org/api/#%E8%8E%B7%E5%8F%96%E7%BE%A4-%E5%85%A8%E4%BD%93%E6%88%90%E5%91%98-%E5%89%A9%E4%BD%99%E6%AC%A1%E6%95%B0)
-    async fn get_group_at_all_remain(&self, group_id: i64) -> Result<GroupAtAllRemain>;
+    async fn get_group_at_all_remain(
+        &self,
+        params: GetGroupAtAllRemain,
+    ) -> Result<GroupAtAllRemain>;
 
     /// [设置群名](https://docs.go-cqhttp.org/api/#%E8%AE%BE%E7%BD%AE%E7%BE%A4%E5%90%8D)
-    async fn set_group_name(&self, group_id: i64, group_name: String) -> Result<()>;
+    async fn set_group_name(&self, params: SetGroupName) -> Result<()>;
 
     /// [设置群头像](https://docs.go-cqhttp.org/api/#%E8%AE%BE%E7%BD%AE%E7%BE%A4%E5%A4%B4%E5%83%8F)
     ///
     /// 提示：`file`参数支持本地文件路径、URL、Base64编码的图片
     ///
     /// 提示：目前这个API在登录一段时间后因cookie失效而失效, 请考虑后使用
-    async fn set_group_portrait(&self, group_id: i64, file: String, cache: bool) -> Result<()>;
+    async fn set_group_portrait(&self, params: SetGroupPortrait) -> Result<()>;
 
     /// [设置群管理员](https://docs.go-cqhttp.org/api/#%E8%AE%BE%E7%BD%AE%E7%BE%A4%E7%AE%A1%E7%90%86%E5%91%98)
-    async fn set_group_admin(&self, group_id: i64, user_id: i64, enable: bool) -> Result<()>;
+    async fn set_group_admin(&self, params: SetGroupAdmin) -> Result<()>;
 
     /// [设置群名片(群备注)](https://docs.go-cqhttp.org/api/#%E8%AE%BE%E7%BD%AE%E7%BE%A4%E5%90%8D%E7%89%87-%E7%BE%A4%E5%A4%87%E6%B3%A8)
-    async fn set_group_card(&self, group_id: i64, user_id: i64, card: String) -> Result<()>;
+    async fn set_group_card(&self, params: SetGroupCard) -> Result<()>;
 
     /// [设置群组专属头衔](https://docs.go-cqhttp.org/api/#%E8%AE%BE%E7%BD%AE%E7%BE%A4%E7%BB%84%E4%B8%93%E5%B1%9E%E5%A4%B4%E8%A1%94)
-    async fn set_group_special_title(
-        &self,
-        group_id: i64,
-        user_id: i64,
-        special_title: String,
-        duration: u32,
-    ) -> Result<()>;
+    async fn set_group_special_title(&self, params: SetGroupSpecialTitle) -> Result<()>;
 
     /// [群单人禁言](https://docs.go-cqhttp.org/api/#%E7%BE%A4%E5%8D%95%E4%BA%BA%E7%A6%81%E8%A8%80)
-    async fn set_group_ban(&self, group_id: i64, user_id: i64, duration: u32) -> Result<()>;
+    async fn set_group_ban(&self, params: SetGroupBan) -> Result<()>;
 
     /// [群全员禁言](https://docs.go-cqhttp.org/api/#%E7%BE%A4%E5%85%A8%E5%91%98%E7%A6%81%E8%A8%80)
-    async fn set_group_whole_ban(&self, group_id: i64, enable: bool) -> Result<()>;
+    async fn set_group_whole_ban(&self, params: SetGroupWholeBan) -> Result<()>;
 
     /// [群匿名用户禁言](https://docs.go-cqhttp.org/api/#%E7%BE%A4%E5%8C%BF%E5%90%8D%E7%94%A8%E6%88%B7%E7%A6%81%E8%A8%80)
     ///
     /// 提示：`anonymous`和`flag`两者任选其一传入即可, 若都传入, 则使用`anonymous`
     ///
     /// **注意**：该API从 go-cqhttp-v0.9.36 开始支持
-    async fn set_group_anonymous_ban(
-        &self,
-        group_id: i64,
-        anonymous: Option<AnonymousGroupMsg>,
-        flag: Option<String>,
-        duration: u32,
-    ) -> Result<()>;
+    async fn set_group_anonymous_ban(&self, params: SetGroupAnonymousBan) -> Result<()>;
 
     /// [设置精华消息](https://docs.go-cqhttp.org/api/#%E8%AE%BE%E7%BD%AE%E7%B2%BE%E5%8D%8E%E6%B6%88%E6%81%AF)
-    async fn set_essence_msg(&self, message_id: i32) -> Result<()>;
+    async fn set_essence_msg(&self, params: SetEssenceMsg) -> Result<()>;
 
     /// [移出精华消息](https://docs.go-cqhttp.org/api/#%E7%A7%BB%E5%87%BA%E7%B2%BE%E5%8D%8E%E6%B6%88%E6%81%AF)
-    async fn delete_essence_msg(&self, message_id: i32) -> Result<()>;
+    async fn delete_essence_msg(&self, params: DeleteEssenceMsg) -> Result<()>;
 
     /// [群打卡](https://docs.go-cqhttp.org/api/#%E7%BE%A4%E6%89%93%E5%8D%A1)
-    async fn send_group_sign(&self, group_id: i64) -> Result<()>;
+    async fn send_group_sign(&self, params: SendGroupSign) -> Result<()>;
 
     /// [群设置匿名](https://docs.go-cqhttp.org/api/#%E7%BE%A4%E8%AE%BE%E7%BD%AE%E5%8C%BF%E5%90%8D)
-    async fn set_group_anonymous(&self, group_id: i64, enable: bool) -> Result<()>;
+    async fn set_group_anonymous(&self, params: SetGroupAnonymous) -> Result<()>;
 
     /// [发送群公告](https://docs.go-cqhttp.org/api/#%E5%8F%91%E9%80%81%E7%BE%A4%E5%85%AC%E5%91%8A)
-    async fn _send_group_notice(
-        &self,
-        group_id: i64,
-        content: String,
-        image: Option<String>,
-    ) -> Result<()>;
+    async fn _send_group_notice(&self, params: SendGroupNotice) -> Result<()>;
 
     /// [获取群公告](https://docs.go-cqhttp.org/api/#%E8%8E%B7%E5%8F%96%E7%BE%A4%E5%85%AC%E5%91%8A)
-    async fn _get_group_notice(&self, group_id: i64) -> Result<GroupNotice>;
+    async fn _get_group_notice(&self, params: GetGroupNotice) -> Result<GroupNotice>;
 
     /// [群组踢人](https://docs.go-cqhttp.org/api/#%E7%BE%A4%E7%BB%84%E8%B8%A2%E4%BA%BA)
-    async fn set_group_kick(
-        &self,
-        group_id: i64,
-        user_id: i64,
-        reject_add_request: bool,
-    ) -> Result<()>;
+    async fn set_group_kick(&self, params: SetGroupKick) -> Result<()>;
 
     /// [退出群组](https://docs.go-cqhttp.org/api/#%E9%80%80%E5%87%BA%E7%BE%A4%E7%BB%84)
-    async fn set_group_leave(&self, group_id: i64, is_dismiss: bool) -> Result<()>;
+    async fn set_group_leave(&self, params: SetGroupLeave) -> Result<()>;
 
     /// [上传群文件](https://docs.go-cqhttp.org/api/#%E4%B8%8A%E4%BC%A0%E7%BE%A4%E6%96%87%E4%BB%B6)
     ///
     /// **注意**：在不提供 folder 参数的情况下默认上传到根目录
     ///
     /// **注意**：只能上传本地文件, 需要上传http文件的话请先调用[`download_file`] API下载
-    async fn upload_group_file(
-        &self,
-        group_id: i64,
-        file: String,
-        name: String,
-        folder: Option<String>,
-    ) -> Result<()>;
+    async fn upload_group_file(&self, params: UploadGroupFile) -> Result<()>;
 
     /// [删除群文件](https://docs.go-cqhttp.org/api/#%E5%88%A0%E9%99%A4%E7%BE%A4%E6%96%87%E4%BB%B6)
-    async fn delete_group_file(&self, group_id: i64, file_id: String, busid: i32) -> Result<()>;
+    async fn delete_group_file(&self, params: DeleteGroupFile) -> Result<()>;
 
     /// [创建群文件文件夹](https://docs.go-cqhttp.org/api/#%E5%88%9B%E5%BB%BA%E7%BE%A4%E6%96%87%E4%BB%B6%E6%96%87%E4%BB%B6%E5%A4%B9)
     ///
     /// **注意**：仅能在根目录创建文件夹
-    async fn create_group_file_folder(
-        &self,
-        group_id: i64,
-        name: String,
-        parent_id: String,
-    ) -> Result<()>;
+    async fn create_group_file_folder(&self, params: CreateGroupFileFolder) -> Result<()>;
 
     /// [删除群文件文件夹](https://docs.go-cqhttp.org/api/#%E5%88%A0%E9%99%A4%E7%BE%A4%E6%96%87%E4%BB%B6%E6%96%87%E4%BB%B6%E5%A4%B9)
-    async fn delete_group_folder(&self, group_id: i64, folder_id: String) -> Result<()>;
+    async fn delete_group_folder(&self, params: DeleteGroupFolder) -> Result<()>;
 
     /// [获取群文件系统信息](https://docs.go-cqhttp.org/api/#%E8%8E%B7%E5%8F%96%E7%BE%A4%E6%96%87%E4%BB%B6%E7%B3%BB%E7%BB%9F%E4%BF%A1%E6%81%AF)
-    async fn get_group_file_system_info(&self, group_id: i64) -> Result<GroupFileSystemInfo>;
+    async fn get_group_file_system_info(
+        &self,
+        params: GetGroupFileSystemInfo,
+    ) -> Result<GroupFileSystemInfo>;
 
     /// [获取群根目录文件列表](https://docs.go-cqhttp.org/api/#%E8%8E%B7%E5%8F%96%E7%BE%A4%E6%A0%B9%E7%9B%AE%E5%BD%95%E6%96%87%E4%BB%B6%E5%88%97%E8%A1%A8)
-    async fn get_group_root_files(&self, group_id: i64) -> Result<GroupFiles>;
+    async fn get_group_root_files(&self, params: GetGroupRootFiles) -> Result<GroupFiles>;
 
     /// [获取群子目录文件列表](https://docs.go-cqhttp.org/api/#%E8%8E%B7%E5%8F%96%E7%BE%A4%E5%AD%90%E7%9B%AE%E5%BD%95%E6%96%87%E4%BB%B6%E5%88%97%E8%A1%A8)
-    async fn get_group_files_by_folder(
-        &self,
-        group_id: i64,
-        folder_id: String,
-    ) -> Result<GroupFiles>;
+    async fn get_group_files_by_folder(&self, params: GetGroupFilesByFolder) -> Result<GroupFiles>;
 
     /// [获取群文件资源链接](https://docs.go-cqhttp.org/api/#%E8%8E%B7%E5%8F%96%E7%BE%A4%E6%96%87%E4%BB%B6%E8%B5%84%E6%BA%90%E9%93%BE%E6%8E%A5)
-    async fn get_group_file_url(
-        &self,
-        group_id: i64,
-        file_id: String,
-        busid: i32,
-    ) -> Result<GroupFileUrl>;
+    async fn get_group_file_url(&self, params: GetGroupFileUrl) -> Result<GroupFileUrl>;
 
     /// [上传私聊文件](https://docs.go-cqhttp.org/api/#%E4%B8%8A%E4%BC%A0%E7%A7%81%E8%81%8A%E6%96%87%E4%BB%B6)
     ///
     /// **注意**：只能上传本地文件, 需要上传http文件的话请先调用[`download_file`] API下载
-    async fn upload_private_file(&self, user_id: i64, file: String, name: String) -> Result<()>;
+    async fn upload_private_file(&self, params: UploadPrivateFile) -> Result<()>;
 
     /// [获取 Cookies](https://docs.go-cqhttp.org/api/#%E8%8E%B7%E5%8F%96-cookies)
     ///
     /// **注意**；该API暂未被go-cqhttp支持
-    async fn _get_cookies(&self, _domain: String) -> Result<Cookies> {
+    async fn _get_cookies(&self, _params: GetCookies) -> Result<Cookies> {
         unimplemented!()
     }
 
@@ -332,7 +267,7 @@ pub trait GoCqhttpAPI {
     /// [获取QQ相关接口凭证](https://docs.go-cqhttp.org/api/#%E8%8E%B7%E5%8F%96-qq-%E7%9B%B8%E5%85%B3%E6%8E%A5%E5%8F%A3%E5%87%AD%E8%AF%81)
     ///
     /// **注意**；该API暂未被go-cqhttp支持
-    async fn _get_credentials(&self) -> Result<Credentials> {
+    async fn _get_credentials(&self, _params: GetCredentials) -> Result<Credentials> {
         unimplemented!()
     }
 
@@ -352,29 +287,135 @@ pub trait GoCqhttpAPI {
     }
 
     /// [重载事件过滤器](https://docs.go-cqhttp.org/api/#%E9%87%8D%E8%BD%BD%E4%BA%8B%E4%BB%B6%E8%BF%87%E6%BB%A4%E5%99%A8)
-    async fn reload_event_filter(&self, file: String) -> Result<()>;
+    async fn reload_event_filter(&self, params: ReloadEventFilter) -> Result<()>;
 
     /// [下载文件到缓存目录](https://docs.go-cqhttp.org/api/#%E4%B8%8B%E8%BD%BD%E6%96%87%E4%BB%B6%E5%88%B0%E7%BC%93%E5%AD%98%E7%9B%AE%E5%BD%95)
     ///
     /// 提示：通过这个API下载的文件能直接放入CQ码作为图片或语音发送
     ///
     /// 提示：调用后会阻塞直到下载完成后才会返回数据，请注意下载大文件时的超时
-    async fn download_file(
-        &self,
-        url: String,
-        thread_count: i32,
-        headers: Vec<String>,
-    ) -> Result<DownloadedFile>;
+    async fn download_file(&self, params: DownloadFile) -> Result<DownloadedFile>;
 
     /// [检查链接安全性](https://docs.go-cqhttp.org/api/#%E6%A3%80%E6%9F%A5%E9%93%BE%E6%8E%A5%E5%AE%89%E5%85%A8%E6%80%A7)
-    async fn check_url_safely(&self, url: String) -> Result<UrlSafety>;
+    async fn check_url_safely(&self, params: CheckUrlSafely) -> Result<UrlSafety>;
 
     /// [获取中文分词(隐藏API)](https://docs.go-cqhttp.org/api/#%E8%8E%B7%E5%8F%96%E4%B8%AD%E6%96%87%E5%88%86%E8%AF%8D-%E9%9A%90%E8%97%8F-api)
     ///
     /// **警告**：隐藏API是不建议一般用户使用的, 它们只应该在OneBot实现内部或由SDK和框架使用, 因为不正确的使用可能造成程序运行不正常。
-    async fn get_word_slices(&self, content: String) -> Result<WordSlices>;
+    async fn get_word_slices(&self, params: GetWordSlices) -> Result<WordSlices>;
+}
+
+/// 以`$callback!`展开所有API的方法名和参数类型，用于为包装类型实现`GoCqhttpAPI`和`GuildAPI`
+///
+/// 展开的格式为`gocqhttp { unit { 方法名: 参数类型, ... } params { ... } } guild { ... }`，
+/// `unit`中的方法没有参数，调用时使用参数类型的`new()`
+macro_rules! for_each_api {
+    ($callback:ident) => {
+        $callback! {
+            gocqhttp {
+                unit {
+                    get_login_info: crate::api::params::GetLoginInfo,
+                    qidian_get_account_info: crate::api::params::QidianGetAccountInfo,
+                    get_friend_list: crate::api::params::GetFriendList,
+                    get_unidirectional_friend_list: crate::api::params::GetUnidirectionalFriendList,
+                    can_send_image: crate::api::params::CanSendImage,
+                    can_send_record: crate::api::params::CanSendRecord,
+                    get_group_system_msg: crate::api::params::GetGroupSystemMsg,
+                    _get_csrf_token: crate::api::params::GetCsrfToken,
+                    get_version_info: crate::api::params::GetVersionInfo,
+                    get_status: crate::api::params::GetStatus,
+                    clean_cache: crate::api::params::CleanCache,
+                }
+                params {
+                    set_qq_profile: crate::api::params::SetQqProfile,
+                    get_model_show: crate::api::params::GetModelShow,
+                    set_model_show: crate::api::params::SetModelShow,
+                    get_online_clients: crate::api::params::GetOnlineClients,
+                    get_stranger_info: crate::api::params::GetStrangerInfo,
+                    delete_friend: crate::api::params::DeleteFriend,
+                    delete_unidirectional_friend: crate::api::params::DeleteUnidirectionalFriend,
+                    send_private_msg: crate::api::params::SendPrivateMsg,
+                    send_group_msg: crate::api::params::SendGroupMsg,
+                    send_msg: crate::api::params::SendMsg,
+                    get_msg: crate::api::params::GetMsg,
+                    delete_msg: crate::api::params::DeleteMsg,
+                    mark_msg_as_read: crate::api::params::MarkMsgAsRead,
+                    get_forward_msg: crate::api::params::GetForwardMsg,
+                    send_group_forward_msg: crate::api::params::SendGroupForwardMsg,
+                    send_private_forward_msg: crate::api::params::SendPrivateForwardMsg,
+                    send_forward_msg: crate::api::params::SendForwardMsg,
+                    get_group_msg_history: crate::api::params::GetGroupMsgHistory,
+                    get_image: crate::api::params::GetImage,
+                    ocr_image: crate::api::params::OcrImage,
+                    get_record: crate::api::params::GetRecord,
+                    set_friend_add_request: crate::api::params::SetFriendAddRequest,
+                    set_group_add_request: crate::api::params::SetGroupAddRequest,
+                    get_group_info: crate::api::params::GetGroupInfo,
+                    get_group_list: crate::api::params::GetGroupList,
+                    get_group_member_info: crate::api::params::GetGroupMemberInfo,
+                    get_group_member_list: crate::api::params::GetGroupMemberList,
+                    get_group_honor_info: crate::api::params::GetGroupHonorInfo,
+                    get_essence_msg_list: crate::api::params::GetEssenceMsgList,
+                    get_group_at_all_remain: crate::api::params::GetGroupAtAllRemain,
+                    set_group_name: crate::api::params::SetGroupName,
+                    set_group_portrait: crate::api::params::SetGroupPortrait,
+                    set_group_admin: crate::api::params::SetGroupAdmin,
+                    set_group_card: crate::api::params::SetGroupCard,
+                    set_group_special_title: crate::api::params::SetGroupSpecialTitle,
+                    set_group_ban: crate::api::params::SetGroupBan,
+                    set_group_whole_ban: crate::api::params::SetGroupWholeBan,
+                    set_group_anonymous_ban: crate::api::params::SetGroupAnonymousBan,
+                    set_essence_msg: crate::api::params::SetEssenceMsg,
+                    delete_essence_msg: crate::api::params::DeleteEssenceMsg,
+                    send_group_sign: crate::api::params::SendGroupSign,
+                    set_group_anonymous: crate::api::params::SetGroupAnonymous,
+                    _send_group_notice: crate::api::params::SendGroupNotice,
+                    _get_group_notice: crate::api::params::GetGroupNotice,
+                    set_group_kick: crate::api::params::SetGroupKick,
+                    set_group_leave: crate::api::params::SetGroupLeave,
+                    upload_group_file: crate::api::params::UploadGroupFile,
+                    delete_group_file: crate::api::params::DeleteGroupFile,
+                    create_group_file_folder: crate::api::params::CreateGroupFileFolder,
+                    delete_group_folder: crate::api::params::DeleteGroupFolder,
+                    get_group_file_system_info: crate::api::params::GetGroupFileSystemInfo,
+                    get_group_root_files: crate::api::params::GetGroupRootFiles,
+                    get_group_files_by_folder: crate::api::params::GetGroupFilesByFolder,
+                    get_group_file_url: crate::api::params::GetGroupFileUrl,
+                    upload_private_file: crate::api::params::UploadPrivateFile,
+                    _get_cookies: crate::api::params::GetCookies,
+                    _get_credentials: crate::api::params::GetCredentials,
+                    reload_event_filter: crate::api::params::ReloadEventFilter,
+                    download_file: crate::api::params::DownloadFile,
+                    check_url_safely: crate::api::params::CheckUrlSafely,
+                    get_word_slices: crate::api::params::GetWordSlices,
+                }
+            }
+            guild {
+                unit {
+                    get_guild_service_profile: crate::api::guild::params::GetGuildServiceProfile,
+                    get_guild_list: crate::api::guild::params::GetGuildList,
+                }
+                params {
+                    get_guild_meta_by_guest: crate::api::guild::params::GetGuildMetaByGuest,
+                    get_guild_channel_list: crate::api::guild::params::GetGuildChannelList,
+                    get_guild_member_list: crate::api::guild::params::GetGuildMemberList,
+                    get_guild_member_profile: crate::api::guild::params::GetGuildMemberProfile,
+                    get_guild_msg: crate::api::guild::params::GetGuildMsg,
+                    send_guild_channel_msg: crate::api::guild::params::SendGuildChannelMsg,
+                    get_topic_channel_feeds: crate::api::guild::params::GetTopicChannelFeeds,
+                    get_guild_roles: crate::api::guild::params::GetGuildRoles,
+                    create_guild_role: crate::api::guild::params::CreateGuildRole,
+                    update_guild_role: crate::api::guild::params::UpdateGuildRole,
+                    delete_guild_role: crate::api::guild::params::DeleteGuildRole,
+                    set_guild_member_role: crate::api::guild::params::SetGuildMemberRole,
+                }
+            }
+        }
+    };
 }
 
+pub(crate) use for_each_api;
+
 /// API状态
 #[derive(Debug, Eq, PartialEq)]
 pub enum APIStatus {
@@ -386,14 +427,13 @@ pub enum APIStatus {
     Failed,
 }
 
-impl ToString for APIStatus {
-    fn to_string(&self) -> String {
-        match self {
+impl fmt::Display for APIStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
             APIStatus::Ok => "ok",
             APIStatus::Async => "async",
             APIStatus::Failed => "failed",
-        }
-        .to_string()
+        })
     }
 }
 
@@ -430,7 +470,21 @@ pub struct APIResponse<T> {
 }
 
 impl<T: DeserializeOwned> APIResponse<T> {
+    /// 解析HTTP响应，`status`为`failed`时返回错误
     pub async fn from_http(resp: Response) -> Result<Self> {
+        let ret = Self::from_http_raw(resp).await?;
+        if ret.is_failed() {
+            Err(Box::new(Error::new(
+                ErrorKind::InvalidData,
+                format!("请求失败: {}", ret.message),
+            )))
+        } else {
+            Ok(ret)
+        }
+    }
+
+    /// 解析HTTP响应，只检查HTTP状态码，不检查`status`
+    pub async fn from_http_raw(resp: Response) -> Result<Self> {
         // https://docs.go-cqhttp.org/api/#%E5%93%8D%E5%BA%94%E8%AF%B4%E6%98%8E
         match resp.status() {
             StatusCode::OK => {}
@@ -466,15 +520,7 @@ impl<T: DeserializeOwned> APIResponse<T> {
                 )))
             }
         }
-        let ret: APIResponse<T> = resp.json().await?;
-        if ret.is_failed() {
-            Err(Box::new(Error::new(
-                ErrorKind::InvalidData,
-                format!("请求失败: {}", ret.message),
-            )))
-        } else {
-            Ok(ret)
-        }
+        Ok(resp.json().await?)
     }
 
     pub fn is_ok(&self) -> bool {
@@ -0,0 +1,151 @@
+//! API的调用方式，见[异步调用](https://docs.go-cqhttp.org/api/#%E5%BC%82%E6%AD%A5%E8%B0%83%E7%94%A8)与[限速调用](https://docs.go-cqhttp.org/api/#%E9%99%90%E9%80%9F%E8%B0%83%E7%94%A8)
+
+use crate::Result;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::fmt;
+
+/// API的调用方式
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum CallMode {
+    /// 等待go-cqhttp执行完毕并返回结果
+    #[default]
+    Normal,
+    /// 在终结点后加`_async`，go-cqhttp立即返回，不等待执行完毕
+    Async,
+    /// 在终结点后加`_rate_limited`，go-cqhttp将调用放入限速队列并立即返回，需要在配置中启用限速
+    RateLimited,
+}
+
+impl CallMode {
+    /// `action`在该调用方式下的终结点
+    pub fn endpoint(&self, action: &str) -> String {
+        match self {
+            CallMode::Normal => action.to_string(),
+            CallMode::Async => format!("{}_async", action),
+            CallMode::RateLimited => format!("{}_rate_limited", action),
+        }
+    }
+}
+
+/// API调用的结果
+#[derive(Debug, Eq, PartialEq)]
+pub enum Outcome<T> {
+    /// 调用已执行完毕
+    Completed(T),
+    /// 调用已提交（`APIStatus::Async`），是否成功无法得知
+    Submitted,
+}
+
+impl<T> Outcome<T> {
+    pub fn is_completed(&self) -> bool {
+        matches!(self, Outcome::Completed(_))
+    }
+
+    pub fn is_submitted(&self) -> bool {
+        matches!(self, Outcome::Submitted)
+    }
+
+    /// 已执行完毕时返回结果
+    pub fn completed(self) -> Option<T> {
+        match self {
+            Outcome::Completed(data) => Some(data),
+            Outcome::Submitted => None,
+        }
+    }
+
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Outcome<U> {
+        match self {
+            Outcome::Completed(data) => Outcome::Completed(f(data)),
+            Outcome::Submitted => Outcome::Submitted,
+        }
+    }
+}
+
+impl Outcome<Value> {
+    /// 将`data`字段反序列化为`R`
+    ///
+    /// 调用已提交时，如果`R`可以由`null`得到（例如`()`和`Option`），返回该值，否则返回`Submitted`错误
+    pub fn into_result<R: DeserializeOwned>(self, action: &str) -> Result<R> {
+        match self {
+            Outcome::Completed(data) => Ok(serde_json::from_value(data)?),
+            Outcome::Submitted => serde_json::from_value(Value::Null).map_err(|_| {
+                Submitted {
+                    action: action.to_string(),
+                }
+                .into()
+            }),
+        }
+    }
+}
+
+/// 调用已提交但需要返回数据时的错误，可以通过`downcast_ref`识别
+#[derive(Debug)]
+pub struct Submitted {
+    /// 调用的API
+    pub action: String,
+}
+
+impl fmt::Display for Submitted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "API`{}`已提交，无法得知其结果", self.action)
+    }
+}
+
+impl std::error::Error for Submitted {}
+
+/// API调用失败（`status`为`failed`）时的错误，可以通过`downcast_ref`识别
+#[derive(Debug)]
+pub struct Failed {
+    /// 调用的API
+    pub action: String,
+    /// 返回码
+    pub retcode: i32,
+    /// 错误信息
+    pub message: String,
+    /// 对错误的详细解释(中文)
+    pub wording: String,
+}
+
+impl fmt::Display for Failed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "请求失败: API`{}`返回{}，{}",
+            self.action, self.retcode, self.message
+        )?;
+        if !self.wording.is_empty() {
+            write!(f, "（{}）", self.wording)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Failed {}
+
+#[cfg(test)]
+mod tests {
+    use super::{CallMode, Outcome, Submitted};
+    use serde_json::json;
+
+    #[test]
+    fn test_call_mode_endpoint() {
+        assert_eq!(CallMode::Normal.endpoint("send_msg"), "send_msg");
+        assert_eq!(CallMode::Async.endpoint("send_msg"), "send_msg_async");
+        assert_eq!(
+            CallMode::RateLimited.endpoint("send_msg"),
+            "send_msg_rate_limited"
+        );
+    }
+
+    #[test]
+    fn test_outcome_into_result() {
+        let id: i32 = Outcome::Completed(json!(1)).into_result("a").unwrap();
+        assert_eq!(id, 1);
+        Outcome::Submitted.into_result::<()>("delete_msg").unwrap();
+        let e = Outcome::Submitted
+            .into_result::<i32>("get_msg")
+            .unwrap_err();
+        assert_eq!(e.downcast_ref::<Submitted>().unwrap().action, "get_msg");
+    }
+}
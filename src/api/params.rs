@@ -0,0 +1,918 @@
+//! API的参数，每个API对应一个结构体
+//!
+//! 必填参数通过`new`传入，可选参数通过同名方法设置，未设置的可选参数不会被序列化，由go-cqhttp使用默认值：
+//!
+//! ```
+//! use rust_gocqhttp::api::params::{Action, SetGroupBan};
+//!
+//! let params = SetGroupBan::new(123, 456).duration(60);
+//! assert_eq!(SetGroupBan::NAME, "set_group_ban");
+//! assert_eq!(
+//!     serde_json::to_string(&params).unwrap(),
+//!     r#"{"group_id":123,"user_id":456,"duration":60}"#
+//! );
+//! ```
+
+use super::data::*;
+use crate::message::forward::ForwardMessage;
+use crate::message::Message;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// 一个API调用：API名称、参数（`Self`）和响应数据的类型
+pub trait Action: Serialize {
+    /// API名称，即终结点
+    const NAME: &'static str;
+    /// 响应数据（`data`字段）的类型
+    type Output: DeserializeOwned;
+}
+
+/// 定义API的参数结构体
+///
+/// `required`中的字段为必填参数，作为`new`的参数；`optional`中的字段为可选参数，类型会被包装为`Option`
+macro_rules! params {
+    ($(
+        $(#[$meta:meta])*
+        $name:ident($action:literal) -> $output:ty {
+            required { $($(#[$rmeta:meta])* $req:ident: $req_ty:ty),* $(,)? }
+            $(optional { $($(#[$ometa:meta])* $opt:ident: $opt_ty:ty),* $(,)? })?
+        }
+    )*) => {$(
+        $(#[$meta])*
+        #[derive(Serialize, Debug)]
+        pub struct $name {
+            $(
+                $(#[$rmeta])*
+                pub $req: $req_ty,
+            )*
+            $($(
+                $(#[$ometa])*
+                #[serde(skip_serializing_if = "Option::is_none")]
+                pub $opt: Option<$opt_ty>,
+            )*)?
+        }
+
+        impl $name {
+            pub fn new($($req: impl Into<$req_ty>),*) -> Self {
+                Self {
+                    $($req: $req.into(),)*
+                    $($($opt: None,)*)?
+                }
+            }
+
+            $($(
+                #[doc = concat!("设置可选参数`", stringify!($opt), "`")]
+                pub fn $opt(mut self, $opt: impl Into<$opt_ty>) -> Self {
+                    self.$opt = Some($opt.into());
+                    self
+                }
+            )*)?
+        }
+
+        impl Action for $name {
+            const NAME: &'static str = $action;
+            type Output = $output;
+        }
+    )*};
+}
+
+pub(crate) use params;
+
+params! {
+    /// [获取登录号信息](https://docs.go-cqhttp.org/api/#%E8%8E%B7%E5%8F%96%E7%99%BB%E5%BD%95%E5%8F%B7%E4%BF%A1%E6%81%AF)
+    #[derive(Default)]
+    GetLoginInfo("get_login_info") -> LoginInfo {
+        required {}
+    }
+
+    /// [设置登录号资料](https://docs.go-cqhttp.org/api/#%E8%AE%BE%E7%BD%AE%E7%99%BB%E5%BD%95%E5%8F%B7%E8%B5%84%E6%96%99)
+    #[derive(Default)]
+    SetQqProfile("set_qq_profile") -> () {
+        required {}
+        optional {
+            /// 名称
+            nickname: String,
+            /// 公司
+            company: String,
+            /// 邮箱
+            email: String,
+            /// 学校
+            college: String,
+            /// 个人说明
+            personal_note: String,
+        }
+    }
+
+    /// [获取企点账号信息](https://docs.go-cqhttp.org/api/#%E8%8E%B7%E5%8F%96%E4%BC%81%E7%82%B9%E8%B4%A6%E5%8F%B7%E4%BF%A1%E6%81%AF)
+    #[derive(Default)]
+    QidianGetAccountInfo("qidian_get_account_info") -> String {
+        required {}
+    }
+
+    /// [获取在线机型](https://docs.go-cqhttp.org/api/#%E8%8E%B7%E5%8F%96%E5%9C%A8%E7%BA%BF%E6%9C%BA%E5%9E%8B)
+    GetModelShow("get_model_show") -> ModelShowVariants {
+        required {
+            /// 机型名称
+            model: String,
+        }
+    }
+
+    /// [设置在线机型](https://docs.go-cqhttp.org/api/#%E8%AE%BE%E7%BD%AE%E5%9C%A8%E7%BA%BF%E6%9C%BA%E5%9E%8B)
+    SetModelShow("set_model_show") -> () {
+        required {
+            /// 机型名称
+            model: String,
+            /// 机型名称
+            model_show: String,
+        }
+    }
+
+    /// [获取当前账号在线客户端列表](https://docs.go-cqhttp.org/api/#%E8%8E%B7%E5%8F%96%E5%BD%93%E5%89%8D%E8%B4%A6%E5%8F%B7%E5%9C%A8%E7%BA%BF%E5%AE%A2%E6%88%B7%E7%AB%AF%E5%88%97%E8%A1%A8)
+    #[derive(Default)]
+    GetOnlineClients("get_online_clients") -> ClientDevices {
+        required {}
+        optional {
+            /// 是否无视缓存
+            no_cache: bool,
+        }
+    }
+
+    /// [获取陌生人信息](https://docs.go-cqhttp.org/api/#%E8%8E%B7%E5%8F%96%E9%99%8C%E7%94%9F%E4%BA%BA%E4%BF%A1%E6%81%AF)
+    GetStrangerInfo("get_stranger_info") -> StrangerInfo {
+        required {
+            /// QQ号
+            user_id: i64,
+        }
+        optional {
+            /// 是否不使用缓存（使用缓存可能更新不及时, 但响应更快）
+            no_cache: bool,
+        }
+    }
+
+    /// [获取好友列表](https://docs.go-cqhttp.org/api/#%E8%8E%B7%E5%8F%96%E5%A5%BD%E5%8F%8B%E5%88%97%E8%A1%A8)
+    #[derive(Default)]
+    GetFriendList("get_friend_list") -> Vec<Friend> {
+        required {}
+    }
+
+    /// [获取单向好友列表](https://docs.go-cqhttp.org/api/#%E8%8E%B7%E5%8F%96%E5%8D%95%E5%90%91%E5%A5%BD%E5%8F%8B%E5%88%97%E8%A1%A8)
+    #[derive(Default)]
+    GetUnidirectionalFriendList("get_unidirectional_friend_list") -> Vec<UnidirectionalFriend> {
+        required {}
+    }
+
+    /// [删除好友](https://docs.go-cqhttp.org/api/#%E5%88%A0%E9%99%A4%E5%A5%BD%E5%8F%8B)
+    DeleteFriend("delete_friend") -> () {
+        required {
+            /// 好友QQ号
+            user_id: i64,
+        }
+    }
+
+    /// [删除单向好友](https://docs.go-cqhttp.org/api/#%E5%88%A0%E9%99%A4%E5%8D%95%E5%90%91%E5%A5%BD%E5%8F%8B)
+    DeleteUnidirectionalFriend("delete_unidirectional_friend") -> () {
+        required {
+            /// 单向好友QQ号
+            user_id: i64,
+        }
+    }
+
+    /// [发送私聊消息](https://docs.go-cqhttp.org/api/#%E5%8F%91%E9%80%81%E7%A7%81%E8%81%8A%E6%B6%88%E6%81%AF)
+    SendPrivateMsg("send_private_msg") -> MessageID {
+        required {
+            /// 对方QQ号
+            user_id: i64,
+            /// 要发送的内容
+            message: Message,
+        }
+        optional {
+            /// 主动发起临时会话时的来源群号(可选, 机器人本身必须是管理员/群主)
+            group_id: i64,
+            /// 消息内容是否作为纯文本发送（即不解析CQ码）
+            auto_escape: bool,
+        }
+    }
+
+    /// [发送群聊消息](https://docs.go-cqhttp.org/api/#%E5%8F%91%E9%80%81%E7%BE%A4%E8%81%8A%E6%B6%88%E6%81%AF)
+    SendGroupMsg("send_group_msg") -> MessageID {
+        required {
+            /// 群号
+            group_id: i64,
+            /// 要发送的内容
+            message: Message,
+        }
+        optional {
+            /// 消息内容是否作为纯文本发送（即不解析CQ码）
+            auto_escape: bool,
+        }
+    }
+
+    /// [发送消息](https://docs.go-cqhttp.org/api/#%E5%8F%91%E9%80%81%E6%B6%88%E6%81%AF)
+    ///
+    /// 通常使用`private`或`group`构造
+    SendMsg("send_msg") -> MessageID {
+        required {
+            /// 要发送的内容
+            message: Message,
+        }
+        optional {
+            /// 消息类型, 如不传入, 则根据传入的`*_id`参数判断
+            message_type: ChatType,
+            /// 对方QQ号(消息类型为`private`时需要)
+            user_id: i64,
+            /// 群号(消息类型为`group`时需要)
+            group_id: i64,
+            /// 消息内容是否作为纯文本发送（即不解析CQ码）
+            auto_escape: bool,
+        }
+    }
+
+    /// [获取消息](https://docs.go-cqhttp.org/api/#%E8%8E%B7%E5%8F%96%E6%B6%88%E6%81%AF)
+    GetMsg("get_msg") -> Msg {
+        required {
+            /// 消息id
+            message_id: i32,
+        }
+    }
+
+    /// [撤回消息](https://docs.go-cqhttp.org/api/#%E6%92%A4%E5%9B%9E%E6%B6%88%E6%81%AF)
+    DeleteMsg("delete_msg") -> () {
+        required {
+            /// 消息id
+            message_id: i32,
+        }
+    }
+
+    /// [标记消息已读](https://docs.go-cqhttp.org/api/#%E6%A0%87%E8%AE%B0%E6%B6%88%E6%81%AF%E5%B7%B2%E8%AF%BB)
+    MarkMsgAsRead("mark_msg_as_read") -> () {
+        required {
+            /// 消息id
+            message_id: i32,
+        }
+    }
+
+    /// [获取合并转发内容](https://docs.go-cqhttp.org/api/#%E8%8E%B7%E5%8F%96%E5%90%88%E5%B9%B6%E8%BD%AC%E5%8F%91%E5%86%85%E5%AE%B9)
+    GetForwardMsg("get_forward_msg") -> Vec<ForwardedMessage> {
+        required {
+            /// 消息id
+            message_id: String,
+        }
+    }
+
+    /// [发送合并转发(群聊)](https://docs.go-cqhttp.org/api/#%E5%8F%91%E9%80%81%E5%90%88%E5%B9%B6%E8%BD%AC%E5%8F%91-%E7%BE%A4%E8%81%8A)
+    SendGroupForwardMsg("send_group_forward_msg") -> ForwardMessageID {
+        required {
+            /// 群号
+            group_id: i64,
+            /// 自定义转发消息
+            messages: ForwardMessage,
+        }
+    }
+
+    /// [发送合并转发(好友)](https://docs.go-cqhttp.org/api/#%E5%8F%91%E9%80%81%E5%90%88%E5%B9%B6%E8%BD%AC%E5%8F%91-%E5%A5%BD%E5%8F%8B)
+    SendPrivateForwardMsg("send_private_forward_msg") -> ForwardMessageID {
+        required {
+            /// 好友QQ号
+            user_id: i64,
+            /// 自定义转发消息
+            messages: ForwardMessage,
+        }
+    }
+
+    /// [发送合并转发](https://docs.go-cqhttp.org/api/#%E5%8F%91%E9%80%81%E5%90%88%E5%B9%B6%E8%BD%AC%E5%8F%91)
+    ///
+    /// 通常使用`private`或`group`构造
+    SendForwardMsg("send_forward_msg") -> ForwardMessageID {
+        required {
+            /// 自定义转发消息
+            messages: ForwardMessage,
+        }
+        optional {
+            /// 消息类型
+            message_type: ChatType,
+            /// 好友QQ号(消息类型为`private`时需要)
+            user_id: i64,
+            /// 群号(消息类型为`group`时需要)
+            group_id: i64,
+        }
+    }
+
+    /// [获取群消息历史记录](https://docs.go-cqhttp.org/api/#%E8%8E%B7%E5%8F%96%E7%BE%A4%E6%B6%88%E6%81%AF%E5%8E%86%E5%8F%B2%E8%AE%B0%E5%BD%95)
+    GetGroupMsgHistory("get_group_msg_history") -> MsgHistory {
+        required {
+            /// 群号
+            group_id: i64,
+        }
+        optional {
+            /// 起始消息序号, 不传入时从最新的消息开始
+            message_seq: i64,
+        }
+    }
+
+    /// [获取图片信息](https://docs.go-cqhttp.org/api/#%E8%8E%B7%E5%8F%96%E5%9B%BE%E7%89%87%E4%BF%A1%E6%81%AF)
+    GetImage("get_image") -> Image {
+        required {
+            /// 图片缓存文件名
+            file: String,
+        }
+    }
+
+    /// [检查是否可以发送图片](https://docs.go-cqhttp.org/api/#%E6%A3%80%E6%9F%A5%E6%98%AF%E5%90%A6%E5%8F%AF%E4%BB%A5%E5%8F%91%E9%80%81%E5%9B%BE%E7%89%87)
+    #[derive(Default)]
+    CanSendImage("can_send_image") -> CanSend {
+        required {}
+    }
+
+    /// [图片OCR](https://docs.go-cqhttp.org/api/#%E5%9B%BE%E7%89%87-ocr)
+    OcrImage("ocr_image") -> OCRImage {
+        required {
+            /// 图片ID
+            image: String,
+        }
+    }
+
+    /// [获取语音](https://docs.go-cqhttp.org/api/#%E8%8E%B7%E5%8F%96%E8%AF%AD%E9%9F%B3)
+    GetRecord("get_record") -> Record {
+        required {
+            /// 收到的语音文件名（消息段的`file`参数）
+            file: String,
+            /// 要转换到的格式
+            out_format: String,
+        }
+    }
+
+    /// [检查是否可以发送语音](https://docs.go-cqhttp.org/api/#%E6%A3%80%E6%9F%A5%E6%98%AF%E5%90%A6%E5%8F%AF%E4%BB%A5%E5%8F%91%E9%80%81%E8%AF%AD%E9%9F%B3)
+    #[derive(Default)]
+    CanSendRecord("can_send_record") -> CanSend {
+        required {}
+    }
+
+    /// [处理加好友请求](https://docs.go-cqhttp.org/api/#%E5%A4%84%E7%90%86%E5%8A%A0%E5%A5%BD%E5%8F%8B%E8%AF%B7%E6%B1%82)
+    SetFriendAddRequest("set_friend_add_request") -> () {
+        required {
+            /// 加好友请求的flag（需从上报的数据中获得）
+            flag: String,
+        }
+        optional {
+            /// 是否同意请求
+            approve: bool,
+            /// 添加后的好友备注（仅在同意时有效）
+            remark: String,
+        }
+    }
+
+    /// [处理加群请求／邀请](https://docs.go-cqhttp.org/api/#%E5%A4%84%E7%90%86%E5%8A%A0%E7%BE%A4%E8%AF%B7%E6%B1%82-%E9%82%80%E8%AF%B7)
+    SetGroupAddRequest("set_group_add_request") -> () {
+        required {
+            /// 加群请求的flag（需从上报的数据中获得）
+            flag: String,
+            /// `add`或`invite`, 请求类型（需要和上报消息中的`sub_type`字段相符）
+            sub_type: String,
+        }
+        optional {
+            /// 是否同意请求／邀请
+            approve: bool,
+            /// 拒绝理由（仅在拒绝时有效）
+            reason: String,
+        }
+    }
+
+    /// [获取群信息](https://docs.go-cqhttp.org/api/#%E8%8E%B7%E5%8F%96%E7%BE%A4%E4%BF%A1%E6%81%AF)
+    GetGroupInfo("get_group_info") -> GroupInfo {
+        required {
+            /// 群号
+            group_id: i64,
+        }
+        optional {
+            /// 是否不使用缓存（使用缓存可能更新不及时, 但响应更快）
+            no_cache: bool,
+        }
+    }
+
+    /// [获取群列表](https://docs.go-cqhttp.org/api/#%E8%8E%B7%E5%8F%96%E7%BE%A4%E5%88%97%E8%A1%A8)
+    #[derive(Default)]
+    GetGroupList("get_group_list") -> Vec<GroupInfo> {
+        required {}
+        optional {
+            /// 是否不使用缓存（使用缓存可能更新不及时, 但响应更快）
+            no_cache: bool,
+        }
+    }
+
+    /// [获取群成员信息](https://docs.go-cqhttp.org/api/#%E8%8E%B7%E5%8F%96%E7%BE%A4%E6%88%90%E5%91%98%E4%BF%A1%E6%81%AF)
+    GetGroupMemberInfo("get_group_member_info") -> GroupMemberInfo {
+        required {
+            /// 群号
+            group_id: i64,
+            /// QQ号
+            user_id: i64,
+        }
+        optional {
+            /// 是否不使用缓存（使用缓存可能更新不及时, 但响应更快）
+            no_cache: bool,
+        }
+    }
+
+    /// [获取群成员列表](https://docs.go-cqhttp.org/api/#%E8%8E%B7%E5%8F%96%E7%BE%A4%E6%88%90%E5%91%98%E5%88%97%E8%A1%A8)
+    GetGroupMemberList("get_group_member_list") -> Vec<GroupMemberInfo> {
+        required {
+            /// 群号
+            group_id: i64,
+        }
+        optional {
+            /// 是否不使用缓存（使用缓存可能更新不及时, 但响应更快）
+            no_cache: bool,
+        }
+    }
+
+    /// [获取群荣誉信息](https://docs.go-cqhttp.org/api/#%E8%8E%B7%E5%8F%96%E7%BE%A4%E8%8D%A3%E8%AA%89%E4%BF%A1%E6%81%AF)
+    GetGroupHonorInfo("get_group_honor_info") -> GroupHonorInfo {
+        required {
+            /// 群号
+            group_id: i64,
+            /// 要获取的群荣誉类型
+            #[serde(rename = "type")]
+            type_: GroupHonorType,
+        }
+    }
+
+    /// [获取群系统消息](https://docs.go-cqhttp.org/api/#%E8%8E%B7%E5%8F%96%E7%BE%A4%E7%B3%BB%E7%BB%9F%E6%B6%88%E6%81%AF)
+    #[derive(Default)]
+    GetGroupSystemMsg("get_group_system_msg") -> GroupSystemMsg {
+        required {}
+    }
+
+    /// [获取精华消息列表](https://docs.go-cqhttp.org/api/#%E8%8E%B7%E5%8F%96%E7%B2%BE%E5%8D%8E%E6%B6%88%E6%81%AF%E5%88%97%E8%A1%A8)
+    GetEssenceMsgList("get_essence_msg_list") -> Vec<EssenceMsg> {
+        required {
+            /// 群号
+            group_id: i64,
+        }
+    }
+
+    /// [获取群 @全体成员 剩余次数](https://docs.go-cqhttp.org/api/#%E8%8E%B7%E5%8F%96%E7%BE%A4-%E5%85%A8%E4%BD%93%E6%88%90%E5%91%98-%E5%89%A9%E4%BD%99%E6%AC%A1%E6%95%B0)
+    GetGroupAtAllRemain("get_group_at_all_remain") -> GroupAtAllRemain {
+        required {
+            /// 群号
+            group_id: i64,
+        }
+    }
+
+    /// [设置群名](https://docs.go-cqhttp.org/api/#%E8%AE%BE%E7%BD%AE%E7%BE%A4%E5%90%8D)
+    SetGroupName("set_group_name") -> () {
+        required {
+            /// 群号
+            group_id: i64,
+            /// 新群名
+            group_name: String,
+        }
+    }
+
+    /// [设置群头像](https://docs.go-cqhttp.org/api/#%E8%AE%BE%E7%BD%AE%E7%BE%A4%E5%A4%B4%E5%83%8F)
+    SetGroupPortrait("set_group_portrait") -> () {
+        required {
+            /// 群号
+            group_id: i64,
+            /// 图片文件，支持本地文件路径、URL、Base64编码的图片
+            file: String,
+        }
+        optional {
+            /// 是否使用已缓存的文件
+            cache: bool,
+        }
+    }
+
+    /// [设置群管理员](https://docs.go-cqhttp.org/api/#%E8%AE%BE%E7%BD%AE%E7%BE%A4%E7%AE%A1%E7%90%86%E5%91%98)
+    SetGroupAdmin("set_group_admin") -> () {
+        required {
+            /// 群号
+            group_id: i64,
+            /// 要设置管理员的QQ号
+            user_id: i64,
+        }
+        optional {
+            /// `true`为设置, `false`为取消
+            enable: bool,
+        }
+    }
+
+    /// [设置群名片(群备注)](https://docs.go-cqhttp.org/api/#%E8%AE%BE%E7%BD%AE%E7%BE%A4%E5%90%8D%E7%89%87-%E7%BE%A4%E5%A4%87%E6%B3%A8)
+    SetGroupCard("set_group_card") -> () {
+        required {
+            /// 群号
+            group_id: i64,
+            /// 要设置的QQ号
+            user_id: i64,
+        }
+        optional {
+            /// 群名片内容, 不填或空字符串表示删除群名片
+            card: String,
+        }
+    }
+
+    /// [设置群组专属头衔](https://docs.go-cqhttp.org/api/#%E8%AE%BE%E7%BD%AE%E7%BE%A4%E7%BB%84%E4%B8%93%E5%B1%9E%E5%A4%B4%E8%A1%94)
+    SetGroupSpecialTitle("set_group_special_title") -> () {
+        required {
+            /// 群号
+            group_id: i64,
+            /// 要设置的QQ号
+            user_id: i64,
+        }
+        optional {
+            /// 专属头衔, 不填或空字符串表示删除专属头衔
+            special_title: String,
+            /// 专属头衔有效期, 单位秒, -1表示永久
+            duration: i64,
+        }
+    }
+
+    /// [群单人禁言](https://docs.go-cqhttp.org/api/#%E7%BE%A4%E5%8D%95%E4%BA%BA%E7%A6%81%E8%A8%80)
+    SetGroupBan("set_group_ban") -> () {
+        required {
+            /// 群号
+            group_id: i64,
+            /// 要禁言的QQ号
+            user_id: i64,
+        }
+        optional {
+            /// 禁言时长, 单位秒, 0表示取消禁言, 默认30分钟
+            duration: i64,
+        }
+    }
+
+    /// [群全员禁言](https://docs.go-cqhttp.org/api/#%E7%BE%A4%E5%85%A8%E5%91%98%E7%A6%81%E8%A8%80)
+    SetGroupWholeBan("set_group_whole_ban") -> () {
+        required {
+            /// 群号
+            group_id: i64,
+        }
+        optional {
+            /// 是否禁言, 默认为`true`
+            enable: bool,
+        }
+    }
+
+    /// [群匿名用户禁言](https://docs.go-cqhttp.org/api/#%E7%BE%A4%E5%8C%BF%E5%90%8D%E7%94%A8%E6%88%B7%E7%A6%81%E8%A8%80)
+    ///
+    /// 提示：`anonymous`和`flag`两者任选其一传入即可, 若都传入, 则使用`anonymous`
+    SetGroupAnonymousBan("set_group_anonymous_ban") -> () {
+        required {
+            /// 群号
+            group_id: i64,
+        }
+        optional {
+            /// 要禁言的匿名用户对象（群消息上报的`anonymous`字段）
+            anonymous: AnonymousGroupMsg,
+            /// 要禁言的匿名用户的flag（需从群消息上报的数据中获得）
+            flag: String,
+            /// 禁言时长, 单位秒, 无法取消匿名用户禁言, 默认30分钟
+            duration: i64,
+        }
+    }
+
+    /// [设置精华消息](https://docs.go-cqhttp.org/api/#%E8%AE%BE%E7%BD%AE%E7%B2%BE%E5%8D%8E%E6%B6%88%E6%81%AF)
+    SetEssenceMsg("set_essence_msg") -> () {
+        required {
+            /// 消息ID
+            message_id: i32,
+        }
+    }
+
+    /// [移出精华消息](https://docs.go-cqhttp.org/api/#%E7%A7%BB%E5%87%BA%E7%B2%BE%E5%8D%8E%E6%B6%88%E6%81%AF)
+    DeleteEssenceMsg("delete_essence_msg") -> () {
+        required {
+            /// 消息ID
+            message_id: i32,
+        }
+    }
+
+    /// [群打卡](https://docs.go-cqhttp.org/api/#%E7%BE%A4%E6%89%93%E5%8D%A1)
+    SendGroupSign("send_group_sign") -> () {
+        required {
+            /// 群号
+            group_id: i64,
+        }
+    }
+
+    /// [群设置匿名](https://docs.go-cqhttp.org/api/#%E7%BE%A4%E8%AE%BE%E7%BD%AE%E5%8C%BF%E5%90%8D)
+    SetGroupAnonymous("set_group_anonymous") -> () {
+        required {
+            /// 群号
+            group_id: i64,
+        }
+        optional {
+            /// 是否允许匿名聊天, 默认为`true`
+            enable: bool,
+        }
+    }
+
+    /// [发送群公告](https://docs.go-cqhttp.org/api/#%E5%8F%91%E9%80%81%E7%BE%A4%E5%85%AC%E5%91%8A)
+    SendGroupNotice("_send_group_notice") -> () {
+        required {
+            /// 群号
+            group_id: i64,
+            /// 公告内容
+            content: String,
+        }
+        optional {
+            /// 图片路径
+            image: String,
+        }
+    }
+
+    /// [获取群公告](https://docs.go-cqhttp.org/api/#%E8%8E%B7%E5%8F%96%E7%BE%A4%E5%85%AC%E5%91%8A)
+    GetGroupNotice("_get_group_notice") -> GroupNotice {
+        required {
+            /// 群号
+            group_id: i64,
+        }
+    }
+
+    /// [群组踢人](https://docs.go-cqhttp.org/api/#%E7%BE%A4%E7%BB%84%E8%B8%A2%E4%BA%BA)
+    SetGroupKick("set_group_kick") -> () {
+        required {
+            /// 群号
+            group_id: i64,
+            /// 要踢的QQ号
+            user_id: i64,
+        }
+        optional {
+            /// 拒绝此人的加群请求
+            reject_add_request: bool,
+        }
+    }
+
+    /// [退出群组](https://docs.go-cqhttp.org/api/#%E9%80%80%E5%87%BA%E7%BE%A4%E7%BB%84)
+    SetGroupLeave("set_group_leave") -> () {
+        required {
+            /// 群号
+            group_id: i64,
+        }
+        optional {
+            /// 是否解散, 如果登录号是群主, 则仅在此项为`true`时能够解散
+            is_dismiss: bool,
+        }
+    }
+
+    /// [上传群文件](https://docs.go-cqhttp.org/api/#%E4%B8%8A%E4%BC%A0%E7%BE%A4%E6%96%87%E4%BB%B6)
+    UploadGroupFile("upload_group_file") -> () {
+        required {
+            /// 群号
+            group_id: i64,
+            /// 本地文件路径
+            file: String,
+            /// 储存名称
+            name: String,
+        }
+        optional {
+            /// 父目录ID, 不提供时上传到根目录
+            folder: String,
+        }
+    }
+
+    /// [删除群文件](https://docs.go-cqhttp.org/api/#%E5%88%A0%E9%99%A4%E7%BE%A4%E6%96%87%E4%BB%B6)
+    DeleteGroupFile("delete_group_file") -> () {
+        required {
+            /// 群号
+            group_id: i64,
+            /// 文件ID
+            file_id: String,
+            /// 文件类型
+            busid: i32,
+        }
+    }
+
+    /// [创建群文件文件夹](https://docs.go-cqhttp.org/api/#%E5%88%9B%E5%BB%BA%E7%BE%A4%E6%96%87%E4%BB%B6%E6%96%87%E4%BB%B6%E5%A4%B9)
+    CreateGroupFileFolder("create_group_file_folder") -> () {
+        required {
+            /// 群号
+            group_id: i64,
+            /// 文件夹名称
+            name: String,
+        }
+        optional {
+            /// 仅能为`/`
+            parent_id: String,
+        }
+    }
+
+    /// [删除群文件文件夹](https://docs.go-cqhttp.org/api/#%E5%88%A0%E9%99%A4%E7%BE%A4%E6%96%87%E4%BB%B6%E6%96%87%E4%BB%B6%E5%A4%B9)
+    DeleteGroupFolder("delete_group_folder") -> () {
+        required {
+            /// 群号
+            group_id: i64,
+            /// 文件夹ID
+            folder_id: String,
+        }
+    }
+
+    /// [获取群文件系统信息](https://docs.go-cqhttp.org/api/#%E8%8E%B7%E5%8F%96%E7%BE%A4%E6%96%87%E4%BB%B6%E7%B3%BB%E7%BB%9F%E4%BF%A1%E6%81%AF)
+    GetGroupFileSystemInfo("get_group_file_system_info") -> GroupFileSystemInfo {
+        required {
+            /// 群号
+            group_id: i64,
+        }
+    }
+
+    /// [获取群根目录文件列表](https://docs.go-cqhttp.org/api/#%E8%8E%B7%E5%8F%96%E7%BE%A4%E6%A0%B9%E7%9B%AE%E5%BD%95%E6%96%87%E4%BB%B6%E5%88%97%E8%A1%A8)
+    GetGroupRootFiles("get_group_root_files") -> GroupFiles {
+        required {
+            /// 群号
+            group_id: i64,
+        }
+    }
+
+    /// [获取群子目录文件列表](https://docs.go-cqhttp.org/api/#%E8%8E%B7%E5%8F%96%E7%BE%A4%E5%AD%90%E7%9B%AE%E5%BD%95%E6%96%87%E4%BB%B6%E5%88%97%E8%A1%A8)
+    GetGroupFilesByFolder("get_group_files_by_folder") -> GroupFiles {
+        required {
+            /// 群号
+            group_id: i64,
+            /// 文件夹ID
+            folder_id: String,
+        }
+    }
+
+    /// [获取群文件资源链接](https://docs.go-cqhttp.org/api/#%E8%8E%B7%E5%8F%96%E7%BE%A4%E6%96%87%E4%BB%B6%E8%B5%84%E6%BA%90%E9%93%BE%E6%8E%A5)
+    GetGroupFileUrl("get_group_file_url") -> GroupFileUrl {
+        required {
+            /// 群号
+            group_id: i64,
+            /// 文件ID
+            file_id: String,
+            /// 文件类型
+            busid: i32,
+        }
+    }
+
+    /// [上传私聊文件](https://docs.go-cqhttp.org/api/#%E4%B8%8A%E4%BC%A0%E7%A7%81%E8%81%8A%E6%96%87%E4%BB%B6)
+    UploadPrivateFile("upload_private_file") -> () {
+        required {
+            /// 对方QQ号
+            user_id: i64,
+            /// 本地文件路径
+            file: String,
+            /// 文件名称
+            name: String,
+        }
+    }
+
+    /// [获取 Cookies](https://docs.go-cqhttp.org/api/#%E8%8E%B7%E5%8F%96-cookies)
+    #[derive(Default)]
+    GetCookies("get_cookies") -> Cookies {
+        required {}
+        optional {
+            /// 需要获取cookies的域名
+            domain: String,
+        }
+    }
+
+    /// [获取CSRF Token](https://docs.go-cqhttp.org/api/#%E8%8E%B7%E5%8F%96-csrf-token)
+    #[derive(Default)]
+    GetCsrfToken("get_csrf_token") -> CSRFToken {
+        required {}
+    }
+
+    /// [获取QQ相关接口凭证](https://docs.go-cqhttp.org/api/#%E8%8E%B7%E5%8F%96-qq-%E7%9B%B8%E5%85%B3%E6%8E%A5%E5%8F%A3%E5%87%AD%E8%AF%81)
+    #[derive(Default)]
+    GetCredentials("get_credentials") -> Credentials {
+        required {}
+        optional {
+            /// 需要获取cookies的域名
+            domain: String,
+        }
+    }
+
+    /// [获取版本信息](https://docs.go-cqhttp.org/api/#%E8%8E%B7%E5%8F%96%E7%89%88%E6%9C%AC%E4%BF%A1%E6%81%AF)
+    #[derive(Default)]
+    GetVersionInfo("get_version_info") -> VersionInfo {
+        required {}
+    }
+
+    /// [获取状态](https://docs.go-cqhttp.org/api/#%E8%8E%B7%E5%8F%96%E7%8A%B6%E6%80%81)
+    #[derive(Default)]
+    GetStatus("get_status") -> Status {
+        required {}
+    }
+
+    /// [清理缓存](https://docs.go-cqhttp.org/api/#%E6%B8%85%E7%90%86%E7%BC%93%E5%AD%98)
+    #[derive(Default)]
+    CleanCache("clean_cache") -> () {
+        required {}
+    }
+
+    /// [重载事件过滤器](https://docs.go-cqhttp.org/api/#%E9%87%8D%E8%BD%BD%E4%BA%8B%E4%BB%B6%E8%BF%87%E6%BB%A4%E5%99%A8)
+    ReloadEventFilter("reload_event_filter") -> () {
+        required {
+            /// 事件过滤器文件
+            file: String,
+        }
+    }
+
+    /// [下载文件到缓存目录](https://docs.go-cqhttp.org/api/#%E4%B8%8B%E8%BD%BD%E6%96%87%E4%BB%B6%E5%88%B0%E7%BC%93%E5%AD%98%E7%9B%AE%E5%BD%95)
+    DownloadFile("download_file") -> DownloadedFile {
+        required {
+            /// 链接地址
+            url: String,
+        }
+        optional {
+            /// 下载线程数
+            thread_count: i32,
+            /// 自定义请求头，格式为`User-Agent=YOUR_UA`
+            headers: Vec<String>,
+        }
+    }
+
+    /// [检查链接安全性](https://docs.go-cqhttp.org/api/#%E6%A3%80%E6%9F%A5%E9%93%BE%E6%8E%A5%E5%AE%89%E5%85%A8%E6%80%A7)
+    CheckUrlSafely("check_url_safely") -> UrlSafety {
+        required {
+            /// 需要检查的链接
+            url: String,
+        }
+    }
+
+    /// [获取中文分词(隐藏API)](https://docs.go-cqhttp.org/api/#%E8%8E%B7%E5%8F%96%E4%B8%AD%E6%96%87%E5%88%86%E8%AF%8D-%E9%9A%90%E8%97%8F-api)
+    GetWordSlices(".get_word_slices") -> WordSlices {
+        required {
+            /// 内容
+            content: String,
+        }
+    }
+}
+
+impl SendMsg {
+    /// 发送私聊消息
+    pub fn private(user_id: i64, message: Message) -> Self {
+        Self::new(message)
+            .message_type(ChatType::Private)
+            .user_id(user_id)
+    }
+
+    /// 发送群聊消息
+    pub fn group(group_id: i64, message: Message) -> Self {
+        Self::new(message)
+            .message_type(ChatType::Group)
+            .group_id(group_id)
+    }
+}
+
+impl SendForwardMsg {
+    /// 发送私聊合并转发
+    pub fn private(user_id: i64, messages: impl Into<ForwardMessage>) -> Self {
+        Self::new(messages)
+            .message_type(ChatType::Private)
+            .user_id(user_id)
+    }
+
+    /// 发送群聊合并转发
+    pub fn group(group_id: i64, messages: impl Into<ForwardMessage>) -> Self {
+        Self::new(messages)
+            .message_type(ChatType::Group)
+            .group_id(group_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn to_json(params: &impl Action) -> serde_json::Value {
+        serde_json::to_value(params).unwrap()
+    }
+
+    #[test]
+    fn test_params_optional() {
+        assert_eq!(to_json(&SetQqProfile::new()), json!({}));
+        assert_eq!(
+            to_json(&SetQqProfile::new().nickname("Alice").personal_note("")),
+            json!({ "nickname": "Alice", "personal_note": "" })
+        );
+        assert_eq!(
+            to_json(&SetGroupSpecialTitle::new(1, 2).duration(-1)),
+            json!({ "group_id": 1, "user_id": 2, "duration": -1 })
+        );
+    }
+
+    #[test]
+    fn test_params_send_msg() {
+        let message: Message = "hello".parse().unwrap();
+        assert_eq!(
+            to_json(&SendPrivateMsg::new(1, message)),
+            json!({ "user_id": 1, "message": "hello" })
+        );
+        let message: Message = "hello".parse().unwrap();
+        assert_eq!(
+            to_json(&SendMsg::group(2, message).auto_escape(true)),
+            json!({ "message_type": "group", "group_id": 2, "message": "hello", "auto_escape": true })
+        );
+    }
+
+    #[test]
+    fn test_params_rename() {
+        assert_eq!(
+            to_json(&GetGroupHonorInfo::new(1, GroupHonorType::StrongNewbie)),
+            json!({ "group_id": 1, "type": "strong_newbie" })
+        );
+        assert_eq!(GetWordSlices::NAME, ".get_word_slices");
+    }
+}
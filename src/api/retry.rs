@@ -0,0 +1,340 @@
+//! API调用失败时的自动重试
+//!
+//! `RetryPolicy`是一个中间件，包装`HttpClient`等`Service`后按策略重试失败的调用：
+//!
+//! ```no_run
+//! # async fn f() -> rust_gocqhttp::error::Result<()> {
+//! use rust_gocqhttp::api::client::HttpClient;
+//! use rust_gocqhttp::api::retry::RetryPolicy;
+//! use rust_gocqhttp::api::service::ServiceBuilder;
+//! use rust_gocqhttp::api::GoCqhttpAPI;
+//!
+//! let api = ServiceBuilder::new()
+//!     .layer(RetryPolicy::default().retcode(100))
+//!     .service(HttpClient::new("http://127.0.0.1:5700"));
+//! api.get_status().await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use super::mode::{Failed, Outcome};
+use super::sandbox::ActionKind;
+use super::service::{Layer, Service};
+use crate::Result;
+use async_trait::async_trait;
+use serde_json::Value;
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::error::Error;
+use std::hash::{BuildHasher, Hasher};
+use std::time::{Duration, Instant};
+
+/// 在哪些错误下重试
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum RetryOn {
+    /// 不重试
+    Never,
+    /// 仅在连接失败时重试，此时请求一定没有到达go-cqhttp，重试不会导致重复执行
+    Connect,
+    /// 在连接失败、超时以及`RetryPolicy::retcode`设置的返回码下重试，适用于幂等的查询
+    Transient,
+}
+
+impl RetryOn {
+    /// 默认的重试条件：查询类API（`ActionKind::Read`）为`Transient`，
+    /// 其余API（发送消息、修改设置等）为`Connect`
+    pub fn default_for(action: &str) -> Self {
+        if ActionKind::of(action) == ActionKind::Read {
+            RetryOn::Transient
+        } else {
+            RetryOn::Connect
+        }
+    }
+}
+
+/// 错误的种类
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum Failure {
+    Connect,
+    Transient,
+    Fatal,
+}
+
+/// 重试策略：按API决定是否重试，重试间隔按指数增长并加入随机抖动，总耗时超过上限后不再重试
+///
+/// ```
+/// use rust_gocqhttp::api::retry::{RetryOn, RetryPolicy};
+/// use std::time::Duration;
+///
+/// let policy = RetryPolicy::default()
+///     .max_elapsed_time(Duration::from_secs(10))
+///     .action("set_group_card", RetryOn::Transient);
+/// assert_eq!(policy.retry_on("get_status"), RetryOn::Transient);
+/// assert_eq!(policy.retry_on("send_msg"), RetryOn::Connect);
+/// assert_eq!(policy.retry_on("set_group_card"), RetryOn::Transient);
+/// ```
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    initial_interval: Duration,
+    multiplier: f64,
+    max_interval: Duration,
+    max_elapsed_time: Duration,
+    jitter: f64,
+    retcodes: Vec<i32>,
+    actions: HashMap<String, RetryOn>,
+}
+
+impl Default for RetryPolicy {
+    /// 首次间隔200毫秒，每次翻倍，最长5秒，抖动±50%，总耗时不超过30秒
+    fn default() -> Self {
+        Self {
+            initial_interval: Duration::from_millis(200),
+            multiplier: 2.0,
+            max_interval: Duration::from_secs(5),
+            max_elapsed_time: Duration::from_secs(30),
+            jitter: 0.5,
+            retcodes: Vec::new(),
+            actions: HashMap::new(),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// 第一次重试前的间隔
+    pub fn initial_interval(mut self, interval: Duration) -> Self {
+        self.initial_interval = interval;
+        self
+    }
+
+    /// 每次重试后间隔的倍数
+    pub fn multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier;
+        self
+    }
+
+    /// 重试间隔的上限
+    pub fn max_interval(mut self, interval: Duration) -> Self {
+        self.max_interval = interval;
+        self
+    }
+
+    /// 从第一次调用开始的总耗时上限，下一次重试会超过上限时直接返回错误
+    pub fn max_elapsed_time(mut self, elapsed: Duration) -> Self {
+        self.max_elapsed_time = elapsed;
+        self
+    }
+
+    /// 随机抖动的比例，取值0到1，间隔在`[(1 - jitter) * t, (1 + jitter) * t]`中随机取值
+    pub fn jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    /// 将返回码`retcode`视为暂时性的错误，例如发送过快
+    pub fn retcode(mut self, retcode: i32) -> Self {
+        self.retcodes.push(retcode);
+        self
+    }
+
+    /// 设置`action`的重试条件，覆盖`RetryOn::default_for`
+    pub fn action(mut self, action: impl Into<String>, retry_on: RetryOn) -> Self {
+        self.actions.insert(action.into(), retry_on);
+        self
+    }
+
+    /// `action`的重试条件
+    pub fn retry_on(&self, action: &str) -> RetryOn {
+        self.actions
+            .get(action)
+            .copied()
+            .unwrap_or_else(|| RetryOn::default_for(action))
+    }
+
+    fn failure(&self, error: &(dyn Error + 'static)) -> Failure {
+        if let Some(e) = error.downcast_ref::<reqwest::Error>() {
+            if e.is_connect() {
+                Failure::Connect
+            } else if e.is_timeout() {
+                Failure::Transient
+            } else {
+                Failure::Fatal
+            }
+        } else if let Some(e) = error.downcast_ref::<Failed>() {
+            if self.retcodes.contains(&e.retcode) {
+                Failure::Transient
+            } else {
+                Failure::Fatal
+            }
+        } else {
+            Failure::Fatal
+        }
+    }
+
+    /// 调用`action`出现`error`后是否应该重试
+    pub fn should_retry(&self, action: &str, error: &(dyn Error + 'static)) -> bool {
+        match (self.retry_on(action), self.failure(error)) {
+            (RetryOn::Never, _) | (_, Failure::Fatal) => false,
+            (RetryOn::Connect, failure) => failure == Failure::Connect,
+            (RetryOn::Transient, _) => true,
+        }
+    }
+
+    /// 第`attempt`次重试（从0开始）前等待的时间，不含抖动
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = self.multiplier.powi(attempt.min(i32::MAX as u32) as i32);
+        let secs = self.initial_interval.as_secs_f64() * factor;
+        Duration::from_secs_f64(secs.min(self.max_interval.as_secs_f64()))
+    }
+
+    /// 第`attempt`次重试前等待的时间，已用时`elapsed`，返回`None`表示不再重试
+    pub fn next_delay(
+        &self,
+        action: &str,
+        error: &(dyn Error + 'static),
+        attempt: u32,
+        elapsed: Duration,
+    ) -> Option<Duration> {
+        if !self.should_retry(action, error) {
+            return None;
+        }
+        let delay = self
+            .backoff(attempt)
+            .mul_f64(1.0 + self.jitter * random_unit());
+        (elapsed + delay <= self.max_elapsed_time).then_some(delay)
+    }
+}
+
+impl<S> Layer<S> for RetryPolicy {
+    type Service = Retrying<S>;
+
+    fn layer(&self, inner: S) -> Retrying<S> {
+        Retrying {
+            inner,
+            policy: self.clone(),
+        }
+    }
+}
+
+/// 作为中间件使用时，`RetryPolicy`包装后的`Service`
+#[derive(Debug, Clone)]
+pub struct Retrying<S> {
+    inner: S,
+    policy: RetryPolicy,
+}
+
+#[async_trait]
+impl<S: Service> Service for Retrying<S> {
+    async fn call(&self, action: &str, params: Value) -> Result<Outcome<Value>> {
+        let start = Instant::now();
+        let mut attempt = 0;
+        loop {
+            // 错误不是`Send`，不能在等待期间持有
+            let delay = match self.inner.call(action, params.clone()).await {
+                Ok(outcome) => return Ok(outcome),
+                Err(e) => {
+                    match self
+                        .policy
+                        .next_delay(action, e.as_ref(), attempt, start.elapsed())
+                    {
+                        Some(delay) => delay,
+                        None => return Err(e),
+                    }
+                }
+            };
+            log::warn!(
+                "API`{}`调用失败，{:?}后第{}次重试",
+                action,
+                delay,
+                attempt + 1
+            );
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+}
+
+/// `[-1, 1]`中的随机数
+fn random_unit() -> f64 {
+    let bits = RandomState::new().build_hasher().finish();
+    (bits >> 11) as f64 / (1u64 << 53) as f64 * 2.0 - 1.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RetryOn, RetryPolicy};
+    use crate::api::client::HttpClient;
+    use crate::api::mode::Failed;
+    use crate::api::service::Layer;
+    use crate::api::GoCqhttpAPI;
+    use std::time::{Duration, Instant};
+
+    fn failed(retcode: i32) -> Failed {
+        Failed {
+            action: "get_status".to_string(),
+            retcode,
+            message: String::new(),
+            wording: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_retry_on() {
+        assert_eq!(RetryOn::default_for("get_msg"), RetryOn::Transient);
+        assert_eq!(
+            RetryOn::default_for("_get_group_notice"),
+            RetryOn::Transient
+        );
+        assert_eq!(RetryOn::default_for(".get_word_slices"), RetryOn::Transient);
+        assert_eq!(RetryOn::default_for("send_group_msg"), RetryOn::Connect);
+        assert_eq!(RetryOn::default_for("set_group_ban"), RetryOn::Connect);
+        let policy = RetryPolicy::default().retcode(100);
+        assert!(policy.should_retry("get_status", &failed(100)));
+        assert!(!policy.should_retry("get_status", &failed(1404)));
+        assert!(!policy.should_retry("send_msg", &failed(100)));
+        let policy = policy.action("get_status", RetryOn::Never);
+        assert!(!policy.should_retry("get_status", &failed(100)));
+    }
+
+    #[test]
+    fn test_retry_backoff() {
+        let policy = RetryPolicy::default()
+            .initial_interval(Duration::from_millis(100))
+            .max_interval(Duration::from_millis(500))
+            .max_elapsed_time(Duration::from_secs(1))
+            .retcode(100);
+        assert_eq!(policy.backoff(0), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(400));
+        assert_eq!(policy.backoff(3), Duration::from_millis(500));
+        assert_eq!(policy.backoff(100), Duration::from_millis(500));
+        for _ in 0..100 {
+            let delay = policy
+                .next_delay("get_status", &failed(100), 1, Duration::ZERO)
+                .unwrap();
+            assert!(delay >= Duration::from_millis(100) && delay <= Duration::from_millis(300));
+        }
+        assert!(policy
+            .next_delay("get_status", &failed(100), 1, Duration::from_millis(990))
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_retry_connect_error() {
+        // 端口1上没有服务，连接会立即失败
+        let policy = RetryPolicy::default()
+            .initial_interval(Duration::from_millis(20))
+            .jitter(0.0)
+            .max_elapsed_time(Duration::from_millis(100));
+        let client = HttpClient::new("http://127.0.0.1:1");
+        let api = policy.layer(client.clone());
+        let start = Instant::now();
+        let e = api.get_status().await.err().unwrap();
+        assert!(e.downcast_ref::<reqwest::Error>().unwrap().is_connect());
+        // 20 + 40ms后重试，下一次80ms会超过上限
+        assert!(start.elapsed() >= Duration::from_millis(60));
+
+        let api = policy.action("get_status", RetryOn::Never).layer(client);
+        let start = Instant::now();
+        assert!(api.get_status().await.is_err());
+        assert!(start.elapsed() < Duration::from_millis(20));
+    }
+}
@@ -0,0 +1,445 @@
+//! 好友、群和群成员的缓存
+//!
+//! `Roster`在第一次查询时通过API加载列表，之后在有效期内直接返回缓存，并根据通知事件更新缓存
+
+use super::data::{Friend, GroupInfo, GroupMemberInfo, GroupRole};
+use super::mode::Failed;
+use super::params::{GetGroupList, GetGroupMemberInfo, GetGroupMemberList, GetStrangerInfo};
+use super::GoCqhttpAPI;
+use crate::event::notice::NoticeEvent;
+use crate::Result;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+struct Entry<T> {
+    value: T,
+    updated: Instant,
+}
+
+impl<T> Entry<T> {
+    fn new(value: T) -> Self {
+        Self {
+            value,
+            updated: Instant::now(),
+        }
+    }
+}
+
+/// 有效期内的缓存
+fn fresh<T>(entry: Option<&Entry<T>>, ttl: Duration) -> Option<&T> {
+    entry
+        .filter(|e| e.updated.elapsed() < ttl)
+        .map(|e| &e.value)
+}
+
+#[derive(Default)]
+struct State {
+    friends: Option<Entry<BTreeMap<i64, Friend>>>,
+    groups: Option<Entry<BTreeMap<i64, GroupInfo>>>,
+    /// 群号到该群成员列表
+    members: HashMap<i64, Entry<BTreeMap<i64, GroupMemberInfo>>>,
+}
+
+impl State {
+    /// 根据通知事件更新缓存中已有的数据，不需要调用API
+    fn apply(&mut self, event: &NoticeEvent) {
+        match event {
+            NoticeEvent::GroupIncrease(e) => {
+                if e.user_id == e.self_id {
+                    // 加入了新的群，群列表需要重新加载
+                    self.groups = None;
+                } else if let Some(group) = self.group_mut(e.group_id) {
+                    group.member_count += 1;
+                }
+            }
+            NoticeEvent::GroupDecrease(e) => {
+                if e.user_id == e.self_id || e.sub_type == "kick_me" {
+                    if let Some(groups) = &mut self.groups {
+                        groups.value.remove(&e.group_id);
+                    }
+                    self.members.remove(&e.group_id);
+                    return;
+                }
+                if let Some(members) = self.members.get_mut(&e.group_id) {
+                    members.value.remove(&e.user_id);
+                }
+                if let Some(group) = self.group_mut(e.group_id) {
+                    group.member_count -= 1;
+                }
+            }
+            NoticeEvent::GroupAdmin(e) => {
+                if let Some(member) = self.member_mut(e.group_id, e.user_id) {
+                    member.role = if e.sub_type == "set" {
+                        GroupRole::Admin
+                    } else {
+                        GroupRole::Member
+                    };
+                }
+            }
+            NoticeEvent::GroupCard(e) => {
+                if let Some(member) = self.member_mut(e.group_id, e.user_id) {
+                    member.card = e.card_new.clone();
+                }
+            }
+            NoticeEvent::FriendAdd(_) => {}
+        }
+    }
+
+    fn group_mut(&mut self, group_id: i64) -> Option<&mut GroupInfo> {
+        self.groups.as_mut()?.value.get_mut(&group_id)
+    }
+
+    fn member_mut(&mut self, group_id: i64, user_id: i64) -> Option<&mut GroupMemberInfo> {
+        self.members.get_mut(&group_id)?.value.get_mut(&user_id)
+    }
+}
+
+/// 好友、群和群成员的缓存
+///
+/// 查询方法的`no_cache`为`true`时总是调用API并更新缓存，否则在有效期（`ttl`，默认5分钟）内不会调用API。
+/// 收到通知事件时调用`update`使缓存保持最新
+pub struct Roster<A> {
+    api: A,
+    ttl: Duration,
+    state: Mutex<State>,
+}
+
+impl<A: GoCqhttpAPI> Roster<A> {
+    pub fn new(api: A) -> Self {
+        Self {
+            api,
+            ttl: Duration::from_secs(5 * 60),
+            state: Mutex::new(State::default()),
+        }
+    }
+
+    /// 设置缓存的有效期
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    pub fn api(&self) -> &A {
+        &self.api
+    }
+
+    /// 清空所有缓存
+    pub fn clear(&self) {
+        *self.state() = State::default();
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 根据通知事件更新缓存
+    ///
+    /// 新成员入群和新增好友时会调用API获取其信息，其余事件直接修改缓存
+    pub async fn update(&self, event: &NoticeEvent) -> Result<()> {
+        self.state().apply(event);
+        match event {
+            NoticeEvent::GroupIncrease(e)
+                if e.user_id != e.self_id && self.state().members.contains_key(&e.group_id) =>
+            {
+                self.member(e.group_id, e.user_id, true).await?;
+            }
+            NoticeEvent::FriendAdd(e) if self.state().friends.is_some() => {
+                let info = self
+                    .api
+                    .get_stranger_info(GetStrangerInfo::new(e.user_id))
+                    .await?;
+                let friend = Friend {
+                    user_id: e.user_id,
+                    nickname: info.nickname,
+                    remark: String::new(),
+                };
+                if let Some(friends) = &mut self.state().friends {
+                    friends.value.insert(e.user_id, friend);
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// 好友列表
+    pub async fn friends(&self, no_cache: bool) -> Result<Vec<Friend>> {
+        if !no_cache {
+            if let Some(friends) = fresh(self.state().friends.as_ref(), self.ttl) {
+                return Ok(friends.values().cloned().collect());
+            }
+        }
+        let friends = self.api.get_friend_list().await?;
+        let map = friends.iter().map(|f| (f.user_id, f.clone())).collect();
+        self.state().friends = Some(Entry::new(map));
+        Ok(friends)
+    }
+
+    /// 查找好友，不是好友时返回`None`
+    pub async fn friend(&self, user_id: i64, no_cache: bool) -> Result<Option<Friend>> {
+        if !no_cache {
+            if let Some(friends) = fresh(self.state().friends.as_ref(), self.ttl) {
+                return Ok(friends.get(&user_id).cloned());
+            }
+        }
+        let friends = self.friends(true).await?;
+        Ok(friends.into_iter().find(|f| f.user_id == user_id))
+    }
+
+    /// 群列表
+    pub async fn groups(&self, no_cache: bool) -> Result<Vec<GroupInfo>> {
+        if !no_cache {
+            if let Some(groups) = fresh(self.state().groups.as_ref(), self.ttl) {
+                return Ok(groups.values().cloned().collect());
+            }
+        }
+        let groups = self
+            .api
+            .get_group_list(GetGroupList::new().no_cache(no_cache))
+            .await?;
+        let map = groups.iter().map(|g| (g.group_id, g.clone())).collect();
+        self.state().groups = Some(Entry::new(map));
+        Ok(groups)
+    }
+
+    /// 查找群，未加入该群时返回`None`
+    pub async fn group(&self, group_id: i64, no_cache: bool) -> Result<Option<GroupInfo>> {
+        if !no_cache {
+            if let Some(groups) = fresh(self.state().groups.as_ref(), self.ttl) {
+                return Ok(groups.get(&group_id).cloned());
+            }
+        }
+        let groups = self.groups(true).await?;
+        Ok(groups.into_iter().find(|g| g.group_id == group_id))
+    }
+
+    /// 群成员列表
+    pub async fn members(&self, group_id: i64, no_cache: bool) -> Result<Vec<GroupMemberInfo>> {
+        if !no_cache {
+            if let Some(members) = fresh(self.state().members.get(&group_id), self.ttl) {
+                return Ok(members.values().cloned().collect());
+            }
+        }
+        let members = self
+            .api
+            .get_group_member_list(GetGroupMemberList::new(group_id).no_cache(no_cache))
+            .await?;
+        let map = members.iter().map(|m| (m.user_id, m.clone())).collect();
+        self.state().members.insert(group_id, Entry::new(map));
+        Ok(members)
+    }
+
+    /// 查找群成员，例如获取成员`user_id`在群`group_id`中的群名片，不是群成员时返回`None`
+    ///
+    /// `no_cache`为`true`时只通过`get_group_member_info`获取该成员，而不是重新加载整个成员列表
+    pub async fn member(
+        &self,
+        group_id: i64,
+        user_id: i64,
+        no_cache: bool,
+    ) -> Result<Option<GroupMemberInfo>> {
+        if no_cache {
+            let params = GetGroupMemberInfo::new(group_id, user_id).no_cache(true);
+            let member = match self.api.get_group_member_info(params).await {
+                Ok(member) => member,
+                // 不是群成员时go-cqhttp返回`failed`
+                Err(e) if e.is::<Failed>() => {
+                    if let Some(members) = self.state().members.get_mut(&group_id) {
+                        members.value.remove(&user_id);
+                    }
+                    return Ok(None);
+                }
+                Err(e) => return Err(e),
+            };
+            if let Some(members) = self.state().members.get_mut(&group_id) {
+                members.value.insert(user_id, member.clone());
+            }
+            return Ok(Some(member));
+        }
+        if let Some(members) = fresh(self.state().members.get(&group_id), self.ttl) {
+            return Ok(members.get(&user_id).cloned());
+        }
+        let members = self.members(group_id, false).await?;
+        Ok(members.into_iter().find(|m| m.user_id == user_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fresh, Entry, Roster, State};
+    use crate::api::client::HttpClient;
+    use crate::api::data::{GroupInfo, GroupMemberInfo, GroupRole};
+    use crate::event::notice::NoticeEvent;
+    use crate::record::{self, RecordedCall, Replay};
+    use serde_json::{json, Value};
+    use std::time::Duration;
+
+    fn call(action: &str, params: Value, data: Value) -> record::Entry {
+        record::Entry::Call(RecordedCall {
+            action: action.to_string(),
+            params,
+            data,
+            submitted: false,
+            error: None,
+            retcode: None,
+        })
+    }
+
+    fn friend_list() -> record::Entry {
+        call(
+            "get_friend_list",
+            json!({}),
+            json!([{ "user_id": 2, "nickname": "a", "remark": "" }]),
+        )
+    }
+
+    fn member_list() -> record::Entry {
+        call(
+            "get_group_member_list",
+            json!({ "group_id": 1, "no_cache": false }),
+            json!([{
+                "group_id": 1, "user_id": 2, "card": "a", "sex": "unknown",
+                "role": "member", "unfriendly": false, "card_changeable": false
+            }]),
+        )
+    }
+
+    fn event(value: serde_json::Value) -> NoticeEvent {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn state() -> State {
+        let group: GroupInfo =
+            serde_json::from_value(json!({ "group_id": 1, "member_count": 2 })).unwrap();
+        let member = |user_id| -> GroupMemberInfo {
+            serde_json::from_value(json!({
+                "group_id": 1, "user_id": user_id, "card": "a", "sex": "unknown",
+                "role": "member", "unfriendly": false
+            }))
+            .unwrap()
+        };
+        let mut state = State {
+            groups: Some(Entry::new([(1, group)].into())),
+            ..Default::default()
+        };
+        state
+            .members
+            .insert(1, Entry::new([(2, member(2)), (3, member(3))].into()));
+        state
+    }
+
+    #[test]
+    fn test_roster_apply() {
+        let mut state = state();
+        state.apply(&event(json!({
+            "notice_type": "group_card", "group_id": 1, "user_id": 2, "card_new": "b"
+        })));
+        state.apply(&event(json!({
+            "notice_type": "group_admin", "sub_type": "set", "group_id": 1, "user_id": 2
+        })));
+        let member = state.member_mut(1, 2).unwrap();
+        assert_eq!(member.card, "b");
+        assert_eq!(member.role, GroupRole::Admin);
+        state.apply(&event(json!({
+            "notice_type": "group_decrease", "sub_type": "leave", "self_id": 9, "group_id": 1, "user_id": 3
+        })));
+        assert!(state.member_mut(1, 3).is_none());
+        assert_eq!(state.group_mut(1).unwrap().member_count, 1);
+        state.apply(&event(json!({
+            "notice_type": "group_decrease", "sub_type": "kick_me", "self_id": 9, "group_id": 1, "user_id": 9
+        })));
+        assert!(state.group_mut(1).is_none());
+        assert!(state.members.is_empty());
+    }
+
+    #[test]
+    fn test_roster_send() {
+        fn assert_send<T: Send>(_: T) {}
+        let roster = Roster::new(HttpClient::new("http://127.0.0.1:5700"));
+        assert_send(roster.member(1, 2, false));
+        assert_send(roster.update(&event(json!({ "notice_type": "friend_add", "user_id": 1 }))));
+    }
+
+    #[tokio::test]
+    async fn test_roster_cache() {
+        // 回放中没有的调用会返回错误，`remaining`为0说明每个记录的调用恰好被调用一次
+        let roster = Roster::new(Replay::new([friend_list(), member_list()]));
+        assert_eq!(roster.friends(false).await.unwrap().len(), 1);
+        assert_eq!(roster.friends(false).await.unwrap().len(), 1);
+        assert_eq!(
+            roster.friend(2, false).await.unwrap().unwrap().nickname,
+            "a"
+        );
+        assert_eq!(roster.member(1, 2, false).await.unwrap().unwrap().card, "a");
+        assert_eq!(roster.member(1, 2, false).await.unwrap().unwrap().card, "a");
+        assert!(roster.member(1, 3, false).await.unwrap().is_none());
+        assert_eq!(roster.api().remaining(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_roster_refetch() {
+        let roster =
+            Roster::new(Replay::new([friend_list(), friend_list()])).ttl(Duration::from_millis(50));
+        roster.friends(false).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        roster.friends(false).await.unwrap();
+        assert_eq!(roster.api().remaining(), 0);
+        assert!(roster.friends(false).await.is_ok());
+
+        let member = call(
+            "get_group_member_info",
+            json!({ "group_id": 1, "user_id": 2, "no_cache": true }),
+            json!({
+                "group_id": 1, "user_id": 2, "card": "b", "sex": "unknown",
+                "role": "admin", "unfriendly": false, "card_changeable": false
+            }),
+        );
+        let roster = Roster::new(Replay::new([
+            friend_list(),
+            friend_list(),
+            member_list(),
+            member,
+        ]));
+        roster.friends(false).await.unwrap();
+        roster.friends(true).await.unwrap();
+        roster.members(1, false).await.unwrap();
+        let member = roster.member(1, 2, true).await.unwrap().unwrap();
+        assert_eq!(member.role, GroupRole::Admin);
+        assert_eq!(roster.api().remaining(), 0);
+        // 单独获取的成员信息更新了成员列表的缓存
+        assert_eq!(roster.member(1, 2, false).await.unwrap().unwrap().card, "b");
+    }
+
+    #[tokio::test]
+    async fn test_roster_not_member() {
+        let failed = record::Entry::Call(RecordedCall {
+            action: "get_group_member_info".to_string(),
+            params: json!({ "group_id": 1, "user_id": 2, "no_cache": true }),
+            data: Value::Null,
+            submitted: false,
+            error: Some("群员不存在".to_string()),
+            retcode: Some(100),
+        });
+        let roster = Roster::new(Replay::new([member_list(), failed.clone(), failed]));
+        roster.members(1, false).await.unwrap();
+        assert!(roster.member(1, 2, true).await.unwrap().is_none());
+        // 离开的成员从缓存中移除
+        assert!(roster.member(1, 2, false).await.unwrap().is_none());
+        let increase = event(json!({
+            "notice_type": "group_increase", "sub_type": "approve", "self_id": 9,
+            "group_id": 1, "user_id": 2, "operator_id": 0
+        }));
+        roster.update(&increase).await.unwrap();
+        assert_eq!(roster.api().remaining(), 0);
+        // 其他错误仍然返回
+        assert!(roster.member(1, 3, true).await.is_err());
+    }
+
+    #[test]
+    fn test_roster_ttl() {
+        let state = state();
+        assert!(fresh(state.groups.as_ref(), Duration::from_secs(60)).is_some());
+        assert!(fresh(state.groups.as_ref(), Duration::ZERO).is_none());
+    }
+}
@@ -0,0 +1,270 @@
+//! 沙盒模式：记录但不执行修改状态的API，避免开发时误踢人、误退群
+//!
+//! ```no_run
+//! # async fn f() -> rust_gocqhttp::error::Result<()> {
+//! use rust_gocqhttp::api::client::HttpClient;
+//! use rust_gocqhttp::api::params::SetGroupKick;
+//! use rust_gocqhttp::api::sandbox::Sandbox;
+//! use rust_gocqhttp::api::service::Layer;
+//! use rust_gocqhttp::api::GoCqhttpAPI;
+//!
+//! let sandbox = Sandbox::new().allow_group(123456);
+//! let api = sandbox.layer(HttpClient::new("http://127.0.0.1:5700"));
+//! // 不会执行，只记录日志
+//! api.set_group_kick(SetGroupKick::new(1, 10000)).await?;
+//! // 测试群中正常执行
+//! api.set_group_kick(SetGroupKick::new(123456, 10000)).await?;
+//! assert_eq!(sandbox.blocked().len(), 1);
+//! # Ok(())
+//! # }
+//! ```
+
+use super::mode::Outcome;
+use super::service::{Layer, Service};
+use crate::Result;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// 发送消息的API
+const SEND_ACTIONS: [&str; 7] = [
+    "send_private_msg",
+    "send_group_msg",
+    "send_msg",
+    "send_group_forward_msg",
+    "send_private_forward_msg",
+    "send_forward_msg",
+    "send_guild_channel_msg",
+];
+
+/// API的种类
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum ActionKind {
+    /// 只读取数据，例如`get_*`, `can_*`, `check_*`, `ocr_image`
+    Read,
+    /// 发送消息
+    Send,
+    /// 其余修改状态的API，例如踢人、禁言、退群、撤回消息、删除文件、修改设置
+    Write,
+}
+
+impl ActionKind {
+    /// `action`的种类，未知的API视为`Write`
+    pub fn of(action: &str) -> Self {
+        let name = action.trim_start_matches(['.', '_']);
+        let read = ["get_", "can_", "check_", "qidian_get_", "ocr_"]
+            .iter()
+            .any(|prefix| name.starts_with(prefix));
+        if read {
+            ActionKind::Read
+        } else if SEND_ACTIONS.contains(&action) {
+            ActionKind::Send
+        } else {
+            ActionKind::Write
+        }
+    }
+}
+
+/// 未执行时返回的数据，需要返回消息ID、文件路径等的API返回`0`或空字符串
+fn dry_run_response(action: &str) -> Value {
+    match action {
+        "download_file" => json!({ "file": "" }),
+        "send_guild_channel_msg" => json!({ "message_id": "" }),
+        "send_group_forward_msg" | "send_private_forward_msg" | "send_forward_msg" => {
+            json!({ "message_id": 0, "forward_id": "" })
+        }
+        _ if SEND_ACTIONS.contains(&action) => json!({ "message_id": 0 }),
+        "create_guild_role" => json!({ "role_id": "" }),
+        _ => Value::Null,
+    }
+}
+
+/// 被拦截的一次调用
+#[derive(Debug, Clone, PartialEq)]
+pub struct Blocked {
+    pub action: String,
+    pub params: Value,
+}
+
+/// 沙盒中间件：`Write`种类的API（默认还包括`block_send`启用后的`Send`）只记录不执行，返回成功
+///
+/// 参数中有`group_id`且该群在白名单中时正常执行；`delete_msg`、`delete_friend`等参数中没有群号的API总是被拦截。
+/// `allow`和`block`可以覆盖单个API的处理方式
+#[derive(Clone, Default)]
+pub struct Sandbox {
+    groups: HashSet<i64>,
+    block_send: bool,
+    overrides: HashMap<String, bool>,
+    blocked: Arc<Mutex<Vec<Blocked>>>,
+}
+
+impl Sandbox {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 允许在群`group_id`中执行所有API
+    pub fn allow_group(mut self, group_id: i64) -> Self {
+        self.groups.insert(group_id);
+        self
+    }
+
+    /// 同时拦截发送消息的API
+    pub fn block_send(mut self) -> Self {
+        self.block_send = true;
+        self
+    }
+
+    /// 总是执行`action`
+    pub fn allow(mut self, action: impl Into<String>) -> Self {
+        self.overrides.insert(action.into(), true);
+        self
+    }
+
+    /// 总是拦截`action`，包括白名单中的群
+    pub fn block(mut self, action: impl Into<String>) -> Self {
+        self.overrides.insert(action.into(), false);
+        self
+    }
+
+    /// 以`params`调用`action`时是否执行
+    pub fn permits(&self, action: &str, params: &Value) -> bool {
+        if let Some(allow) = self.overrides.get(action) {
+            return *allow;
+        }
+        match ActionKind::of(action) {
+            ActionKind::Read => true,
+            ActionKind::Send if !self.block_send => true,
+            ActionKind::Send | ActionKind::Write => params["group_id"]
+                .as_i64()
+                .is_some_and(|group_id| self.groups.contains(&group_id)),
+        }
+    }
+
+    fn blocked_calls(&self) -> MutexGuard<'_, Vec<Blocked>> {
+        self.blocked.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 所有被拦截的调用，克隆的`Sandbox`共享同一份记录
+    pub fn blocked(&self) -> Vec<Blocked> {
+        self.blocked_calls().clone()
+    }
+}
+
+impl<S> Layer<S> for Sandbox {
+    type Service = Sandboxed<S>;
+
+    fn layer(&self, inner: S) -> Sandboxed<S> {
+        Sandboxed {
+            inner,
+            sandbox: self.clone(),
+        }
+    }
+}
+
+/// `Sandbox`包装后的`Service`
+pub struct Sandboxed<S> {
+    inner: S,
+    sandbox: Sandbox,
+}
+
+#[async_trait]
+impl<S: Service> Service for Sandboxed<S> {
+    async fn call(&self, action: &str, params: Value) -> Result<Outcome<Value>> {
+        if self.sandbox.permits(action, &params) {
+            return self.inner.call(action, params).await;
+        }
+        log::info!("沙盒模式，未执行API`{}`: {}", action, params);
+        self.sandbox.blocked_calls().push(Blocked {
+            action: action.to_string(),
+            params,
+        });
+        Ok(Outcome::Completed(dry_run_response(action)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{dry_run_response, ActionKind, Sandbox};
+    use crate::api::for_each_api;
+    use crate::api::params::{Action, DeleteMsg, SendGroupMsg, SetGroupKick};
+    use crate::api::service::Layer;
+    use crate::api::GoCqhttpAPI;
+    use crate::message::Message;
+    use crate::record::Replay;
+    use serde_json::{json, Value};
+
+    /// `A`的响应数据能否从`data`反序列化
+    fn check<A: Action>(data: Value) -> serde_json::Result<()> {
+        serde_json::from_value::<A::Output>(data).map(|_| ())
+    }
+
+    /// 所有API的名称和对应的`check`
+    macro_rules! checks {
+        (
+            gocqhttp {
+                unit { $($unit:ident: $unit_params:ty,)* }
+                params { $($method:ident: $params:ty,)* }
+            }
+            guild {
+                unit { $($guild_unit:ident: $guild_unit_params:ty,)* }
+                params { $($guild_method:ident: $guild_params:ty,)* }
+            }
+        ) => {
+            [
+                $((<$unit_params>::NAME, check::<$unit_params> as fn(Value) -> _),)*
+                $((<$params>::NAME, check::<$params>),)*
+                $((<$guild_unit_params>::NAME, check::<$guild_unit_params>),)*
+                $((<$guild_params>::NAME, check::<$guild_params>),)*
+            ]
+        };
+    }
+
+    #[test]
+    fn test_action_kind() {
+        assert_eq!(ActionKind::of("get_group_list"), ActionKind::Read);
+        assert_eq!(ActionKind::of("_get_group_notice"), ActionKind::Read);
+        assert_eq!(ActionKind::of("send_group_msg"), ActionKind::Send);
+        assert_eq!(ActionKind::of("set_group_kick"), ActionKind::Write);
+        assert_eq!(ActionKind::of("delete_group_file"), ActionKind::Write);
+        assert_eq!(ActionKind::of("send_group_sign"), ActionKind::Write);
+        assert_eq!(ActionKind::of("unknown_action"), ActionKind::Write);
+    }
+
+    #[tokio::test]
+    async fn test_sandbox() {
+        // 没有任何记录，实际执行的调用都会失败
+        let sandbox = Sandbox::new().allow_group(1).block_send();
+        let api = sandbox.layer(Replay::new([]));
+        api.set_group_kick(SetGroupKick::new(2, 10)).await.unwrap();
+        assert!(api.set_group_kick(SetGroupKick::new(1, 10)).await.is_err());
+        api.delete_msg(DeleteMsg::new(1)).await.unwrap();
+        let sent = api
+            .send_group_msg(SendGroupMsg::new(2, "hi".parse::<Message>().unwrap()))
+            .await
+            .unwrap();
+        assert_eq!(sent.message_id, 0);
+        assert!(api.get_status().await.is_err());
+        let blocked = sandbox.blocked();
+        assert_eq!(blocked.len(), 3);
+        assert_eq!(blocked[0].action, "set_group_kick");
+        assert_eq!(blocked[0].params["group_id"], 2);
+
+        let sandbox = Sandbox::new().allow("delete_msg").block("get_status");
+        assert!(sandbox.permits("delete_msg", &json!({ "message_id": 1 })));
+        assert!(!sandbox.permits("get_status", &json!({})));
+    }
+
+    #[test]
+    fn test_dry_run_response() {
+        let sandbox = Sandbox::new().block_send();
+        for (action, check) in for_each_api!(checks) {
+            if !sandbox.permits(action, &json!({})) {
+                if let Err(e) = check(dry_run_response(action)) {
+                    panic!("dry-run response of `{}`: {}", action, e);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,402 @@
+//! API调用的统一入口与中间件
+//!
+//! 所有`GoCqhttpAPI`和`GuildAPI`的方法最终都通过`Service::call(action, params)`调用，
+//! 实现了`Service`的类型自动实现这两个trait。中间件（`Layer`）包装一个`Service`，
+//! 在调用前后加入日志、统计、限速、记录等逻辑，用法与tower相同：
+//!
+//! ```no_run
+//! # async fn f() -> rust_gocqhttp::error::Result<()> {
+//! use rust_gocqhttp::api::client::HttpClient;
+//! use rust_gocqhttp::api::params::GetStrangerInfo;
+//! use rust_gocqhttp::api::service::{LogLayer, Metrics, ServiceBuilder};
+//! use rust_gocqhttp::api::GoCqhttpAPI;
+//!
+//! let metrics = Metrics::new();
+//! // 先添加的中间件在外层
+//! let api = ServiceBuilder::new()
+//!     .layer(LogLayer)
+//!     .layer(metrics.clone())
+//!     .service(HttpClient::new("http://127.0.0.1:5700"));
+//! api.get_stranger_info(GetStrangerInfo::new(10000)).await?;
+//! assert_eq!(metrics.get("get_stranger_info").calls, 1);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! `Service::call`只传递API名称和参数，中间件不能读取或修改HTTP请求头等传输层的信息。
+//! 认证等请求头通过`HttpClient::header`设置，或通过`HttpClient::with_client`使用设置了默认请求头的`reqwest::Client`
+
+use super::for_each_api;
+use super::guild::GuildAPI;
+use super::mode::Outcome;
+use super::params::Action;
+use super::GoCqhttpAPI;
+use crate::Result;
+use async_trait::async_trait;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// 调用API的统一入口
+#[async_trait]
+pub trait Service: Send + Sync {
+    /// 调用`action`，`status`为`failed`时返回`mode::Failed`错误
+    async fn call(&self, action: &str, params: Value) -> Result<Outcome<Value>>;
+}
+
+#[async_trait]
+impl<S: Service + ?Sized> Service for &S {
+    async fn call(&self, action: &str, params: Value) -> Result<Outcome<Value>> {
+        (**self).call(action, params).await
+    }
+}
+
+#[async_trait]
+impl<S: Service + ?Sized> Service for Box<S> {
+    async fn call(&self, action: &str, params: Value) -> Result<Outcome<Value>> {
+        (**self).call(action, params).await
+    }
+}
+
+#[async_trait]
+impl<S: Service + ?Sized> Service for Arc<S> {
+    async fn call(&self, action: &str, params: Value) -> Result<Outcome<Value>> {
+        (**self).call(action, params).await
+    }
+}
+
+/// 通过`service`调用`params`对应的API
+pub async fn execute<S, A>(service: &S, params: A) -> Result<A::Output>
+where
+    S: Service + ?Sized,
+    A: Action,
+{
+    let params = serde_json::to_value(&params)?;
+    service.call(A::NAME, params).await?.into_result(A::NAME)
+}
+
+macro_rules! impl_api_for_service {
+    (
+        gocqhttp {
+            unit { $($unit:ident: $unit_params:ty,)* }
+            params { $($method:ident: $params:ty,)* }
+        }
+        guild {
+            unit { $($guild_unit:ident: $guild_unit_params:ty,)* }
+            params { $($guild_method:ident: $guild_params:ty,)* }
+        }
+    ) => {
+        #[async_trait]
+        impl<S: Service + ?Sized> GoCqhttpAPI for S {
+            $(
+                async fn $unit(&self) -> Result<<$unit_params as Action>::Output> {
+                    execute(self, <$unit_params>::new()).await
+                }
+            )*
+            $(
+                async fn $method(&self, params: $params) -> Result<<$params as Action>::Output> {
+                    execute(self, params).await
+                }
+            )*
+        }
+
+        #[async_trait]
+        impl<S: Service + ?Sized> GuildAPI for S {
+            $(
+                async fn $guild_unit(&self) -> Result<<$guild_unit_params as Action>::Output> {
+                    execute(self, <$guild_unit_params>::new()).await
+                }
+            )*
+            $(
+                async fn $guild_method(
+                    &self,
+                    params: $guild_params,
+                ) -> Result<<$guild_params as Action>::Output> {
+                    execute(self, params).await
+                }
+            )*
+        }
+    };
+}
+
+for_each_api!(impl_api_for_service);
+
+/// 中间件，将`S`包装为新的`Service`
+pub trait Layer<S> {
+    type Service;
+
+    fn layer(&self, inner: S) -> Self::Service;
+}
+
+/// 不做任何包装的中间件
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Identity;
+
+impl<S> Layer<S> for Identity {
+    type Service = S;
+
+    fn layer(&self, inner: S) -> S {
+        inner
+    }
+}
+
+/// 两个中间件的组合，`Outer`在外层
+#[derive(Debug, Clone)]
+pub struct Stack<Inner, Outer> {
+    inner: Inner,
+    outer: Outer,
+}
+
+impl<S, Inner, Outer> Layer<S> for Stack<Inner, Outer>
+where
+    Inner: Layer<S>,
+    Outer: Layer<Inner::Service>,
+{
+    type Service = Outer::Service;
+
+    fn layer(&self, service: S) -> Self::Service {
+        self.outer.layer(self.inner.layer(service))
+    }
+}
+
+/// 组合多个中间件，先添加的中间件在外层，最先处理调用
+#[derive(Debug, Clone)]
+pub struct ServiceBuilder<L> {
+    layer: L,
+}
+
+impl ServiceBuilder<Identity> {
+    pub fn new() -> Self {
+        Self { layer: Identity }
+    }
+}
+
+impl Default for ServiceBuilder<Identity> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<L> ServiceBuilder<L> {
+    /// 在已添加的中间件内层添加`layer`
+    pub fn layer<T>(self, layer: T) -> ServiceBuilder<Stack<T, L>> {
+        ServiceBuilder {
+            layer: Stack {
+                inner: layer,
+                outer: self.layer,
+            },
+        }
+    }
+
+    /// 用所有中间件包装`service`
+    pub fn service<S>(&self, service: S) -> L::Service
+    where
+        L: Layer<S>,
+    {
+        self.layer.layer(service)
+    }
+}
+
+/// 记录每次调用及其耗时的中间件，成功时为`debug`级别，失败时为`warn`级别
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LogLayer;
+
+impl<S> Layer<S> for LogLayer {
+    type Service = Logged<S>;
+
+    fn layer(&self, inner: S) -> Logged<S> {
+        Logged { inner }
+    }
+}
+
+/// `LogLayer`包装后的`Service`
+#[derive(Debug, Clone)]
+pub struct Logged<S> {
+    inner: S,
+}
+
+#[async_trait]
+impl<S: Service> Service for Logged<S> {
+    async fn call(&self, action: &str, params: Value) -> Result<Outcome<Value>> {
+        log::debug!("调用API`{}`: {}", action, params);
+        let start = Instant::now();
+        let result = self.inner.call(action, params).await;
+        match &result {
+            Ok(_) => log::debug!("API`{}`调用完成，耗时{:?}", action, start.elapsed()),
+            Err(e) => log::warn!("API`{}`调用失败，耗时{:?}: {}", action, start.elapsed(), e),
+        }
+        result
+    }
+}
+
+/// 单个API的调用统计
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct ActionStats {
+    /// 调用次数
+    pub calls: u64,
+    /// 失败次数
+    pub failures: u64,
+    /// 总耗时
+    pub total_time: Duration,
+}
+
+/// 按API统计调用次数、失败次数和耗时的中间件，克隆后共享同一份统计
+#[derive(Debug, Clone, Default)]
+pub struct Metrics {
+    stats: Arc<Mutex<HashMap<String, ActionStats>>>,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn stats(&self) -> MutexGuard<'_, HashMap<String, ActionStats>> {
+        self.stats.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// `action`的统计，未调用过时全为0
+    pub fn get(&self, action: &str) -> ActionStats {
+        self.stats().get(action).copied().unwrap_or_default()
+    }
+
+    /// 所有API的统计
+    pub fn snapshot(&self) -> HashMap<String, ActionStats> {
+        self.stats().clone()
+    }
+
+    /// 清空统计
+    pub fn reset(&self) {
+        self.stats().clear();
+    }
+}
+
+impl<S> Layer<S> for Metrics {
+    type Service = Measured<S>;
+
+    fn layer(&self, inner: S) -> Measured<S> {
+        Measured {
+            inner,
+            metrics: self.clone(),
+        }
+    }
+}
+
+/// `Metrics`包装后的`Service`
+#[derive(Debug, Clone)]
+pub struct Measured<S> {
+    inner: S,
+    metrics: Metrics,
+}
+
+#[async_trait]
+impl<S: Service> Service for Measured<S> {
+    async fn call(&self, action: &str, params: Value) -> Result<Outcome<Value>> {
+        let start = Instant::now();
+        let result = self.inner.call(action, params).await;
+        let mut stats = self.metrics.stats();
+        let entry = stats.entry(action.to_string()).or_default();
+        entry.calls += 1;
+        entry.failures += result.is_err() as u64;
+        entry.total_time += start.elapsed();
+        drop(stats);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Layer, Metrics, Service, ServiceBuilder};
+    use crate::api::mode::{Failed, Outcome};
+    use crate::api::params::{DeleteMsg, GetStrangerInfo};
+    use crate::api::GoCqhttpAPI;
+    use crate::Result;
+    use async_trait::async_trait;
+    use serde_json::{json, Value};
+    use std::sync::Mutex;
+
+    /// 记录调用并返回固定结果
+    #[derive(Default)]
+    struct Echo {
+        calls: Mutex<Vec<(String, Value)>>,
+    }
+
+    #[async_trait]
+    impl Service for Echo {
+        async fn call(&self, action: &str, params: Value) -> Result<Outcome<Value>> {
+            self.calls
+                .lock()
+                .unwrap()
+                .push((action.to_string(), params.clone()));
+            if action == "delete_msg" {
+                return Err(Box::new(Failed {
+                    action: action.to_string(),
+                    retcode: 100,
+                    message: String::new(),
+                    wording: String::new(),
+                }));
+            }
+            Ok(Outcome::Completed(
+                json!({ "user_id": params["user_id"], "nickname": "a", "sex": "male" }),
+            ))
+        }
+    }
+
+    /// 在参数中加入`tag`字段的中间件
+    struct Tag(&'static str);
+
+    struct Tagged<S>(S, &'static str);
+
+    impl<S> Layer<S> for Tag {
+        type Service = Tagged<S>;
+
+        fn layer(&self, inner: S) -> Tagged<S> {
+            Tagged(inner, self.0)
+        }
+    }
+
+    #[async_trait]
+    impl<S: Service> Service for Tagged<S> {
+        async fn call(&self, action: &str, mut params: Value) -> Result<Outcome<Value>> {
+            let tags = params["tag"].as_str().unwrap_or_default().to_string();
+            params["tag"] = json!(tags + self.1);
+            self.0.call(action, params).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_service_api() {
+        let echo = Echo::default();
+        let info = echo
+            .get_stranger_info(GetStrangerInfo::new(1))
+            .await
+            .unwrap();
+        assert_eq!(info.user_id, 1);
+        let calls = echo.calls.lock().unwrap();
+        assert_eq!(
+            calls[0],
+            ("get_stranger_info".to_string(), json!({ "user_id": 1 }))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_service_builder() {
+        let metrics = Metrics::new();
+        let api = ServiceBuilder::new()
+            .layer(Tag("a"))
+            .layer(metrics.clone())
+            .layer(Tag("b"))
+            .service(Echo::default());
+        api.get_stranger_info(GetStrangerInfo::new(1))
+            .await
+            .unwrap();
+        assert!(api.delete_msg(DeleteMsg::new(1)).await.is_err());
+        // 先添加的中间件先处理
+        let echo = &api.0.inner.0;
+        assert_eq!(echo.calls.lock().unwrap()[0].1["tag"], "ab");
+        assert_eq!(metrics.get("get_stranger_info").calls, 1);
+        assert_eq!(metrics.get("delete_msg").failures, 1);
+        assert_eq!(metrics.get("get_status").calls, 0);
+    }
+}
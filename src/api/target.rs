@@ -0,0 +1,187 @@
+//! 按发送目标发送消息，以及回复消息事件
+
+use super::guild::params::SendGuildChannelMsg;
+use super::guild::GuildAPI;
+use super::params::{SendGroupMsg, SendPrivateMsg};
+use super::GoCqhttpAPI;
+use crate::event::message::MessageEvent;
+use crate::message::cq_code::code::Reply;
+use crate::message::cq_code::CQCode;
+use crate::message::{Message, MessageType};
+use crate::Result;
+use async_trait::async_trait;
+
+/// 消息的发送目标
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Target {
+    /// 私聊，`via_group`为临时会话的来源群号
+    Private {
+        user_id: i64,
+        via_group: Option<i64>,
+    },
+    /// 群聊
+    Group { group_id: i64 },
+    /// 子频道
+    GuildChannel {
+        guild_id: String,
+        channel_id: String,
+    },
+}
+
+impl Target {
+    /// 私聊好友
+    pub fn private(user_id: i64) -> Self {
+        Target::Private {
+            user_id,
+            via_group: None,
+        }
+    }
+
+    /// 通过群`group_id`发起临时会话
+    pub fn temp(user_id: i64, group_id: i64) -> Self {
+        Target::Private {
+            user_id,
+            via_group: Some(group_id),
+        }
+    }
+
+    pub fn group(group_id: i64) -> Self {
+        Target::Group { group_id }
+    }
+
+    pub fn guild_channel(guild_id: impl Into<String>, channel_id: impl Into<String>) -> Self {
+        Target::GuildChannel {
+            guild_id: guild_id.into(),
+            channel_id: channel_id.into(),
+        }
+    }
+}
+
+impl From<&MessageEvent> for Target {
+    /// 消息事件的来源：群消息回到群中，私聊回到发送者（临时会话保留来源群），频道消息回到子频道
+    fn from(event: &MessageEvent) -> Self {
+        match event {
+            MessageEvent::Private(e) => Target::Private {
+                user_id: e.user_id,
+                via_group: e.sender.group_id,
+            },
+            MessageEvent::Group(e) => Target::group(e.group_id),
+            MessageEvent::Guild(e) => Target::guild_channel(&e.guild_id, &e.channel_id),
+        }
+    }
+}
+
+/// 发送消息后返回的消息ID
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum SentMessageID {
+    /// 私聊或群聊消息
+    Chat(i32),
+    /// 频道消息
+    Guild(String),
+}
+
+/// 在`message`开头加上引用`message_id`的回复
+pub fn quote(message_id: i32, mut message: Message) -> Result<Message> {
+    let reply = Reply {
+        id: Some(message_id),
+        text: None,
+        qq: None,
+        time: None,
+        seq: None,
+    };
+    let segment = match message.message_type() {
+        MessageType::String => reply.to_string(),
+        MessageType::Array => reply.to_json()?,
+    };
+    message.messages.insert(0, segment);
+    Ok(message)
+}
+
+/// 按`Target`发送消息，为同时实现了`GoCqhttpAPI`和`GuildAPI`的类型自动实现
+#[async_trait]
+pub trait SendAPI: GoCqhttpAPI + GuildAPI + Sync {
+    /// 根据`target`选择`send_private_msg`、`send_group_msg`或`send_guild_channel_msg`发送
+    async fn send(&self, target: Target, message: Message) -> Result<SentMessageID> {
+        match target {
+            Target::Private { user_id, via_group } => {
+                let mut params = SendPrivateMsg::new(user_id, message);
+                params.group_id = via_group;
+                Ok(SentMessageID::Chat(
+                    self.send_private_msg(params).await?.message_id,
+                ))
+            }
+            Target::Group { group_id } => Ok(SentMessageID::Chat(
+                self.send_group_msg(SendGroupMsg::new(group_id, message))
+                    .await?
+                    .message_id,
+            )),
+            Target::GuildChannel {
+                guild_id,
+                channel_id,
+            } => Ok(SentMessageID::Guild(
+                self.send_guild_channel_msg(SendGuildChannelMsg::new(
+                    guild_id, channel_id, message,
+                ))
+                .await?
+                .message_id,
+            )),
+        }
+    }
+
+    /// 回复消息事件，发送到事件的来源
+    ///
+    /// `quote`为`true`时通过`Reply`引用原消息，频道消息不支持引用，会忽略`quote`
+    async fn reply_to(
+        &self,
+        event: &MessageEvent,
+        message: Message,
+        quote: bool,
+    ) -> Result<SentMessageID> {
+        let message = match event.message_id() {
+            Some(id) if quote => self::quote(id, message)?,
+            _ => message,
+        };
+        self.send(Target::from(event), message).await
+    }
+}
+
+impl<T: GoCqhttpAPI + GuildAPI + Sync + ?Sized> SendAPI for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::{quote, Target};
+    use crate::api::params::SendGroupMsg;
+    use crate::event::message::MessageEvent;
+    use crate::message::Message;
+    use serde_json::json;
+
+    #[test]
+    fn test_target_from_event() {
+        let event: MessageEvent = serde_json::from_str(
+            r#"{"post_type":"message","message_type":"private","sub_type":"group","message_id":1,"user_id":2,"message":"hi","sender":{"user_id":2,"group_id":3}}"#,
+        )
+        .unwrap();
+        assert_eq!(Target::from(&event), Target::temp(2, 3));
+        let event: MessageEvent = serde_json::from_str(
+            r#"{"post_type":"message","message_type":"guild","message_id":"1","guild_id":"2","channel_id":"3","user_id":"4","message":"hi","sender":{"tiny_id":"4"}}"#,
+        )
+        .unwrap();
+        assert_eq!(Target::from(&event), Target::guild_channel("2", "3"));
+    }
+
+    #[test]
+    fn test_quote() {
+        let message: Message = "hi".parse().unwrap();
+        assert_eq!(quote(5, message).unwrap().to_string(), "[CQ:reply,id=5]hi");
+        // 数组格式的消息以消息段数组发送
+        let message: Message = r#"[{"type":"text","data":{"text":"hi"}}]"#.parse().unwrap();
+        let params = SendGroupMsg::new(1, quote(5, message).unwrap());
+        assert_eq!(
+            serde_json::to_value(params).unwrap()["message"],
+            json!([
+                { "type": "reply", "data": { "id": 5 } },
+                { "type": "text", "data": { "text": "hi" } }
+            ])
+        );
+    }
+}
@@ -0,0 +1,1017 @@
+//! 聊天命令的解析，例如`/ban @某人 10m 刷屏`
+//!
+//! 消息先按前缀和命令名匹配（`invocation`），剩余部分切分为参数（`Args`）：文本按空白切分，
+//! 引号（`"..."`, `'...'`, `“...”`）内的空白不切分，CQ码单独作为一个参数。参数类型实现`Arg`，
+//! 命令类型实现`Command`，解析失败时`CommandError`包含错误原因和用法，可以直接回复给发送者
+//!
+//! 通常使用`#[derive(Command)]`实现`Command`，以下为手动实现的示例：
+//!
+//! ```
+//! use rust_gocqhttp::command::{Args, Command, CommandSpec, ParseError, Rest, UserId};
+//! use rust_gocqhttp::message::Message;
+//! use std::time::Duration;
+//!
+//! struct Ban {
+//!     target: UserId,
+//!     duration: Duration,
+//!     reason: Option<Rest>,
+//! }
+//!
+//! impl Command for Ban {
+//!     fn specs() -> Vec<CommandSpec> {
+//!         vec![CommandSpec::new("ban")
+//!             .alias("禁言")
+//!             .help("禁言群成员")
+//!             .arg::<UserId>("target", "被禁言的成员")
+//!             .arg::<Duration>("duration", "禁言时长")
+//!             .arg::<Option<Rest>>("reason", "原因")]
+//!     }
+//!
+//!     fn from_args(name: &str, args: &mut Args) -> Option<Result<Self, ParseError>> {
+//!         Self::specs()[0].matches(name).then(|| {
+//!             Ok(Ban {
+//!                 target: args.parse("target")?,
+//!                 duration: args.parse("duration")?,
+//!                 reason: args.parse("reason")?,
+//!             })
+//!         })
+//!     }
+//! }
+//!
+//! let message: Message = "/禁言 [CQ:at,qq=123] 10m 刷屏 太多".parse().unwrap();
+//! let ban = Ban::parse(&message, &["/"]).unwrap().unwrap();
+//! assert_eq!(ban.target.0, 123);
+//! assert_eq!(ban.duration, Duration::from_secs(600));
+//! assert_eq!(ban.reason.unwrap().0.to_string(), "刷屏 太多");
+//! assert_eq!(
+//!     Ban::help("/"),
+//!     "/ban <target:QQ号或@成员> <duration:时长> [reason:文本...]\n  禁言群成员\n  别名：禁言\n  target：被禁言的成员\n  duration：禁言时长\n  reason：原因"
+//! );
+//! ```
+
+pub use cq_code_derive::Command;
+
+use crate::api::target::SendAPI;
+use crate::dispatcher::{Context, Handler};
+use crate::event::message::MessageEvent;
+use crate::message::cq_code::code::{At, Image};
+use crate::message::cq_code::derive::escape_text;
+use crate::message::cq_code::CQCode;
+use crate::message::Message;
+use crate::permission::Permission;
+use crate::Result;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::fmt;
+use std::future::Future;
+use std::marker::PhantomData;
+use std::time::Duration;
+
+/// 消息切分后的一个参数
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Token {
+    /// 文本，已去除引号和CQ码转义
+    Text(String),
+    /// CQ码，`kind`为CQ码的类型，`raw`为原始的CQ码字符串或JSON
+    Code { kind: String, raw: String },
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Text(text) => f.write_str(text),
+            Token::Code { kind, .. } => write!(f, "[{}]", kind),
+        }
+    }
+}
+
+/// 去除文本中的CQ码转义
+fn unescape(s: &str) -> String {
+    s.replace("&#91;", "[")
+        .replace("&#93;", "]")
+        .replace("&amp;", "&")
+}
+
+/// 消息段的类型和文本，文本段的类型为`text`
+fn segment(raw: &str) -> (String, Option<String>) {
+    if raw.starts_with('{') {
+        let value: Value = serde_json::from_str(raw).unwrap_or_default();
+        let kind = value["type"].as_str().unwrap_or_default().to_string();
+        let text = value["data"]["text"].as_str().map(str::to_string);
+        (kind, text.filter(|_| value["type"] == "text"))
+    } else if let Some(code) = raw.strip_prefix("[CQ:") {
+        let end = code.find([',', ']']).unwrap_or(code.len());
+        (code[..end].to_string(), None)
+    } else {
+        ("text".to_string(), Some(unescape(raw)))
+    }
+}
+
+/// 将文本按空白切分，引号内的空白不切分，未闭合的引号延续到文本结尾
+fn split_text(text: &str, tokens: &mut VecDeque<Token>) {
+    let mut chars = text.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        let close = match c {
+            '"' => Some('"'),
+            '\'' => Some('\''),
+            '“' => Some('”'),
+            _ => None,
+        };
+        let mut word = String::new();
+        if let Some(close) = close {
+            chars.next();
+            for c in chars.by_ref() {
+                if c == close {
+                    break;
+                }
+                word.push(c);
+            }
+        } else {
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() {
+                    break;
+                }
+                word.push(c);
+                chars.next();
+            }
+        }
+        tokens.push_back(Token::Text(word));
+    }
+}
+
+/// 将消息切分为参数
+pub fn tokenize(message: &Message) -> VecDeque<Token> {
+    let mut tokens = VecDeque::new();
+    for raw in &message.messages {
+        match segment(raw) {
+            (_, Some(text)) => split_text(&text, &mut tokens),
+            (kind, None) => tokens.push_back(Token::Code {
+                kind,
+                raw: raw.clone(),
+            }),
+        }
+    }
+    tokens
+}
+
+/// 单个参数的解析错误，不含参数名
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ArgError {
+    /// 缺少参数
+    Missing,
+    /// 参数格式错误，`value`为输入，`expected`为期望的类型
+    Invalid {
+        value: String,
+        expected: &'static str,
+    },
+}
+
+/// 命令的解析错误
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ParseError {
+    /// 缺少参数`arg`
+    Missing { arg: String },
+    /// 参数`arg`格式错误
+    Invalid {
+        arg: String,
+        value: String,
+        expected: &'static str,
+    },
+    /// 多余的参数
+    TooMany { rest: String },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Missing { arg } => write!(f, "缺少参数{}", arg),
+            ParseError::Invalid {
+                arg,
+                value,
+                expected,
+            } => write!(f, "参数{}应为{}，而不是“{}”", arg, expected, value),
+            ParseError::TooMany { rest } => write!(f, "多余的参数“{}”", rest),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// 命令的参数，按顺序取出并解析
+#[derive(Debug, Clone, Default)]
+pub struct Args {
+    tokens: VecDeque<Token>,
+}
+
+impl Args {
+    pub fn new(tokens: impl IntoIterator<Item = Token>) -> Self {
+        Self {
+            tokens: tokens.into_iter().collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    pub fn peek(&self) -> Option<&Token> {
+        self.tokens.front()
+    }
+
+    pub fn next_token(&mut self) -> Option<Token> {
+        self.tokens.pop_front()
+    }
+
+    /// 取出所有剩余参数
+    pub fn rest(&mut self) -> Vec<Token> {
+        self.tokens.drain(..).collect()
+    }
+
+    /// 解析名为`name`的参数
+    pub fn parse<T: Arg>(&mut self, name: &str) -> std::result::Result<T, ParseError> {
+        T::parse(self).map_err(|e| match e {
+            ArgError::Missing => ParseError::Missing {
+                arg: name.to_string(),
+            },
+            ArgError::Invalid { value, expected } => ParseError::Invalid {
+                arg: name.to_string(),
+                value,
+                expected,
+            },
+        })
+    }
+
+    /// 检查参数已全部解析
+    pub fn finish(&self) -> std::result::Result<(), ParseError> {
+        match self.tokens.is_empty() {
+            true => Ok(()),
+            false => Err(ParseError::TooMany {
+                rest: self
+                    .tokens
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            }),
+        }
+    }
+
+    /// 取出一个文本参数
+    fn text(&mut self, expected: &'static str) -> std::result::Result<String, ArgError> {
+        match self.next_token() {
+            Some(Token::Text(text)) => Ok(text),
+            Some(token) => Err(ArgError::Invalid {
+                value: token.to_string(),
+                expected,
+            }),
+            None => Err(ArgError::Missing),
+        }
+    }
+
+    /// 取出一个文本参数并用`FromStr`解析
+    pub fn from_str<T: std::str::FromStr>(
+        &mut self,
+        expected: &'static str,
+    ) -> std::result::Result<T, ArgError> {
+        let text = self.text(expected)?;
+        text.parse().map_err(|_| ArgError::Invalid {
+            value: text,
+            expected,
+        })
+    }
+
+    /// 取出一个类型为`kind`的CQ码
+    pub fn code<T: CQCode>(
+        &mut self,
+        kind: &str,
+        expected: &'static str,
+    ) -> std::result::Result<T, ArgError> {
+        let invalid = |value: String| ArgError::Invalid { value, expected };
+        match self.next_token() {
+            Some(Token::Code { kind: k, raw }) if k == kind => {
+                let code = if raw.starts_with('{') {
+                    T::from_json(&raw)
+                } else {
+                    T::from_string(raw.clone())
+                };
+                code.map_err(|_| invalid(raw))
+            }
+            Some(token) => Err(invalid(token.to_string())),
+            None => Err(ArgError::Missing),
+        }
+    }
+}
+
+/// 可以作为命令参数的类型
+pub trait Arg: Sized {
+    /// 在用法中显示的类型
+    const KIND: &'static str;
+    /// 是否可以省略
+    const OPTIONAL: bool = false;
+
+    fn parse(args: &mut Args) -> std::result::Result<Self, ArgError>;
+}
+
+macro_rules! impl_arg_from_str {
+    ($($ty:ty => $kind:literal),* $(,)?) => {
+        $(
+            impl Arg for $ty {
+                const KIND: &'static str = $kind;
+
+                fn parse(args: &mut Args) -> std::result::Result<Self, ArgError> {
+                    args.from_str(Self::KIND)
+                }
+            }
+        )*
+    };
+}
+
+impl_arg_from_str! {
+    i32 => "整数",
+    i64 => "整数",
+    u32 => "非负整数",
+    u64 => "非负整数",
+    f64 => "数字",
+    String => "文本",
+}
+
+/// 可以省略的参数，没有剩余参数时为`None`
+impl<T: Arg> Arg for Option<T> {
+    const KIND: &'static str = T::KIND;
+    const OPTIONAL: bool = true;
+
+    fn parse(args: &mut Args) -> std::result::Result<Self, ArgError> {
+        match args.is_empty() {
+            true => Ok(None),
+            false => T::parse(args).map(Some),
+        }
+    }
+}
+
+/// 不限数量的参数，取出所有剩余参数
+impl<T: Arg> Arg for Vec<T> {
+    const KIND: &'static str = T::KIND;
+    const OPTIONAL: bool = true;
+
+    fn parse(args: &mut Args) -> std::result::Result<Self, ArgError> {
+        let mut values = Vec::new();
+        while !args.is_empty() {
+            values.push(T::parse(args)?);
+        }
+        Ok(values)
+    }
+}
+
+/// 解析时长，例如`90`（秒）、`30s`、`10m`、`1h30m`、`2d`、`10分钟`
+pub fn parse_duration(s: &str) -> Option<Duration> {
+    if let Ok(secs) = s.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let mut total = 0u64;
+    let mut rest = s;
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let value: u64 = rest[..digits].parse().ok()?;
+        rest = &rest[digits..];
+        let unit_end = rest
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let unit = match &rest[..unit_end] {
+            "s" | "sec" | "秒" | "秒钟" => 1,
+            "m" | "min" | "分" | "分钟" => 60,
+            "h" | "小时" | "时" => 60 * 60,
+            "d" | "天" => 24 * 60 * 60,
+            _ => return None,
+        };
+        rest = &rest[unit_end..];
+        total = total.checked_add(value.checked_mul(unit)?)?;
+    }
+    Some(Duration::from_secs(total))
+}
+
+impl Arg for Duration {
+    const KIND: &'static str = "时长";
+
+    fn parse(args: &mut Args) -> std::result::Result<Self, ArgError> {
+        let text = args.text(Self::KIND)?;
+        parse_duration(&text).ok_or(ArgError::Invalid {
+            value: text,
+            expected: Self::KIND,
+        })
+    }
+}
+
+impl Arg for At {
+    const KIND: &'static str = "@成员";
+
+    fn parse(args: &mut Args) -> std::result::Result<Self, ArgError> {
+        args.code("at", Self::KIND)
+    }
+}
+
+impl Arg for Image {
+    const KIND: &'static str = "图片";
+
+    fn parse(args: &mut Args) -> std::result::Result<Self, ArgError> {
+        args.code("image", Self::KIND)
+    }
+}
+
+/// 用户QQ号，可以是@（不含@全体成员），也可以直接输入QQ号
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct UserId(pub i64);
+
+impl Arg for UserId {
+    const KIND: &'static str = "QQ号或@成员";
+
+    fn parse(args: &mut Args) -> std::result::Result<Self, ArgError> {
+        let invalid = |value: String| ArgError::Invalid {
+            value,
+            expected: Self::KIND,
+        };
+        match args.peek() {
+            Some(Token::Code { kind, .. }) if kind == "at" => {
+                let at: At = args.code("at", Self::KIND)?;
+                at.qq.parse().map(UserId).map_err(|_| invalid(at.qq))
+            }
+            _ => args.from_str(Self::KIND).map(UserId),
+        }
+    }
+}
+
+/// 剩余的所有参数，以空格连接为消息，其中的CQ码（@、图片等）保持原样，例如禁言原因或需要复读的内容
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Rest(pub Message);
+
+impl Arg for Rest {
+    const KIND: &'static str = "文本...";
+
+    fn parse(args: &mut Args) -> std::result::Result<Self, ArgError> {
+        if args.is_empty() {
+            return Err(ArgError::Missing);
+        }
+        Ok(Rest(join_tokens(args.rest())))
+    }
+}
+
+/// 将参数以空格连接为消息，CQ码为数组格式时整个消息使用数组格式
+fn join_tokens(tokens: Vec<Token>) -> Message {
+    let array = tokens
+        .iter()
+        .any(|t| matches!(t, Token::Code { raw, .. } if raw.starts_with('{')));
+    let text_segment = |text: &str| match array {
+        true => json!({ "type": "text", "data": { "text": text } }).to_string(),
+        false => escape_text(text),
+    };
+    let mut messages = Vec::new();
+    let mut text = String::new();
+    for (i, token) in tokens.into_iter().enumerate() {
+        if i > 0 {
+            text.push(' ');
+        }
+        match token {
+            Token::Text(t) => text.push_str(&t),
+            Token::Code { raw, .. } => {
+                if !text.is_empty() {
+                    messages.push(text_segment(&std::mem::take(&mut text)));
+                }
+                messages.push(raw);
+            }
+        }
+    }
+    if !text.is_empty() {
+        messages.push(text_segment(&text));
+    }
+    Message { messages }
+}
+
+/// 参数的说明
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ArgSpec {
+    pub name: String,
+    pub kind: &'static str,
+    pub optional: bool,
+    pub help: String,
+}
+
+/// 命令的说明，用于匹配命令名和生成帮助
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct CommandSpec {
+    pub name: String,
+    pub aliases: Vec<String>,
+    pub help: String,
+    pub args: Vec<ArgSpec>,
+}
+
+impl CommandSpec {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+
+    /// 添加别名
+    pub fn alias(mut self, alias: impl Into<String>) -> Self {
+        self.aliases.push(alias.into());
+        self
+    }
+
+    /// 设置命令的说明
+    pub fn help(mut self, help: impl Into<String>) -> Self {
+        self.help = help.into();
+        self
+    }
+
+    /// 添加类型为`T`的参数
+    pub fn arg<T: Arg>(mut self, name: impl Into<String>, help: impl Into<String>) -> Self {
+        self.args.push(ArgSpec {
+            name: name.into(),
+            kind: T::KIND,
+            optional: T::OPTIONAL,
+            help: help.into(),
+        });
+        self
+    }
+
+    /// `name`是否为该命令的名称或别名
+    pub fn matches(&self, name: &str) -> bool {
+        self.name == name || self.aliases.iter().any(|a| a == name)
+    }
+
+    /// 一行用法，例如`/ban <target:@成员> [reason:文本]`
+    pub fn usage(&self, prefix: &str) -> String {
+        let mut usage = format!("{}{}", prefix, self.name);
+        for arg in &self.args {
+            let (open, close) = if arg.optional { ('[', ']') } else { ('<', '>') };
+            usage += &format!(" {}{}:{}{}", open, arg.name, arg.kind, close);
+        }
+        usage
+    }
+
+    /// 用法以及命令和参数的说明
+    pub fn help_text(&self, prefix: &str) -> String {
+        let mut lines = vec![self.usage(prefix)];
+        if !self.help.is_empty() {
+            lines.push(format!("  {}", self.help));
+        }
+        if !self.aliases.is_empty() {
+            lines.push(format!("  别名：{}", self.aliases.join("、")));
+        }
+        for arg in self.args.iter().filter(|a| !a.help.is_empty()) {
+            lines.push(format!("  {}：{}", arg.name, arg.help));
+        }
+        lines.join("\n")
+    }
+}
+
+/// 消息中的命令调用
+#[derive(Debug, Clone)]
+pub struct Invocation {
+    /// 匹配到的前缀
+    pub prefix: String,
+    /// 命令名
+    pub name: String,
+    pub args: Args,
+}
+
+/// 匹配以`prefixes`之一开头的命令，开头的回复（`reply`）会被忽略，不是命令时返回`None`
+///
+/// 有多个前缀可以匹配时使用最长的前缀，前缀为空字符串时任何消息都会被当作命令
+pub fn invocation(message: &Message, prefixes: &[&str]) -> Option<Invocation> {
+    let mut tokens = tokenize(message);
+    while matches!(tokens.front(), Some(Token::Code { kind, .. }) if kind == "reply") {
+        tokens.pop_front();
+    }
+    let Some(Token::Text(first)) = tokens.pop_front() else {
+        return None;
+    };
+    let prefix = prefixes
+        .iter()
+        .filter(|p| first.starts_with(**p))
+        .max_by_key(|p| p.len())?;
+    let name = first[prefix.len()..].to_string();
+    if name.is_empty() {
+        return None;
+    }
+    Some(Invocation {
+        prefix: prefix.to_string(),
+        name,
+        args: Args { tokens },
+    })
+}
+
+/// 命令的解析错误和用法，`Display`的结果可以直接回复给发送者
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CommandError {
+    pub error: ParseError,
+    pub usage: String,
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}\n用法：{}", self.error, self.usage)
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+/// 聊天命令，枚举可以包含多个命令
+pub trait Command: Sized {
+    /// 包含的所有命令
+    fn specs() -> Vec<CommandSpec>;
+
+    /// 从命令`name`的参数中解析，`name`不是该类型的命令时返回`None`
+    ///
+    /// 不需要检查多余的参数，`parse`会在解析后检查
+    fn from_args(name: &str, args: &mut Args) -> Option<std::result::Result<Self, ParseError>>;
+
+    /// 从消息中解析命令，不是该类型的命令时返回`None`
+    fn parse(
+        message: &Message,
+        prefixes: &[&str],
+    ) -> Option<std::result::Result<Self, CommandError>> {
+        let mut invocation = invocation(message, prefixes)?;
+        let result = Self::from_args(&invocation.name, &mut invocation.args)?
+            .and_then(|command| invocation.args.finish().map(|_| command));
+        Some(result.map_err(|error| {
+            let usage = Self::specs()
+                .iter()
+                .find(|spec| spec.matches(&invocation.name))
+                .map(|spec| spec.usage(&invocation.prefix))
+                .unwrap_or_default();
+            CommandError { error, usage }
+        }))
+    }
+
+    /// 所有命令的帮助
+    fn help(prefix: &str) -> String {
+        Self::specs()
+            .iter()
+            .map(|spec| spec.help_text(prefix))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// 将文本转为消息，转义其中的`[`, `]`, `&`
+fn text_message(text: &str) -> Message {
+    Message {
+        messages: vec![escape_text(text)],
+    }
+}
+
+/// 从消息事件中解析命令，解析失败时引用原消息向发送者回复错误和用法，并返回`None`
+pub async fn parse_or_reply<C, A>(
+    api: &A,
+    event: &MessageEvent,
+    prefixes: &[&str],
+) -> Result<Option<C>>
+where
+    C: Command,
+    A: SendAPI + ?Sized,
+{
+    let error = match C::parse(event.message(), prefixes) {
+        None => return Ok(None),
+        Some(Ok(command)) => return Ok(Some(command)),
+        Some(Err(error)) => error.to_string(),
+    };
+    api.reply_to(event, text_message(&error), true).await?;
+    Ok(None)
+}
+
+/// 处理命令`C`的处理器，解析失败时回复错误和用法
+pub struct CommandHandler<C, F> {
+    prefixes: Vec<String>,
+    permission: Option<Permission>,
+    handler: F,
+    command: PhantomData<fn() -> C>,
+}
+
+impl<C, F> CommandHandler<C, F> {
+    pub fn new<P: Into<String>>(prefixes: impl IntoIterator<Item = P>, handler: F) -> Self {
+        Self {
+            prefixes: prefixes.into_iter().map(Into::into).collect(),
+            permission: None,
+            handler,
+            command: PhantomData,
+        }
+    }
+
+    /// 只有满足`permission`的用户和超级用户可以使用，其他用户使用时回复“权限不足”
+    pub fn permission(mut self, permission: Permission) -> Self {
+        self.permission = Some(permission);
+        self
+    }
+}
+
+#[async_trait]
+impl<C, F, Fut> Handler for CommandHandler<C, F>
+where
+    C: Command + Send + 'static,
+    F: Fn(Context, C) -> Fut + Send + Sync,
+    Fut: Future<Output = Result<()>> + Send,
+{
+    async fn handle(&self, ctx: Context) -> Result<()> {
+        let Some(event) = ctx.message_event() else {
+            return Ok(());
+        };
+        let prefixes: Vec<&str> = self.prefixes.iter().map(String::as_str).collect();
+        let Some(result) = C::parse(event.message(), &prefixes) else {
+            return Ok(());
+        };
+        // 先检查权限，没有权限的用户不会看到用法
+        if let Some(permission) = &self.permission {
+            if !permission.allows(&ctx).await? {
+                ctx.api()
+                    .reply_to(&event, text_message("权限不足"), true)
+                    .await?;
+                return Ok(());
+            }
+        }
+        match result {
+            Ok(command) => (self.handler)(ctx, command).await,
+            Err(error) => {
+                let reply = text_message(&error.to_string());
+                ctx.api().reply_to(&event, reply, true).await?;
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        invocation, parse_duration, parse_or_reply, tokenize, Args, Command, CommandHandler,
+        CommandSpec, ParseError, Rest, Token, UserId,
+    };
+    use crate::api::mode::Outcome;
+    use crate::api::service::Service;
+    use crate::api::target::SendAPI;
+    use crate::dispatcher::{Context, Dispatcher};
+    use crate::event::message::MessageEvent;
+    use crate::message::cq_code::code::Image;
+    use crate::message::Message;
+    use crate::permission::Permission;
+    use crate::Result;
+    use async_trait::async_trait;
+    use serde_json::{json, Value};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    enum Admin {
+        Kick { target: UserId },
+        Mute { seconds: Option<i64> },
+    }
+
+    impl Command for Admin {
+        fn specs() -> Vec<CommandSpec> {
+            vec![
+                CommandSpec::new("kick").arg::<UserId>("target", ""),
+                CommandSpec::new("mute").arg::<Option<i64>>("seconds", ""),
+            ]
+        }
+
+        fn from_args(name: &str, args: &mut Args) -> Option<std::result::Result<Self, ParseError>> {
+            match name {
+                "kick" => Some(args.parse("target").map(|target| Admin::Kick { target })),
+                "mute" => Some(args.parse("seconds").map(|seconds| Admin::Mute { seconds })),
+                _ => None,
+            }
+        }
+    }
+
+    fn message(s: &str) -> Message {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_tokenize() {
+        let tokens = tokenize(&message(r#"a  "b c" “d e” [CQ:face,id=1]'f&#91;'"#));
+        assert_eq!(tokens.len(), 5);
+        assert_eq!(tokens[1], Token::Text("b c".to_string()));
+        assert_eq!(tokens[2], Token::Text("d e".to_string()));
+        assert!(matches!(&tokens[3], Token::Code { kind, .. } if kind == "face"));
+        assert_eq!(tokens[4], Token::Text("f[".to_string()));
+        let tokens = tokenize(&message(
+            r#"[{"type":"text","data":{"text":"/a b"}},{"type":"image","data":{"file":"1.jpg"}}]"#,
+        ));
+        assert_eq!(tokens[1], Token::Text("b".to_string()));
+        let mut args = Args::new(tokens.into_iter().skip(2));
+        let image: Image = args.parse("image").unwrap();
+        assert_eq!(image.file.to_string(), "1.jpg");
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("90"), Some(Duration::from_secs(90)));
+        assert_eq!(parse_duration("1h30m"), Some(Duration::from_secs(5400)));
+        assert_eq!(parse_duration("2d"), Some(Duration::from_secs(172800)));
+        assert_eq!(parse_duration("10分钟"), Some(Duration::from_secs(600)));
+        assert_eq!(parse_duration("10x"), None);
+        assert_eq!(parse_duration("m"), None);
+    }
+
+    #[test]
+    fn test_invocation() {
+        assert!(invocation(&message("ban"), &["/"]).is_none());
+        assert!(invocation(&message("/"), &["/"]).is_none());
+        let inv = invocation(&message("[CQ:reply,id=1]!!ban 1"), &["!", "!!"]).unwrap();
+        assert_eq!(inv.prefix, "!!");
+        assert_eq!(inv.name, "ban");
+        let mut args = inv.args;
+        assert_eq!(args.parse::<i64>("id"), Ok(1));
+        assert!(args.finish().is_ok());
+    }
+
+    #[test]
+    fn test_command_parse() {
+        let Some(Ok(Admin::Kick { target })) = Admin::parse(&message("/kick 123"), &["/"]) else {
+            panic!("not kick")
+        };
+        assert_eq!(target, UserId(123));
+        assert!(matches!(
+            Admin::parse(&message("/mute"), &["/"]),
+            Some(Ok(Admin::Mute { seconds: None }))
+        ));
+        assert!(Admin::parse(&message("/unknown"), &["/"]).is_none());
+        let error = Admin::parse(&message("/kick [CQ:at,qq=all]"), &["/"])
+            .unwrap()
+            .err()
+            .unwrap();
+        assert_eq!(
+            error.to_string(),
+            "参数target应为QQ号或@成员，而不是“all”\n用法：/kick <target:QQ号或@成员>"
+        );
+        let error = Admin::parse(&message("/mute 1 2"), &["/"])
+            .unwrap()
+            .err()
+            .unwrap();
+        assert_eq!(
+            error.error,
+            ParseError::TooMany {
+                rest: "2".to_string()
+            }
+        );
+        let mut args = Args::new([Token::Text("a".into()), Token::Text("b".into())]);
+        assert_eq!(args.parse::<Rest>("r").unwrap().0.to_string(), "a b");
+        assert_eq!(
+            Admin::help(">"),
+            ">kick <target:QQ号或@成员>\n>mute [seconds:整数]"
+        );
+    }
+
+    #[test]
+    fn test_rest_keeps_codes() {
+        let rest = |text: &str| {
+            let mut args = Args::new(tokenize(&text.parse::<Message>().unwrap()));
+            args.next_token();
+            args.parse::<Rest>("r").unwrap().0
+        };
+        let echo = rest("/echo 你好 [CQ:at,qq=1]  &#91;图&#93;[CQ:image,file=a.png]");
+        assert_eq!(
+            echo.messages,
+            [
+                "你好 ",
+                "[CQ:at,qq=1]",
+                " &#91;图&#93; ",
+                "[CQ:image,file=a.png]"
+            ]
+        );
+        let echo = rest(
+            r#"[{"type":"text","data":{"text":"/echo a"}},{"type":"face","data":{"id":"1"}}]"#,
+        );
+        assert_eq!(
+            echo.messages,
+            [
+                r#"{"data":{"text":"a "},"type":"text"}"#,
+                r#"{"type":"face","data":{"id":"1"}}"#
+            ]
+        );
+    }
+
+    /// 记录发送的消息
+    #[derive(Default)]
+    struct Sent(Mutex<Vec<Value>>);
+
+    #[async_trait]
+    impl Service for Sent {
+        async fn call(&self, _action: &str, params: Value) -> Result<Outcome<Value>> {
+            self.0.lock().unwrap().push(params);
+            Ok(Outcome::Completed(json!({ "message_id": 2 })))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_echo_rest() {
+        let event: MessageEvent = serde_json::from_value(json!({
+            "post_type": "message", "message_type": "group", "message_id": 1,
+            "group_id": 10, "user_id": 2, "sender": { "user_id": 2 },
+            "message": [
+                { "type": "text", "data": { "text": "/echo 你好" } },
+                { "type": "face", "data": { "id": "1" } }
+            ]
+        }))
+        .unwrap();
+        let mut args = Args::new(tokenize(event.message()));
+        args.next_token();
+        let rest: Rest = args.parse("content").unwrap();
+        let api = Sent::default();
+        api.reply_to(&event, rest.0, false).await.unwrap();
+        // 数组格式的内容以消息段数组原样复读
+        assert_eq!(
+            api.0.lock().unwrap()[0]["message"],
+            json!([
+                { "type": "text", "data": { "text": "你好 " } },
+                { "type": "face", "data": { "id": "1" } }
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_parse_or_reply() {
+        let event: MessageEvent = serde_json::from_value(json!({
+            "post_type": "message", "message_type": "group", "message_id": 1,
+            "group_id": 10, "user_id": 2, "message": "/kick abc", "sender": { "user_id": 2 }
+        }))
+        .unwrap();
+        let api = Sent::default();
+        let command: Option<Admin> = parse_or_reply(&api, &event, &["/"]).await.unwrap();
+        assert!(command.is_none());
+        let sent = api.0.lock().unwrap();
+        assert_eq!(sent[0]["group_id"], 10);
+        assert_eq!(
+            sent[0]["message"],
+            "[CQ:reply,id=1]参数target应为QQ号或@成员，而不是“abc”\n用法：/kick <target:QQ号或@成员>"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_command_handler() {
+        let api = Arc::new(Sent::default());
+        let kicked = Arc::new(Mutex::new(Vec::new()));
+        let handler = {
+            let kicked = kicked.clone();
+            move |_ctx: Context, command: Admin| {
+                let kicked = kicked.clone();
+                async move {
+                    if let Admin::Kick { target } = command {
+                        kicked.lock().unwrap().push(target.0);
+                    }
+                    Ok(())
+                }
+            }
+        };
+        let dispatcher = Dispatcher::new(api.clone())
+            .superusers([3])
+            .handler(CommandHandler::new(["/"], handler).permission(Permission::admin()));
+        for (user_id, role, text) in [
+            (2, "member", "/kick 1"),
+            (2, "admin", "/kick 2"),
+            (2, "owner", "/kick x"),
+            (3, "member", "/kick 3"),
+        ] {
+            dispatcher.dispatch(json!({
+                "post_type": "message", "message_type": "group", "message_id": 1,
+                "group_id": 10, "user_id": user_id, "message": text,
+                "sender": { "user_id": user_id, "role": role }
+            }));
+        }
+        // 四个事件分别产生两次踢出和两条回复，超级用户不受限制
+        let handled = || kicked.lock().unwrap().len() + api.0.lock().unwrap().len();
+        let poll = async {
+            while handled() < 4 {
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+        };
+        tokio::time::timeout(Duration::from_secs(1), poll)
+            .await
+            .expect("commands were not handled in time");
+        let mut kicked = kicked.lock().unwrap().clone();
+        kicked.sort();
+        assert_eq!(kicked, [2, 3]);
+        let mut replies: Vec<_> = api
+            .0
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|p| p["message"].clone())
+            .collect();
+        replies.sort_by_key(ToString::to_string);
+        assert_eq!(
+            replies,
+            [
+                "[CQ:reply,id=1]参数target应为QQ号或@成员，而不是“x”\n用法：/kick <target:QQ号或@成员>",
+                "[CQ:reply,id=1]权限不足"
+            ]
+        );
+    }
+}
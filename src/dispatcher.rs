@@ -0,0 +1,348 @@
+//! 事件分发：将上报的事件交给所有处理器，以及等待后续消息的会话
+//!
+//! 每个事件的每个处理器都在单独的任务中运行，处理器可以通过`Context::wait_for`挂起，
+//! 等待之后的某个事件（例如同一用户在同一群中的下一条消息）。被会话等到的事件默认不再分发给处理器，
+//! `Dispatcher::pass_session_events`可以改为同时分发
+//!
+//! ```no_run
+//! # async fn f(events: impl futures::Stream<Item = serde_json::Value>) {
+//! use rust_gocqhttp::api::client::HttpClient;
+//! use rust_gocqhttp::api::target::SendAPI;
+//! use rust_gocqhttp::dispatcher::{Context, Dispatcher};
+//! use rust_gocqhttp::message::Message;
+//! use std::time::Duration;
+//!
+//! let dispatcher = Dispatcher::new(HttpClient::new("http://127.0.0.1:5700")).handler(
+//!     |ctx: Context| async move {
+//!         let Some(event) = ctx.message_event() else { return Ok(()) };
+//!         if event.message().to_string() != "/猜数字" {
+//!             return Ok(());
+//!         }
+//!         // 错误类型不是`Send`，不能在`.await`期间持有
+//!         let reply: Message = "请输入一个数字".parse()?;
+//!         ctx.api().reply_to(&event, reply, false).await?;
+//!         match ctx.wait_for(ctx.same_session(), Duration::from_secs(30)).await {
+//!             Some(answer) => log::info!("收到回答: {}", answer["raw_message"]),
+//!             None => log::info!("等待超时"),
+//!         }
+//!         Ok(())
+//!     },
+//! );
+//! dispatcher.run(events).await;
+//! # }
+//! ```
+
+use crate::api::service::Service;
+use crate::event::message::MessageEvent;
+use crate::Result;
+use async_trait::async_trait;
+use futures::channel::oneshot;
+use futures::{Stream, StreamExt};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::future::Future;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+/// 事件处理器
+#[async_trait]
+pub trait Handler: Send + Sync {
+    /// 处理一个事件，返回的错误会被记录到日志
+    async fn handle(&self, ctx: Context) -> Result<()>;
+}
+
+#[async_trait]
+impl<F, Fut> Handler for F
+where
+    F: Fn(Context) -> Fut + Send + Sync,
+    Fut: Future<Output = Result<()>> + Send,
+{
+    async fn handle(&self, ctx: Context) -> Result<()> {
+        self(ctx).await
+    }
+}
+
+/// 会话的过滤条件
+type Filter = Box<dyn Fn(&Value) -> bool + Send + Sync>;
+
+/// 一个等待中的会话
+struct Waiter {
+    filter: Filter,
+    sender: oneshot::Sender<Value>,
+}
+
+/// 所有等待中的会话
+#[derive(Clone, Default)]
+struct Sessions {
+    waiters: Arc<Mutex<Vec<Waiter>>>,
+}
+
+impl Sessions {
+    fn waiters(&self) -> MutexGuard<'_, Vec<Waiter>> {
+        self.waiters.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 将`event`交给第一个匹配的会话，返回是否被会话接收
+    fn deliver(&self, event: &Value) -> bool {
+        let mut waiters = self.waiters();
+        // 超时的会话已丢弃接收端
+        waiters.retain(|w| !w.sender.is_canceled());
+        let Some(i) = waiters.iter().position(|w| (w.filter)(event)) else {
+            return false;
+        };
+        waiters.remove(i).sender.send(event.clone()).is_ok()
+    }
+}
+
+/// 处理器收到的上下文，克隆后共享同一个分发器的会话
+#[derive(Clone)]
+pub struct Context {
+    event: Arc<Value>,
+    api: Arc<dyn Service>,
+    sessions: Sessions,
+    superusers: Arc<HashSet<i64>>,
+}
+
+impl Context {
+    /// 上报的事件
+    pub fn event(&self) -> &Value {
+        &self.event
+    }
+
+    /// 调用API，`dyn Service`实现了`GoCqhttpAPI`、`GuildAPI`和`SendAPI`
+    pub fn api(&self) -> &dyn Service {
+        &*self.api
+    }
+
+    /// 事件的发送者是否为`Dispatcher::superusers`设置的超级用户
+    pub fn is_superuser(&self) -> bool {
+        self.event["user_id"]
+            .as_i64()
+            .is_some_and(|user_id| self.superusers.contains(&user_id))
+    }
+
+    /// 事件为消息事件时将其解析为`MessageEvent`
+    pub fn message_event(&self) -> Option<MessageEvent> {
+        match MessageEvent::is_message_event(&self.event) {
+            true => serde_json::from_value((*self.event).clone()).ok(),
+            false => None,
+        }
+    }
+
+    /// 匹配与当前事件同一会话的消息的过滤条件：同一用户在同一群、同一频道或私聊中发送的消息
+    pub fn same_session(&self) -> impl Fn(&Value) -> bool + Send + Sync + 'static {
+        let key = session_key(&self.event);
+        move |event: &Value| key.is_some() && session_key(event) == key
+    }
+
+    /// 等待之后第一个满足`filter`的事件，超时返回`None`
+    ///
+    /// 多个会话都满足时，事件交给最早开始等待的会话
+    pub async fn wait_for<F>(&self, filter: F, timeout: Duration) -> Option<Value>
+    where
+        F: Fn(&Value) -> bool + Send + Sync + 'static,
+    {
+        let (sender, receiver) = oneshot::channel();
+        self.sessions.waiters().push(Waiter {
+            filter: Box::new(filter),
+            sender,
+        });
+        tokio::time::timeout(timeout, receiver).await.ok()?.ok()
+    }
+}
+
+/// 消息事件所在的会话：消息类型、群号、频道ID、子频道ID和发送者，不是消息事件时为`None`
+fn session_key(event: &Value) -> Option<[Value; 5]> {
+    if event["post_type"] != "message" {
+        return None;
+    }
+    Some(
+        [
+            "message_type",
+            "group_id",
+            "guild_id",
+            "channel_id",
+            "user_id",
+        ]
+        .map(|key| event[key].clone()),
+    )
+}
+
+/// 事件分发器
+pub struct Dispatcher {
+    api: Arc<dyn Service>,
+    handlers: Vec<Arc<dyn Handler>>,
+    sessions: Sessions,
+    superusers: Arc<HashSet<i64>>,
+    pass_session_events: bool,
+}
+
+impl Dispatcher {
+    pub fn new(api: impl Service + 'static) -> Self {
+        Self {
+            api: Arc::new(api),
+            handlers: Vec::new(),
+            sessions: Sessions::default(),
+            superusers: Arc::default(),
+            pass_session_events: false,
+        }
+    }
+
+    /// 添加处理器
+    pub fn handler(mut self, handler: impl Handler + 'static) -> Self {
+        self.handlers.push(Arc::new(handler));
+        self
+    }
+
+    /// 设置超级用户，通常来自配置文件。超级用户不受`Permission::guard`和`CommandHandler::permission`的限制
+    pub fn superusers(mut self, user_ids: impl IntoIterator<Item = i64>) -> Self {
+        self.superusers = Arc::new(user_ids.into_iter().collect());
+        self
+    }
+
+    /// 被会话等到的事件同样分发给所有处理器
+    pub fn pass_session_events(mut self) -> Self {
+        self.pass_session_events = true;
+        self
+    }
+
+    /// 等待中的会话数量
+    pub fn sessions(&self) -> usize {
+        let mut waiters = self.sessions.waiters();
+        waiters.retain(|w| !w.sender.is_canceled());
+        waiters.len()
+    }
+
+    /// 分发一个事件，处理器在新的任务中运行，需要在tokio运行时中调用
+    pub fn dispatch(&self, event: Value) {
+        if self.sessions.deliver(&event) && !self.pass_session_events {
+            return;
+        }
+        let event = Arc::new(event);
+        for handler in &self.handlers {
+            let handler = handler.clone();
+            let ctx = Context {
+                event: event.clone(),
+                api: self.api.clone(),
+                sessions: self.sessions.clone(),
+                superusers: self.superusers.clone(),
+            };
+            tokio::spawn(async move {
+                if let Err(e) = handler.handle(ctx).await {
+                    log::warn!("事件处理失败: {}", e);
+                }
+            });
+        }
+    }
+
+    /// 分发`events`中的所有事件，直到其结束
+    pub async fn run<S: Stream<Item = Value>>(&self, events: S) {
+        let mut events = std::pin::pin!(events);
+        while let Some(event) = events.next().await {
+            self.dispatch(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Context, Dispatcher};
+    use crate::record::Replay;
+    use futures::channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender};
+    use futures::StreamExt;
+    use serde_json::{json, Value};
+    use std::time::Duration;
+
+    fn message(group_id: i64, user_id: i64, text: &str) -> Value {
+        json!({
+            "post_type": "message", "message_type": "group", "message_id": 1,
+            "group_id": group_id, "user_id": user_id, "raw_message": text,
+            "message": text, "sender": { "user_id": user_id }
+        })
+    }
+
+    /// 接收处理器发出的下一个值，所有发送端都已丢弃时返回`None`，1秒内没有收到时测试失败
+    async fn recv<T>(receiver: &mut UnboundedReceiver<T>) -> Option<T> {
+        tokio::time::timeout(Duration::from_secs(1), receiver.next())
+            .await
+            .expect("handler did not respond in time")
+    }
+
+    /// 等待会话数量变为`n`
+    async fn wait_sessions(dispatcher: &Dispatcher, n: usize) {
+        let poll = async {
+            while dispatcher.sessions() != n {
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+        };
+        tokio::time::timeout(Duration::from_secs(1), poll)
+            .await
+            .expect("session count did not change in time");
+    }
+
+    /// 第一个处理器发出收到的每条消息，第二个处理器在收到`/start`后等待同一会话的下一条消息并发出
+    fn dispatcher(
+        seen: UnboundedSender<Value>,
+        answers: UnboundedSender<Option<Value>>,
+    ) -> Dispatcher {
+        Dispatcher::new(Replay::new([]))
+            .handler(move |ctx: Context| {
+                let _ = seen.unbounded_send(ctx.event()["raw_message"].clone());
+                async { Ok(()) }
+            })
+            .handler(move |ctx: Context| {
+                let answers = answers.clone();
+                async move {
+                    if ctx.event()["raw_message"] == "/start" {
+                        let answer = ctx
+                            .wait_for(ctx.same_session(), Duration::from_millis(200))
+                            .await;
+                        let _ = answers.unbounded_send(answer);
+                    }
+                    Ok(())
+                }
+            })
+    }
+
+    #[tokio::test]
+    async fn test_wait_for() {
+        let (seen_sender, mut seen) = unbounded();
+        let (answer_sender, mut answers) = unbounded();
+        let dispatcher = dispatcher(seen_sender, answer_sender);
+        dispatcher.dispatch(message(1, 10, "/start"));
+        wait_sessions(&dispatcher, 1).await;
+        // 其他用户、其他群的消息不属于该会话
+        dispatcher.dispatch(message(1, 11, "a"));
+        dispatcher.dispatch(message(2, 10, "b"));
+        dispatcher.dispatch(message(1, 10, "c"));
+        let answer = recv(&mut answers).await.unwrap().unwrap();
+        assert_eq!(answer["raw_message"], "c");
+        assert_eq!(dispatcher.sessions(), 0);
+        // 被会话接收的消息没有分发给处理器：处理器随分发器丢弃后不会再收到其他消息
+        drop(dispatcher);
+        let mut messages = Vec::new();
+        while let Some(message) = recv(&mut seen).await {
+            messages.push(message);
+        }
+        messages.sort_by_key(ToString::to_string);
+        assert_eq!(messages, ["/start", "a", "b"]);
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_timeout() {
+        let (seen_sender, mut seen) = unbounded();
+        let (answer_sender, mut answers) = unbounded();
+        let dispatcher = dispatcher(seen_sender, answer_sender).pass_session_events();
+        dispatcher.dispatch(message(1, 10, "/start"));
+        wait_sessions(&dispatcher, 1).await;
+        dispatcher.dispatch(message(1, 10, "c"));
+        let answer = recv(&mut answers).await.unwrap().unwrap();
+        assert_eq!(answer["raw_message"], "c");
+        // 被会话接收的消息同样分发给了处理器
+        assert_eq!(recv(&mut seen).await.unwrap(), "/start");
+        assert_eq!(recv(&mut seen).await.unwrap(), "c");
+        dispatcher.dispatch(message(1, 10, "/start"));
+        assert!(recv(&mut answers).await.unwrap().is_none());
+        assert_eq!(dispatcher.sessions(), 0);
+    }
+}
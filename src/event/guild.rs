@@ -0,0 +1,247 @@
+//! [频道](https://docs.go-cqhttp.org/guild/event/)相关的事件
+
+use super::message::default_post_type;
+use super::{serialize_notice, serialize_tagged};
+use crate::api::guild::data::{ChannelInfo, GuildSender, ReactionInfo};
+use crate::message::Message;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+
+/// [收到频道消息](https://docs.go-cqhttp.org/guild/event/#%E6%94%B6%E5%88%B0%E9%A2%91%E9%81%93%E6%B6%88%E6%81%AF)
+///
+/// `post_type`为`message`或`message_sent`，`message_type`为`guild`
+#[derive(Serialize, Deserialize)]
+pub struct GuildMessageEvent {
+    #[serde(default = "default_post_type")]
+    /// 上报类型，`message`为收到的消息，`message_sent`为自身发送的消息
+    pub post_type: String,
+    #[serde(default)]
+    /// 事件发生的时间戳
+    pub time: i64,
+    #[serde(default)]
+    /// 收到事件的机器人QQ号
+    pub self_id: i64,
+    #[serde(default)]
+    /// 机器人在频道系统中的ID
+    pub self_tiny_id: String,
+    #[serde(default)]
+    /// 消息子类型，目前固定为`channel`
+    pub sub_type: String,
+    #[serde(default)]
+    /// 消息ID
+    pub message_id: String,
+    #[serde(default)]
+    /// 频道ID
+    pub guild_id: String,
+    #[serde(default)]
+    /// 子频道ID
+    pub channel_id: String,
+    #[serde(default)]
+    /// 发送者ID
+    pub user_id: String,
+    /// 消息内容
+    pub message: Message,
+    /// 发送者
+    pub sender: GuildSender,
+}
+
+/// [频道消息表情贴更新](https://docs.go-cqhttp.org/guild/event/#%E9%A2%91%E9%81%93%E6%B6%88%E6%81%AF%E8%A1%A8%E6%83%85%E8%B4%B4%E6%9B%B4%E6%96%B0)
+#[derive(Serialize, Deserialize)]
+pub struct MessageReactionsUpdated {
+    #[serde(default)]
+    /// 事件发生的时间戳
+    pub time: i64,
+    #[serde(default)]
+    /// 收到事件的机器人QQ号
+    pub self_id: i64,
+    #[serde(default)]
+    /// 机器人在频道系统中的ID
+    pub self_tiny_id: String,
+    #[serde(default)]
+    /// 频道ID
+    pub guild_id: String,
+    #[serde(default)]
+    /// 子频道ID
+    pub channel_id: String,
+    #[serde(default)]
+    /// 操作者ID
+    pub user_id: String,
+    #[serde(default)]
+    /// 消息ID
+    pub message_id: String,
+    #[serde(default)]
+    /// 当前消息被贴表情列表
+    pub current_reactions: Vec<ReactionInfo>,
+}
+
+/// [子频道信息更新](https://docs.go-cqhttp.org/guild/event/#%E5%AD%90%E9%A2%91%E9%81%93%E4%BF%A1%E6%81%AF%E6%9B%B4%E6%96%B0)
+#[derive(Serialize, Deserialize)]
+pub struct ChannelUpdated {
+    #[serde(default)]
+    /// 事件发生的时间戳
+    pub time: i64,
+    #[serde(default)]
+    /// 收到事件的机器人QQ号
+    pub self_id: i64,
+    #[serde(default)]
+    /// 机器人在频道系统中的ID
+    pub self_tiny_id: String,
+    #[serde(default)]
+    /// 频道ID
+    pub guild_id: String,
+    #[serde(default)]
+    /// 子频道ID
+    pub channel_id: String,
+    #[serde(default)]
+    /// 操作者ID
+    pub operator_id: String,
+    /// 更新前的频道信息
+    pub old_info: ChannelInfo,
+    /// 更新后的频道信息
+    pub new_info: ChannelInfo,
+}
+
+/// [子频道创建](https://docs.go-cqhttp.org/guild/event/#%E5%AD%90%E9%A2%91%E9%81%93%E5%88%9B%E5%BB%BA)与[子频道删除](https://docs.go-cqhttp.org/guild/event/#%E5%AD%90%E9%A2%91%E9%81%93%E5%88%A0%E9%99%A4)
+#[derive(Serialize, Deserialize)]
+pub struct ChannelChanged {
+    #[serde(default)]
+    /// 事件发生的时间戳
+    pub time: i64,
+    #[serde(default)]
+    /// 收到事件的机器人QQ号
+    pub self_id: i64,
+    #[serde(default)]
+    /// 机器人在频道系统中的ID
+    pub self_tiny_id: String,
+    #[serde(default)]
+    /// 频道ID
+    pub guild_id: String,
+    #[serde(default)]
+    /// 子频道ID
+    pub channel_id: String,
+    #[serde(default)]
+    /// 操作者ID
+    pub operator_id: String,
+    /// 频道信息
+    pub channel_info: ChannelInfo,
+}
+
+/// 频道通知事件，`post_type`为`notice`，按`notice_type`区分
+#[derive(Deserialize)]
+#[serde(tag = "notice_type", rename_all = "snake_case")]
+pub enum GuildNoticeEvent {
+    MessageReactionsUpdated(MessageReactionsUpdated),
+    ChannelUpdated(ChannelUpdated),
+    ChannelCreated(ChannelChanged),
+    ChannelDestroyed(ChannelChanged),
+}
+
+impl Serialize for GuildNoticeEvent {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            GuildNoticeEvent::MessageReactionsUpdated(e) => {
+                serialize_notice(serializer, e, "message_reactions_updated")
+            }
+            GuildNoticeEvent::ChannelUpdated(e) => {
+                serialize_notice(serializer, e, "channel_updated")
+            }
+            GuildNoticeEvent::ChannelCreated(e) => {
+                serialize_notice(serializer, e, "channel_created")
+            }
+            GuildNoticeEvent::ChannelDestroyed(e) => {
+                serialize_notice(serializer, e, "channel_destroyed")
+            }
+        }
+    }
+}
+
+/// 频道相关的事件
+pub enum GuildEvent {
+    Message(GuildMessageEvent),
+    Notice(GuildNoticeEvent),
+}
+
+impl GuildEvent {
+    /// 判断上报的事件是否为频道事件
+    pub fn is_guild_event(event: &Value) -> bool {
+        match event["post_type"].as_str() {
+            Some("message") | Some("message_sent") => event["message_type"] == "guild",
+            Some("notice") => matches!(
+                event["notice_type"].as_str(),
+                Some(
+                    "message_reactions_updated"
+                        | "channel_updated"
+                        | "channel_created"
+                        | "channel_destroyed"
+                )
+            ),
+            _ => false,
+        }
+    }
+}
+
+impl Serialize for GuildEvent {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            GuildEvent::Message(e) => serialize_tagged(serializer, e, &[("message_type", "guild")]),
+            GuildEvent::Notice(e) => e.serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for GuildEvent {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        use serde::de::Error;
+        let event = Value::deserialize(deserializer)?;
+        if !Self::is_guild_event(&event) {
+            return Err(D::Error::custom(format!("不是频道事件: {}", event)));
+        }
+        if event["post_type"] == "notice" {
+            GuildNoticeEvent::deserialize(event)
+                .map(GuildEvent::Notice)
+                .map_err(D::Error::custom)
+        } else {
+            GuildMessageEvent::deserialize(event)
+                .map(GuildEvent::Message)
+                .map_err(D::Error::custom)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GuildEvent, GuildNoticeEvent};
+
+    #[test]
+    fn test_guild_message_event() {
+        let event: GuildEvent = serde_json::from_str(
+            r#"{"post_type":"message","message_type":"guild","sub_type":"channel","time":1,"self_id":2,"self_tiny_id":"3","message_id":"4","guild_id":"5","channel_id":"6","user_id":"7","message":"你好[CQ:face,id=1]","sender":{"tiny_id":"7","nickname":"a"}}"#,
+        )
+        .unwrap();
+        let GuildEvent::Message(m) = event else {
+            panic!("not a message")
+        };
+        assert_eq!(m.guild_id, "5");
+        assert_eq!(m.sender.nickname, "a");
+        assert_eq!(m.message.messages, vec!["你好", "[CQ:face,id=1]"]);
+    }
+
+    #[test]
+    fn test_guild_notice_event() {
+        let event: GuildEvent = serde_json::from_str(
+            r#"{"post_type":"notice","notice_type":"channel_created","guild_id":"1","channel_id":"2","operator_id":"3","channel_info":{"channel_id":"2","channel_name":"c","channel_type":1}}"#,
+        )
+        .unwrap();
+        let GuildEvent::Notice(GuildNoticeEvent::ChannelCreated(c)) = event else {
+            panic!("not channel_created")
+        };
+        assert_eq!(c.channel_info.channel_name, "c");
+        assert!(serde_json::from_str::<GuildEvent>(
+            r#"{"post_type":"message","message_type":"group","message":"a"}"#
+        )
+        .is_err());
+    }
+}
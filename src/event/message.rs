@@ -0,0 +1,197 @@
+//! [消息事件](https://docs.go-cqhttp.org/event/#%E7%A7%81%E8%81%8A%E6%B6%88%E6%81%AF)
+
+use super::guild::GuildMessageEvent;
+use super::serialize_tagged;
+use crate::api::data::{AnonymousGroupMsg, Sender};
+use crate::message::Message;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+
+/// [私聊消息](https://docs.go-cqhttp.org/event/#%E7%A7%81%E8%81%8A%E6%B6%88%E6%81%AF)
+///
+/// `post_type`为`message`或`message_sent`，`message_type`为`private`
+#[derive(Serialize, Deserialize)]
+pub struct PrivateMessageEvent {
+    #[serde(default = "default_post_type")]
+    /// 上报类型，`message`为收到的消息，`message_sent`为自身发送的消息
+    pub post_type: String,
+    #[serde(default)]
+    /// 事件发生的时间戳
+    pub time: i64,
+    #[serde(default)]
+    /// 收到事件的机器人QQ号
+    pub self_id: i64,
+    #[serde(default)]
+    /// 消息子类型，`friend`为好友，`group`为群临时会话，`group_self`为群中自身发送，`other`为其他
+    pub sub_type: String,
+    #[serde(default)]
+    /// 消息ID
+    pub message_id: i32,
+    #[serde(default)]
+    /// 发送者QQ号
+    pub user_id: i64,
+    #[serde(default)]
+    /// 接收者QQ号
+    pub target_id: i64,
+    /// 消息内容
+    pub message: Message,
+    #[serde(default)]
+    /// 原始消息内容
+    pub raw_message: String,
+    #[serde(default)]
+    /// 字体
+    pub font: i32,
+    /// 发送者信息，临时会话时`sender.group_id`为来源群号
+    pub sender: Sender,
+}
+
+/// [群消息](https://docs.go-cqhttp.org/event/#%E7%BE%A4%E6%B6%88%E6%81%AF)
+///
+/// `post_type`为`message`或`message_sent`，`message_type`为`group`
+#[derive(Serialize, Deserialize)]
+pub struct GroupMessageEvent {
+    #[serde(default = "default_post_type")]
+    /// 上报类型，`message`为收到的消息，`message_sent`为自身发送的消息
+    pub post_type: String,
+    #[serde(default)]
+    /// 事件发生的时间戳
+    pub time: i64,
+    #[serde(default)]
+    /// 收到事件的机器人QQ号
+    pub self_id: i64,
+    #[serde(default)]
+    /// 消息子类型，`normal`为正常消息，`anonymous`为匿名消息，`notice`为系统提示
+    pub sub_type: String,
+    #[serde(default)]
+    /// 消息ID
+    pub message_id: i32,
+    #[serde(default)]
+    /// 群号
+    pub group_id: i64,
+    #[serde(default)]
+    /// 发送者QQ号
+    pub user_id: i64,
+    #[serde(default)]
+    /// 匿名信息，不是匿名消息时为`None`
+    pub anonymous: Option<AnonymousGroupMsg>,
+    /// 消息内容
+    pub message: Message,
+    #[serde(default)]
+    /// 原始消息内容
+    pub raw_message: String,
+    #[serde(default)]
+    /// 字体
+    pub font: i32,
+    /// 发送者信息
+    pub sender: Sender,
+}
+
+/// 缺少`post_type`时视为收到的消息
+pub(crate) fn default_post_type() -> String {
+    "message".to_string()
+}
+
+/// 消息事件，按`message_type`区分
+pub enum MessageEvent {
+    Private(PrivateMessageEvent),
+    Group(GroupMessageEvent),
+    Guild(GuildMessageEvent),
+}
+
+impl MessageEvent {
+    /// 判断上报的事件是否为消息事件（包括自身发送的消息）
+    pub fn is_message_event(event: &Value) -> bool {
+        matches!(
+            event["post_type"].as_str(),
+            Some("message") | Some("message_sent")
+        )
+    }
+
+    /// 消息内容
+    pub fn message(&self) -> &Message {
+        match self {
+            MessageEvent::Private(e) => &e.message,
+            MessageEvent::Group(e) => &e.message,
+            MessageEvent::Guild(e) => &e.message,
+        }
+    }
+
+    /// 私聊和群消息的消息ID，频道消息的ID为字符串，返回`None`
+    pub fn message_id(&self) -> Option<i32> {
+        match self {
+            MessageEvent::Private(e) => Some(e.message_id),
+            MessageEvent::Group(e) => Some(e.message_id),
+            MessageEvent::Guild(_) => None,
+        }
+    }
+}
+
+/// 序列化为上报格式，保留原事件的`post_type`
+impl Serialize for MessageEvent {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            MessageEvent::Private(e) => {
+                serialize_tagged(serializer, e, &[("message_type", "private")])
+            }
+            MessageEvent::Group(e) => serialize_tagged(serializer, e, &[("message_type", "group")]),
+            MessageEvent::Guild(e) => serialize_tagged(serializer, e, &[("message_type", "guild")]),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for MessageEvent {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        use serde::de::Error;
+        let event = Value::deserialize(deserializer)?;
+        if !Self::is_message_event(&event) {
+            return Err(D::Error::custom(format!("不是消息事件: {}", event)));
+        }
+        match event["message_type"].as_str() {
+            Some("private") => PrivateMessageEvent::deserialize(event)
+                .map(MessageEvent::Private)
+                .map_err(D::Error::custom),
+            Some("group") => GroupMessageEvent::deserialize(event)
+                .map(MessageEvent::Group)
+                .map_err(D::Error::custom),
+            Some("guild") => GuildMessageEvent::deserialize(event)
+                .map(MessageEvent::Guild)
+                .map_err(D::Error::custom),
+            _ => Err(D::Error::custom(format!(
+                "未知的消息类型: {}",
+                event["message_type"]
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MessageEvent;
+
+    #[test]
+    fn test_message_event() {
+        let event: MessageEvent = serde_json::from_str(
+            r#"{"post_type":"message","message_type":"private","sub_type":"group","time":1,"self_id":2,"message_id":3,"user_id":4,"target_id":2,"message":"hi","raw_message":"hi","font":0,"sender":{"user_id":4,"nickname":"a","group_id":5}}"#,
+        )
+        .unwrap();
+        let MessageEvent::Private(e) = &event else {
+            panic!("not private")
+        };
+        assert_eq!(e.sender.group_id, Some(5));
+        assert_eq!(event.message_id(), Some(3));
+        let event: MessageEvent = serde_json::from_str(
+            r#"{"post_type":"message","message_type":"group","sub_type":"normal","message_id":6,"group_id":7,"user_id":8,"anonymous":null,"message":"[CQ:face,id=1]","sender":{"user_id":8,"nickname":"b","card":"c","role":"admin"}}"#,
+        )
+        .unwrap();
+        let MessageEvent::Group(e) = &event else {
+            panic!("not group")
+        };
+        assert_eq!(e.group_id, 7);
+        assert!(e.anonymous.is_none());
+        assert_eq!(event.message().messages, vec!["[CQ:face,id=1]"]);
+        assert!(serde_json::from_str::<MessageEvent>(r#"{"post_type":"notice"}"#).is_err());
+    }
+}
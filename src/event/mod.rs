@@ -0,0 +1,45 @@
+//! go-cqhttp上报的[事件](https://docs.go-cqhttp.org/event/)
+
+pub mod guild;
+pub mod message;
+pub mod notice;
+
+use serde::{Serialize, Serializer};
+use serde_json::Value;
+
+/// 将`inner`序列化为上报格式，并加入`post_type`等不在结构体中的字段
+pub(crate) fn serialize_tagged<S, T>(
+    serializer: S,
+    inner: &T,
+    tags: &[(&str, &str)],
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: Serialize,
+{
+    use serde::ser::Error;
+    let mut value = serde_json::to_value(inner).map_err(S::Error::custom)?;
+    if let Value::Object(map) = &mut value {
+        for (key, tag) in tags {
+            map.insert(key.to_string(), Value::from(*tag));
+        }
+    }
+    value.serialize(serializer)
+}
+
+/// 将通知事件序列化为上报格式
+pub(crate) fn serialize_notice<S, T>(
+    serializer: S,
+    inner: &T,
+    notice_type: &str,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: Serialize,
+{
+    serialize_tagged(
+        serializer,
+        inner,
+        &[("post_type", "notice"), ("notice_type", notice_type)],
+    )
+}
@@ -0,0 +1,153 @@
+//! [通知事件](https://docs.go-cqhttp.org/event/#%E7%BE%A4%E6%88%90%E5%91%98%E5%A2%9E%E5%8A%A0)中与好友、群成员变动相关的部分
+
+use super::serialize_notice;
+use serde::{Deserialize, Serialize, Serializer};
+
+/// [群成员增加](https://docs.go-cqhttp.org/event/#%E7%BE%A4%E6%88%90%E5%91%98%E5%A2%9E%E5%8A%A0)
+#[derive(Serialize, Deserialize)]
+pub struct GroupIncrease {
+    #[serde(default)]
+    /// 事件发生的时间戳
+    pub time: i64,
+    #[serde(default)]
+    /// 收到事件的机器人QQ号
+    pub self_id: i64,
+    #[serde(default)]
+    /// `approve`为管理员已同意入群，`invite`为管理员邀请入群
+    pub sub_type: String,
+    #[serde(default)]
+    /// 群号
+    pub group_id: i64,
+    #[serde(default)]
+    /// 操作者QQ号
+    pub operator_id: i64,
+    #[serde(default)]
+    /// 加入者QQ号
+    pub user_id: i64,
+}
+
+/// [群成员减少](https://docs.go-cqhttp.org/event/#%E7%BE%A4%E6%88%90%E5%91%98%E5%87%8F%E5%B0%91)
+#[derive(Serialize, Deserialize)]
+pub struct GroupDecrease {
+    #[serde(default)]
+    /// 事件发生的时间戳
+    pub time: i64,
+    #[serde(default)]
+    /// 收到事件的机器人QQ号
+    pub self_id: i64,
+    #[serde(default)]
+    /// `leave`为主动退群，`kick`为成员被踢，`kick_me`为登录号被踢
+    pub sub_type: String,
+    #[serde(default)]
+    /// 群号
+    pub group_id: i64,
+    #[serde(default)]
+    /// 操作者QQ号，主动退群时与`user_id`相同
+    pub operator_id: i64,
+    #[serde(default)]
+    /// 离开者QQ号
+    pub user_id: i64,
+}
+
+/// [群管理员变动](https://docs.go-cqhttp.org/event/#%E7%BE%A4%E7%AE%A1%E7%90%86%E5%91%98%E5%8F%98%E5%8A%A8)
+#[derive(Serialize, Deserialize)]
+pub struct GroupAdmin {
+    #[serde(default)]
+    /// 事件发生的时间戳
+    pub time: i64,
+    #[serde(default)]
+    /// 收到事件的机器人QQ号
+    pub self_id: i64,
+    #[serde(default)]
+    /// `set`为设置管理员，`unset`为取消管理员
+    pub sub_type: String,
+    #[serde(default)]
+    /// 群号
+    pub group_id: i64,
+    #[serde(default)]
+    /// 管理员QQ号
+    pub user_id: i64,
+}
+
+/// [群成员名片更新](https://docs.go-cqhttp.org/event/#%E7%BE%A4%E6%88%90%E5%91%98%E5%90%8D%E7%89%87%E6%9B%B4%E6%96%B0)
+///
+/// **注意**：此事件不保证时效性, 仅在收到消息时校验卡片
+#[derive(Serialize, Deserialize)]
+pub struct GroupCard {
+    #[serde(default)]
+    /// 事件发生的时间戳
+    pub time: i64,
+    #[serde(default)]
+    /// 收到事件的机器人QQ号
+    pub self_id: i64,
+    #[serde(default)]
+    /// 群号
+    pub group_id: i64,
+    #[serde(default)]
+    /// 成员QQ号
+    pub user_id: i64,
+    #[serde(default)]
+    /// 新名片
+    pub card_new: String,
+    #[serde(default)]
+    /// 旧名片
+    pub card_old: String,
+}
+
+/// [好友添加](https://docs.go-cqhttp.org/event/#%E5%A5%BD%E5%8F%8B%E6%B7%BB%E5%8A%A0)
+#[derive(Serialize, Deserialize)]
+pub struct FriendAdd {
+    #[serde(default)]
+    /// 事件发生的时间戳
+    pub time: i64,
+    #[serde(default)]
+    /// 收到事件的机器人QQ号
+    pub self_id: i64,
+    #[serde(default)]
+    /// 新添加好友QQ号
+    pub user_id: i64,
+}
+
+/// 通知事件，`post_type`为`notice`，按`notice_type`区分
+#[derive(Deserialize)]
+#[serde(tag = "notice_type", rename_all = "snake_case")]
+pub enum NoticeEvent {
+    GroupIncrease(GroupIncrease),
+    GroupDecrease(GroupDecrease),
+    GroupAdmin(GroupAdmin),
+    GroupCard(GroupCard),
+    FriendAdd(FriendAdd),
+}
+
+impl Serialize for NoticeEvent {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            NoticeEvent::GroupIncrease(e) => serialize_notice(serializer, e, "group_increase"),
+            NoticeEvent::GroupDecrease(e) => serialize_notice(serializer, e, "group_decrease"),
+            NoticeEvent::GroupAdmin(e) => serialize_notice(serializer, e, "group_admin"),
+            NoticeEvent::GroupCard(e) => serialize_notice(serializer, e, "group_card"),
+            NoticeEvent::FriendAdd(e) => serialize_notice(serializer, e, "friend_add"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NoticeEvent;
+
+    #[test]
+    fn test_notice_event() {
+        let event: NoticeEvent = serde_json::from_str(
+            r#"{"post_type":"notice","notice_type":"group_card","group_id":1,"user_id":2,"card_new":"b","card_old":"a"}"#,
+        )
+        .unwrap();
+        let NoticeEvent::GroupCard(e) = event else {
+            panic!("not group_card")
+        };
+        assert_eq!(e.card_new, "b");
+        assert!(serde_json::from_str::<NoticeEvent>(
+            r#"{"post_type":"notice","notice_type":"unknown"}"#
+        )
+        .is_err());
+    }
+}
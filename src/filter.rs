@@ -0,0 +1,364 @@
+//! [事件过滤器](https://docs.go-cqhttp.org/guide/eventfilter.html)
+//!
+//! `Filter`序列化为go-cqhttp的过滤器格式，可以通过`GoCqhttp::apply_event_filter`写入文件并重新加载，
+//! 也可以用`matches`在本地检查事件，两者的判断方式相同：比较时数字和字符串按其文本比较，
+//! 缺少的字段视为空字符串
+//!
+//! ```
+//! use rust_gocqhttp::filter::Filter;
+//! use serde_json::json;
+//!
+//! // 只上报指定群中以“/”开头的群消息，以及所有私聊消息
+//! let filter = Filter::message_type("group")
+//!     .and(Filter::groups([123456, 654321]))
+//!     .and(Filter::field("raw_message").regex("^/").unwrap())
+//!     .or(Filter::message_type("private"));
+//! assert_eq!(
+//!     filter.to_json(),
+//!     json!({ ".or": [
+//!         {
+//!             "message_type": { ".eq": "group" },
+//!             "group_id": { ".in": [123456, 654321] },
+//!             "raw_message": { ".regex": "^/" }
+//!         },
+//!         { "message_type": { ".eq": "private" } }
+//!     ] })
+//! );
+//! assert!(filter.matches(&json!({ "message_type": "group", "group_id": 123456, "raw_message": "/help" })));
+//! assert!(!filter.matches(&json!({ "message_type": "group", "group_id": 1, "raw_message": "/help" })));
+//! ```
+
+use regex::Regex;
+use serde::{Serialize, Serializer};
+use serde_json::{json, Map, Value};
+use std::fmt;
+
+/// 事件过滤器，作用于事件或事件中的某个字段
+#[derive(Debug, Clone)]
+pub enum Filter {
+    /// 同时满足所有条件，`.and`
+    All(Vec<Filter>),
+    /// 满足任一条件，`.or`
+    Any(Vec<Filter>),
+    /// 不满足条件，`.not`
+    Not(Box<Filter>),
+    /// 字段满足条件
+    Field(String, Box<Filter>),
+    /// 等于，`.eq`
+    Eq(Value),
+    /// 不等于，`.neq`
+    Neq(Value),
+    /// 在数组中，或是字符串的子串，`.in`
+    In(Value),
+    /// 包含子串，`.contains`
+    Contains(String),
+    /// 匹配正则表达式，`.regex`
+    Regex(Regex),
+}
+
+/// 值在比较时的文本，与go-cqhttp相同
+fn text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+impl Filter {
+    /// 事件中的字段`path`，可以用`.`分隔表示嵌套的字段，例如`sender.role`
+    pub fn field(path: &str) -> FieldFilter {
+        FieldFilter {
+            path: path.to_string(),
+        }
+    }
+
+    /// `post_type`等于`post_type`
+    pub fn post_type(post_type: &str) -> Self {
+        Self::field("post_type").eq(post_type)
+    }
+
+    /// `message_type`等于`message_type`
+    pub fn message_type(message_type: &str) -> Self {
+        Self::field("message_type").eq(message_type)
+    }
+
+    /// `notice_type`等于`notice_type`
+    pub fn notice_type(notice_type: &str) -> Self {
+        Self::field("notice_type").eq(notice_type)
+    }
+
+    /// 群号在`group_ids`中
+    pub fn groups(group_ids: impl IntoIterator<Item = i64>) -> Self {
+        Self::field("group_id").one_of(group_ids)
+    }
+
+    /// QQ号在`user_ids`中
+    pub fn users(user_ids: impl IntoIterator<Item = i64>) -> Self {
+        Self::field("user_id").one_of(user_ids)
+    }
+
+    /// 同时满足两者
+    pub fn and(self, other: Filter) -> Self {
+        match self {
+            Filter::All(mut filters) => {
+                filters.push(other);
+                Filter::All(filters)
+            }
+            _ => Filter::All(vec![self, other]),
+        }
+    }
+
+    /// 满足任一
+    pub fn or(self, other: Filter) -> Self {
+        match self {
+            Filter::Any(mut filters) => {
+                filters.push(other);
+                Filter::Any(filters)
+            }
+            _ => Filter::Any(vec![self, other]),
+        }
+    }
+
+    /// 不满足
+    #[allow(clippy::should_implement_trait)]
+    pub fn not(self) -> Self {
+        match self {
+            Filter::Not(inner) => *inner,
+            _ => Filter::Not(Box::new(self)),
+        }
+    }
+
+    /// go-cqhttp过滤器格式的JSON
+    pub fn to_json(&self) -> Value {
+        match self {
+            Filter::All(filters) => {
+                let objects: Vec<Value> = filters.iter().map(Filter::to_json).collect();
+                let mut merged = Map::new();
+                for object in &objects {
+                    let Value::Object(object) = object else {
+                        unreachable!()
+                    };
+                    if object.keys().any(|key| merged.contains_key(key)) {
+                        // 同一字段有多个条件时无法合并为一个对象，改为`!(!a || !b)`
+                        let negated: Vec<Value> =
+                            objects.into_iter().map(|o| json!({ ".not": o })).collect();
+                        return json!({ ".not": { ".or": negated } });
+                    }
+                    merged.extend(object.clone());
+                }
+                Value::Object(merged)
+            }
+            Filter::Any(filters) => {
+                json!({ ".or": filters.iter().map(Filter::to_json).collect::<Vec<_>>() })
+            }
+            Filter::Not(inner) => json!({ ".not": inner.to_json() }),
+            Filter::Field(name, inner) => json!({ name.as_str(): inner.to_json() }),
+            Filter::Eq(value) => json!({ ".eq": value }),
+            Filter::Neq(value) => json!({ ".neq": value }),
+            Filter::In(value) => json!({ ".in": value }),
+            Filter::Contains(s) => json!({ ".contains": s }),
+            Filter::Regex(regex) => json!({ ".regex": regex.as_str() }),
+        }
+    }
+
+    /// 在本地检查上报的事件（或事件中的字段）是否满足过滤器
+    pub fn matches(&self, value: &Value) -> bool {
+        match self {
+            Filter::All(filters) => filters.iter().all(|f| f.matches(value)),
+            Filter::Any(filters) => filters.iter().any(|f| f.matches(value)),
+            Filter::Not(inner) => !inner.matches(value),
+            Filter::Field(name, inner) => inner.matches(&value[name.as_str()]),
+            Filter::Eq(expected) => text(value) == text(expected),
+            Filter::Neq(expected) => text(value) != text(expected),
+            Filter::In(Value::Array(values)) => {
+                let value = text(value);
+                values.iter().any(|v| text(v) == value)
+            }
+            Filter::In(Value::String(s)) => s.contains(&text(value)),
+            Filter::In(_) => false,
+            Filter::Contains(s) => text(value).contains(s.as_str()),
+            Filter::Regex(regex) => regex.is_match(&text(value)),
+        }
+    }
+
+    /// 在本地检查解析后的事件，例如`MessageEvent`, `NoticeEvent`
+    pub fn matches_event<E: Serialize>(&self, event: &E) -> bool {
+        serde_json::to_value(event).is_ok_and(|event| self.matches(&event))
+    }
+}
+
+/// 格式化为过滤器文件的内容
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match serde_json::to_string_pretty(&self.to_json()) {
+            Ok(json) => f.write_str(&json),
+            Err(_) => Err(fmt::Error),
+        }
+    }
+}
+
+impl Serialize for Filter {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_json().serialize(serializer)
+    }
+}
+
+/// 事件中某个字段的条件，由`Filter::field`创建
+#[derive(Debug, Clone)]
+pub struct FieldFilter {
+    path: String,
+}
+
+impl FieldFilter {
+    /// 字段满足`filter`
+    pub fn matches(self, filter: Filter) -> Filter {
+        self.path.rsplit('.').fold(filter, |f, name| {
+            Filter::Field(name.to_string(), Box::new(f))
+        })
+    }
+
+    /// 字段等于`value`
+    pub fn eq(self, value: impl Into<Value>) -> Filter {
+        self.matches(Filter::Eq(value.into()))
+    }
+
+    /// 字段不等于`value`
+    pub fn neq(self, value: impl Into<Value>) -> Filter {
+        self.matches(Filter::Neq(value.into()))
+    }
+
+    /// 字段等于`values`之一
+    pub fn one_of<V: Into<Value>>(self, values: impl IntoIterator<Item = V>) -> Filter {
+        let values = values.into_iter().map(Into::into).collect();
+        self.matches(Filter::In(Value::Array(values)))
+    }
+
+    /// 字段是`s`的子串
+    pub fn substring_of(self, s: &str) -> Filter {
+        self.matches(Filter::In(Value::from(s)))
+    }
+
+    /// 字段包含`s`
+    pub fn contains(self, s: &str) -> Filter {
+        self.matches(Filter::Contains(s.to_string()))
+    }
+
+    /// 字段匹配正则表达式`pattern`，go-cqhttp使用Go的正则语法，请避免两者不兼容的语法
+    pub fn regex(self, pattern: &str) -> Result<Filter, regex::Error> {
+        Ok(self.matches(Filter::Regex(Regex::new(pattern)?)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Filter;
+    use crate::event::message::MessageEvent;
+    use crate::event::notice::NoticeEvent;
+    use crate::record::{Entry, RecordedCall, Replay};
+    use crate::GoCqhttp;
+    use serde_json::{json, Value};
+
+    #[test]
+    fn test_to_json() {
+        let filter = Filter::field("sender.role")
+            .eq("admin")
+            .and(Filter::field("user_id").neq(1).not());
+        assert_eq!(
+            filter.to_json(),
+            json!({
+                "sender": { "role": { ".eq": "admin" } },
+                ".not": { "user_id": { ".neq": 1 } }
+            })
+        );
+        // 同一字段的多个条件
+        let filter = Filter::field("raw_message")
+            .contains("a")
+            .and(Filter::field("raw_message").substring_of("abc"));
+        assert_eq!(
+            filter.to_json(),
+            json!({ ".not": { ".or": [
+                { ".not": { "raw_message": { ".contains": "a" } } },
+                { ".not": { "raw_message": { ".in": "abc" } } }
+            ] } })
+        );
+        assert!(filter.matches(&json!({ "raw_message": "ab" })));
+        assert!(!filter.matches(&json!({ "raw_message": "ac" })));
+        assert_eq!(
+            Filter::post_type("notice").not().not().to_json(),
+            Filter::post_type("notice").to_json()
+        );
+    }
+
+    #[test]
+    fn test_matches() {
+        let admin = Filter::field("sender.role").one_of(["admin", "owner"]);
+        let event: MessageEvent = serde_json::from_value(json!({
+            "post_type": "message", "message_type": "group", "message_id": 1,
+            "group_id": 10, "user_id": 2, "message": "签到",
+            "sender": { "user_id": 2, "role": "owner" }
+        }))
+        .unwrap();
+        assert!(admin.matches_event(&event));
+        assert!(Filter::message_type("group").matches_event(&event));
+        // 数字与字符串按文本比较
+        assert!(Filter::field("group_id").eq("10").matches_event(&event));
+        assert!(Filter::field("message")
+            .regex("^签")
+            .unwrap()
+            .matches_event(&event));
+        let notice: NoticeEvent = serde_json::from_value(json!({
+            "post_type": "notice", "notice_type": "group_increase", "group_id": 10, "user_id": 3
+        }))
+        .unwrap();
+        assert!(Filter::notice_type("group_increase")
+            .and(Filter::post_type("notice"))
+            .and(Filter::groups([10]))
+            .matches_event(&notice));
+        assert!(!admin.matches_event(&notice));
+        // 自身发送的消息保留`post_type`
+        let sent: MessageEvent = serde_json::from_value(json!({
+            "post_type": "message_sent", "message_type": "private", "message_id": 2,
+            "user_id": 1, "target_id": 2, "message": "hi", "sender": { "user_id": 1 }
+        }))
+        .unwrap();
+        assert!(Filter::post_type("message_sent").matches_event(&sent));
+        assert!(!Filter::post_type("message").matches_event(&sent));
+        assert!(Filter::post_type("message").matches_event(&event));
+        assert!(Filter::field("sub_type").eq("").matches(&json!({})));
+        // `.in`的操作数为字符串时，数字字段同样按文本判断是否为其子串
+        let in_text = Filter::field("group_id").substring_of("123456");
+        assert!(in_text.matches(&json!({ "group_id": 2345 })));
+        assert!(!in_text.matches(&json!({ "group_id": 99 })));
+    }
+
+    #[tokio::test]
+    async fn test_apply_event_filter() {
+        let directory =
+            std::env::temp_dir().join(format!("gocqhttp-filter-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let gocqhttp = GoCqhttp::new(directory.to_string_lossy().to_string())
+            .await
+            .unwrap();
+        let api = Replay::new([Entry::Call(RecordedCall {
+            action: "reload_event_filter".to_string(),
+            params: json!({ "file": "filter.json" }),
+            data: Value::Null,
+            submitted: false,
+            error: None,
+            retcode: None,
+        })]);
+        let filter = Filter::groups([1]).not();
+        gocqhttp
+            .apply_event_filter(&api, &filter, "filter.json")
+            .await
+            .unwrap();
+        assert_eq!(api.remaining(), 0);
+        let written = std::fs::read_to_string(directory.join("filter.json")).unwrap();
+        std::fs::remove_dir_all(&directory).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Value>(&written).unwrap(),
+            json!({ ".not": { "group_id": { ".in": [1] } } })
+        );
+    }
+}
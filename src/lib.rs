@@ -1,8 +1,25 @@
-mod api;
-mod error;
-mod message;
+// 使`cq_code_derive`生成的`::rust_gocqhttp::...`路径在本crate内同样可用
+extern crate self as rust_gocqhttp;
 
+pub mod api;
+pub mod command;
+pub mod dispatcher;
+pub mod error;
+pub mod event;
+pub mod filter;
+pub mod message;
+pub mod permission;
+pub mod plugin;
+pub mod record;
+#[cfg(feature = "testing")]
+pub mod testing;
+
+pub use cq_code_derive::cq;
+
+use crate::api::params::ReloadEventFilter;
+use crate::api::GoCqhttpAPI;
 use crate::error::Result;
+use crate::filter::Filter;
 use log::{error, info};
 use std::io::{Error, ErrorKind};
 use std::path::Path;
@@ -60,4 +77,15 @@ impl GoCqhttp {
             },
         }
     }
+
+    /// 将`filter`写入go-cqhttp目录下的`file`，并通过`api`通知go-cqhttp重新加载事件过滤器
+    pub async fn apply_event_filter<A>(&self, api: &A, filter: &Filter, file: &str) -> Result<()>
+    where
+        A: GoCqhttpAPI + ?Sized,
+    {
+        let path = Path::new(&self.directory).join(file);
+        tokio::fs::write(&path, filter.to_string()).await?;
+        info!("事件过滤器已写入{}", path.display());
+        api.reload_event_filter(ReloadEventFilter::new(file)).await
+    }
 }
@@ -1,10 +1,12 @@
 #![allow(non_snake_case)] // 某个字段的命名不符合规范，但是为了兼容go-cqhttp，所以不改了
 
-use super::CQCode;
-use crate::message::Message;
-use cq_code_derive::CQCode;
-use serde::{Deserialize, Serialize};
+pub use super::media::MediaSource;
+use super::{derive, CQCode};
+use crate::message::forward::ForwardMessage;
+use crate::message::{Message, MessageType};
+use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::Value;
+use std::fmt;
 use std::io::{Error, ErrorKind};
 use std::str::FromStr;
 
@@ -20,14 +22,14 @@ pub type BoolInCQCode = i8;
 #[derive(Debug, Serialize, Deserialize, CQCode)]
 pub struct Face {
     /// QQ表情ID, 见[QQ表情ID表](https://github.com/richardchien/coolq-http-api/wiki/%E8%A1%A8%E6%83%85-CQ-%E7%A0%81-ID-%E8%A1%A8)
-    pub id: Option<i32>,
+    pub id: i32,
 }
 
 /// [语音](https://docs.go-cqhttp.org/cqcode/#%E8%AF%AD%E9%9F%B3)
 #[derive(Debug, Serialize, Deserialize, CQCode)]
 pub struct Record {
-    /// 语音文件名
-    pub file: Option<String>,
+    /// 语音文件，支持的来源同`Image.file`
+    pub file: MediaSource,
     /// 发送时可选, 默认0, 设置为1表示变声
     pub magic: Option<BoolInCQCode>,
     /// 语音 URL
@@ -44,9 +46,9 @@ pub struct Record {
 #[derive(Debug, Serialize, Deserialize, CQCode)]
 pub struct Video {
     /// 视频地址, 支持http和file发送
-    pub file: Option<String>,
+    pub file: MediaSource,
     /// 视频封面, 支持http, file和base64发送, 格式必须为jpg
-    pub cover: Option<String>,
+    pub cover: Option<MediaSource>,
     /// 通过网络下载视频时的线程数, 默认单线程. (在资源不支持并发时会自动处理)
     pub c: Option<i32>,
 }
@@ -55,7 +57,7 @@ pub struct Video {
 #[derive(Debug, Serialize, Deserialize, CQCode)]
 pub struct At {
     /// @的QQ号, all表示全体成员
-    pub qq: Option<String>,
+    pub qq: String,
     /// 当在群中找不到此QQ号的名称时才会生效
     pub name: Option<String>,
 }
@@ -93,9 +95,9 @@ pub struct Anonymous {
 #[derive(Debug, Serialize, Deserialize, CQCode)]
 pub struct Share {
     /// URL
-    pub url: Option<String>,
+    pub url: String,
     /// 标题
-    pub title: Option<String>,
+    pub title: String,
     /// 发送时可选, 内容描述
     pub content: Option<String>,
     /// 发送时可选, 图片URL
@@ -106,53 +108,156 @@ pub struct Share {
 #[derive(Debug, Serialize, Deserialize, CQCode)]
 pub struct Contact {
     /// 类型, group或friend
-    pub type_: Option<String>,
+    #[serde(rename = "type")]
+    pub type_: String,
     /// QQ号或群号
-    pub id: Option<String>,
+    pub id: String,
 }
 
 /// [位置](https://docs.go-cqhttp.org/cqcode/#%E4%BD%8D%E7%BD%AE)
 #[derive(Debug, Serialize, Deserialize, CQCode)]
 pub struct Location {
     /// 经度
-    pub lon: Option<f64>,
+    pub lon: f64,
     /// 纬度
-    pub lat: Option<f64>,
+    pub lat: f64,
     /// 发送时可选, 标题
     pub title: Option<String>,
     /// 发送时可选, 内容描述
     pub content: Option<String>,
 }
 
-/// [音乐分享](https://docs.go-cqhttp.org/cqcode/#%E9%9F%B3%E4%B9%90%E5%88%86%E4%BA%AB)
-///
-/// [音乐自定义分享](https://docs.go-cqhttp.org/cqcode/#%E9%9F%B3%E4%B9%90%E8%87%AA%E5%AE%9A%E4%B9%89%E5%88%86%E4%BA%AB)
-///
-/// 由于这两类的`type`相同，所以合并为一个结构体
-///
-/// 1. 公有字段：`type_`
-/// 2. [音乐分享](https://docs.go-cqhttp.org/cqcode/#%E9%9F%B3%E4%B9%90%E5%88%86%E4%BA%AB)私有字段：`id`
-/// 3. [音乐自定义分享](https://docs.go-cqhttp.org/cqcode/#%E9%9F%B3%E4%B9%90%E8%87%AA%E5%AE%9A%E4%B9%89%E5%88%86%E4%BA%AB)私有字段：`url`, `audio`, `title`, `content`, `image`
+/// `Music::Platform`的音乐平台
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum MusicPlatform {
+    /// QQ音乐，`qq`
+    QQ,
+    /// 网易云音乐，`163`
+    NetEase,
+    /// 虾米音乐，`xm`
+    Xiami,
+}
+
+impl FromStr for MusicPlatform {
+    type Err = Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "qq" => Ok(MusicPlatform::QQ),
+            "163" => Ok(MusicPlatform::NetEase),
+            "xm" => Ok(MusicPlatform::Xiami),
+            _ => Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("未知的音乐平台: {}", s),
+            )),
+        }
+    }
+}
+
+impl fmt::Display for MusicPlatform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            MusicPlatform::QQ => "qq",
+            MusicPlatform::NetEase => "163",
+            MusicPlatform::Xiami => "xm",
+        })
+    }
+}
+
+impl Serialize for MusicPlatform {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for MusicPlatform {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// [音乐分享](https://docs.go-cqhttp.org/cqcode/#%E9%9F%B3%E4%B9%90%E5%88%86%E4%BA%AB)与[音乐自定义分享](https://docs.go-cqhttp.org/cqcode/#%E9%9F%B3%E4%B9%90%E8%87%AA%E5%AE%9A%E4%B9%89%E5%88%86%E4%BA%AB)
 ///
-/// **注意**：这两类的字段不同，使用时请务必查看文档。本类在序列化时，会根据`type`字段自动选择序列化的字段，如果`type`字段不匹配，不再序列化其它字段，直接返回`[CQ:music,type=<your_wrong_input>]`
-#[derive(Debug, Serialize, Deserialize, CQCode)]
-pub struct Music {
-    /// [音乐分享](https://docs.go-cqhttp.org/cqcode/#%E9%9F%B3%E4%B9%90%E5%88%86%E4%BA%AB): 可选值为`qq`, `163`, `xm`分别表示使用QQ音乐、网易云音乐、虾米音乐，此时需要填写`id`字段
-    ///
-    /// [音乐自定义分享](https://docs.go-cqhttp.org/cqcode/#%E9%9F%B3%E4%B9%90%E8%87%AA%E5%AE%9A%E4%B9%89%E5%88%86%E4%BA%AB): 可选值为`custom`，此时需要填写`url`、`audio`、`title`、`content`、`image`字段
-    pub type_: Option<String>,
-    /// [音乐分享](https://docs.go-cqhttp.org/cqcode/#%E9%9F%B3%E4%B9%90%E5%88%86%E4%BA%AB)私有字段，歌曲ID
-    pub id: Option<String>,
-    /// [音乐自定义分享](https://docs.go-cqhttp.org/cqcode/#%E9%9F%B3%E4%B9%90%E8%87%AA%E5%AE%9A%E4%B9%89%E5%88%86%E4%BA%AB)私有字段，点击后跳转目标URL
-    pub url: Option<String>,
-    /// [音乐自定义分享](https://docs.go-cqhttp.org/cqcode/#%E9%9F%B3%E4%B9%90%E8%87%AA%E5%AE%9A%E4%B9%89%E5%88%86%E4%BA%AB)私有字段，音乐URL
-    pub audio: Option<String>,
-    /// [音乐自定义分享](https://docs.go-cqhttp.org/cqcode/#%E9%9F%B3%E4%B9%90%E8%87%AA%E5%AE%9A%E4%B9%89%E5%88%86%E4%BA%AB)私有字段，标题
-    pub title: Option<String>,
-    /// [音乐自定义分享](https://docs.go-cqhttp.org/cqcode/#%E9%9F%B3%E4%B9%90%E8%87%AA%E5%AE%9A%E4%B9%89%E5%88%86%E4%BA%AB)私有字段，发送时可选，内容描述
-    pub content: Option<String>,
-    /// [音乐自定义分享](https://docs.go-cqhttp.org/cqcode/#%E9%9F%B3%E4%B9%90%E8%87%AA%E5%AE%9A%E4%B9%89%E5%88%86%E4%BA%AB)私有字段，发送时可选，图片URL
-    pub image: Option<String>,
+/// 这两类的`type`字段相同，但字段不同，所以用`type`字段区分为两个变体
+#[derive(Debug, CQCode)]
+pub enum Music {
+    /// [音乐分享](https://docs.go-cqhttp.org/cqcode/#%E9%9F%B3%E4%B9%90%E5%88%86%E4%BA%AB)
+    Platform {
+        /// 音乐平台
+        #[cq(tag)]
+        kind: MusicPlatform,
+        /// 歌曲ID
+        id: String,
+    },
+    /// [音乐自定义分享](https://docs.go-cqhttp.org/cqcode/#%E9%9F%B3%E4%B9%90%E8%87%AA%E5%AE%9A%E4%B9%89%E5%88%86%E4%BA%AB)
+    #[cq(tag = "custom")]
+    Custom {
+        /// 点击后跳转目标URL
+        url: String,
+        /// 音乐URL
+        audio: String,
+        /// 标题
+        title: String,
+        /// 发送时可选，内容描述
+        content: Option<String>,
+        /// 发送时可选，图片URL
+        image: Option<String>,
+    },
+}
+
+/// `Image.type_`字段的类型
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ImageType {
+    /// 闪照，`flash`
+    Flash,
+    /// 秀图，`show`
+    Show,
+}
+
+impl FromStr for ImageType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "flash" => Ok(ImageType::Flash),
+            "show" => Ok(ImageType::Show),
+            _ => Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("未知的图片类型: {}", s),
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ImageType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ImageType::Flash => "flash",
+            ImageType::Show => "show",
+        })
+    }
+}
+
+impl Serialize for ImageType {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for ImageType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
 }
 
 /// [图片](https://docs.go-cqhttp.org/cqcode/#%E5%9B%BE%E7%89%87)
@@ -162,9 +267,12 @@ pub struct Image {
     /// - 绝对路径，例如 `file:///C:\\Users\Alice\Pictures\1.png`，格式使用 [file URI](https://tools.ietf.org/html/rfc8089)
     /// - 网络 URL，例如 `https://www.baidu.com/img/PCtm_d9c8750bed0b3c7d089fa7d55720d6cf.png`
     /// - Base64 编码，例如 `base64://iVBORw0KGg==`
-    pub file: Option<String>,
+    ///
+    /// 接收到的图片为go-cqhttp缓存中的文件名
+    pub file: MediaSource,
     /// 图片类型, flash表示闪照, show表示秀图, 默认普通图片
-    pub type_: Option<String>,
+    #[serde(rename = "type")]
+    pub type_: Option<ImageType>,
     /// 图片子类型, 只出现在群聊
     ///
     /// |value|说明|
@@ -220,7 +328,7 @@ pub struct Reply {
 #[derive(Debug, Serialize, Deserialize, CQCode)]
 pub struct RedBag {
     /// 祝福语/口令
-    pub title: Option<String>,
+    pub title: String,
 }
 
 /// [戳一戳](https://docs.go-cqhttp.org/cqcode/#%E6%88%B3%E4%B8%80%E6%88%B3)
@@ -229,14 +337,14 @@ pub struct RedBag {
 #[derive(Debug, Serialize, Deserialize, CQCode)]
 pub struct Poke {
     /// 需要戳的成员
-    pub qq: Option<i64>,
+    pub qq: i64,
 }
 
 /// [礼物](https://docs.go-cqhttp.org/cqcode/#%E7%A4%BC%E7%89%A9)
 #[derive(Debug, Serialize, Deserialize, CQCode)]
 pub struct Gift {
     /// 接收礼物的成员
-    pub qq: Option<i64>,
+    pub qq: i64,
     /// 礼物的类型
     ///
     /// |id|类型|
@@ -255,14 +363,14 @@ pub struct Gift {
     /// |11|神秘面具|
     /// |12|我超忙的|
     /// |13|爱心口罩|
-    pub id: Option<i32>,
+    pub id: i32,
 }
 
 /// [合并转发](https://docs.go-cqhttp.org/cqcode/#%E5%90%88%E5%B9%B6%E8%BD%AC%E5%8F%91)
 #[derive(Debug, Serialize, Deserialize, CQCode)]
 pub struct Forward {
     /// 合并转发ID, 需要通过`/get_forward_msg`API获取转发的具体内容
-    pub id: Option<i32>,
+    pub id: i32,
 }
 
 /// [合并转发消息节点](https://docs.go-cqhttp.org/cqcode/#%E5%90%88%E5%B9%B6%E8%BD%AC%E5%8F%91%E6%B6%88%E6%81%AF%E8%8A%82%E7%82%B9)
@@ -278,7 +386,7 @@ pub struct Node {
     pub name: Option<String>,
     /// 发送者QQ号, 用于自定义消息
     pub uin: Option<i64>,
-    /// 用于自定义消息 不支持转发套娃
+    /// 用于自定义消息，可以是嵌套的合并转发，见`Node::nested`
     pub content: Option<Message>,
     /// 具体消息, 用于自定义消息
     pub seq: Option<Message>,
@@ -297,11 +405,12 @@ impl CQCode for Node {
             s.push_str(format!(",uin={}", uin).as_str());
         }
         if let Some(content) = &self.content {
-            s.push_str(format!(",content={}", content.to_string()).as_str());
+            s.push_str(format!(",content={}", content).as_str());
         }
         if let Some(seq) = &self.seq {
-            s.push_str(format!(",seq={}", seq.to_string()).as_str());
+            s.push_str(format!(",seq={}", seq).as_str());
         }
+        s.push(']');
         s
     }
 
@@ -311,21 +420,14 @@ impl CQCode for Node {
         let mut uin = None;
         let mut content = None;
         let mut seq = None;
-        let re = regex::Regex::new(r"\[CQ:node(?P<fields>(,\w+=[^,\]]+)*)]")?;
-        for cap in re.captures_iter(&s) {
-            let fields = cap.name("fields").unwrap().as_str();
-            for field in fields.split(',') {
-                let mut iter = field.split('=');
-                let key = iter.next().unwrap();
-                let value = iter.next().unwrap();
-                match key {
-                    "id" => id = Some(i32::from_str(value)?),
-                    "name" => name = Some(value.to_string()),
-                    "uin" => uin = Some(i64::from_str(value)?),
-                    "content" => content = Some(Message::from_string(value.to_string())?),
-                    "seq" => seq = Some(Message::from_string(value.to_string())?),
-                    _ => {}
-                }
+        for (key, value) in derive::parse::<Self>("node", &s)? {
+            match key.as_str() {
+                "id" => id = Some(derive::parse_value("node", &key, &value)?),
+                "name" => name = Some(value),
+                "uin" => uin = Some(derive::parse_value("node", &key, &value)?),
+                "content" => content = Some(Message::from_string(value)?),
+                "seq" => seq = Some(Message::from_string(value)?),
+                _ => return Err(derive::unknown_field("node", &key)),
             }
         }
         Ok(Self {
@@ -338,45 +440,103 @@ impl CQCode for Node {
     }
 
     fn to_json(&self) -> crate::error::Result<String> {
-        let mut s = String::from("{\"type\":\"node\",\"data\":{");
+        let mut data = Vec::new();
         if let Some(id) = &self.id {
-            s.push_str(format!("\"id\":{},", id).as_str());
+            data.push(format!("\"id\":{}", id));
         }
         if let Some(name) = &self.name {
-            s.push_str(format!("\"name\":\"{}\",", name).as_str());
+            data.push(format!("\"name\":{}", serde_json::to_string(name)?));
         }
         if let Some(uin) = &self.uin {
-            s.push_str(format!("\"uin\":{},", uin).as_str());
+            data.push(format!("\"uin\":{}", uin));
         }
         if let Some(content) = &self.content {
-            s.push_str(format!("\"content\":{},", content.to_string()).as_str());
+            data.push(format!("\"content\":{}", Self::message_to_json(content)?));
         }
         if let Some(seq) = &self.seq {
-            s.push_str(format!("\"seq\":{}", seq.to_string()).as_str());
+            data.push(format!("\"seq\":{}", Self::message_to_json(seq)?));
         }
-        if s.ends_with(',') {
-            s.pop();
-        }
-        s.push_str("}}");
-        Ok(s)
+        Ok(format!(
+            "{{\"type\":\"node\",\"data\":{{{}}}}}",
+            data.join(",")
+        ))
     }
 
     fn from_json(s: &str) -> crate::error::Result<Self> {
-        let v: Value = serde_json::from_str(s)?;
-        if let Some("node") = v["type"].as_str() {
-            Ok(Self {
-                id: v["data"]["id"].as_i64().and_then(|i| i32::try_from(i).ok()),
-                name: v["data"]["name"].as_str().map(ToString::to_string),
-                uin: v["data"]["uin"].as_i64(),
-                content: v["data"]["content"]
-                    .as_str()
-                    .and_then(|c| Message::from_json(c).ok()),
-                seq: v["data"]["seq"]
-                    .as_str()
-                    .and_then(|c| Message::from_json(c).ok()),
-            })
-        } else {
-            Err(Error::new(ErrorKind::InvalidData, "type字段不为node").into())
+        let data = derive::json_data("node", s)?;
+        Ok(Self {
+            id: match data.get("id") {
+                Some(id) => Some(derive::json_value("node", "id", id.clone())?),
+                None => None,
+            },
+            name: data
+                .get("name")
+                .and_then(Value::as_str)
+                .map(ToString::to_string),
+            uin: match data.get("uin") {
+                Some(uin) => Some(derive::json_value("node", "uin", uin.clone())?),
+                None => None,
+            },
+            content: data
+                .get("content")
+                .map(Self::message_from_json)
+                .transpose()?,
+            seq: data.get("seq").map(Self::message_from_json).transpose()?,
+        })
+    }
+}
+
+impl Node {
+    /// 引用已有的消息
+    pub fn reference(id: i32) -> Self {
+        Self {
+            id: Some(id),
+            name: None,
+            uin: None,
+            content: None,
+            seq: None,
+        }
+    }
+
+    /// 自定义消息，`name`和`uin`为显示的发送者
+    pub fn custom(name: impl Into<String>, uin: i64, content: Message) -> Self {
+        Self {
+            id: None,
+            name: Some(name.into()),
+            uin: Some(uin),
+            content: Some(content),
+            seq: None,
+        }
+    }
+
+    /// 自定义消息，内容为嵌套的合并转发
+    pub fn nested(
+        name: impl Into<String>,
+        uin: i64,
+        forward: &ForwardMessage,
+    ) -> crate::error::Result<Self> {
+        Ok(Self::custom(name, uin, forward.to_message()?))
+    }
+
+    /// 数组格式的消息原样输出，字符串格式的消息输出为JSON字符串
+    fn message_to_json(message: &Message) -> crate::error::Result<String> {
+        Ok(match message.message_type() {
+            MessageType::Array => message.to_string(),
+            MessageType::String => serde_json::to_string(&message.to_string())?,
+        })
+    }
+
+    fn message_from_json(value: &Value) -> crate::error::Result<Message> {
+        match value {
+            Value::String(s) => Message::from_string(s.clone()),
+            Value::Array(segments) => Ok(Message {
+                messages: segments.iter().map(ToString::to_string).collect(),
+            }),
+            _ => Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("CQ码`node`的消息内容不合法: {}", value),
+            )
+            .into()),
         }
     }
 }
@@ -385,7 +545,7 @@ impl CQCode for Node {
 #[derive(Debug, Serialize, Deserialize, CQCode)]
 pub struct Xml {
     /// xml内容, xml中的value部分, 记得实体化处理
-    pub data: Option<String>,
+    pub data: String,
     /// 可能为空, 或空字符串
     pub resid: Option<i32>,
 }
@@ -394,9 +554,10 @@ pub struct Xml {
 #[derive(Debug, Serialize, Deserialize, CQCode)]
 pub struct Json {
     /// json内容, json的所有字符串记得实体化处理
-    pub data: Option<String>,
+    pub data: String,
     /// 默认不填为0, 走小程序通道, 填了走富文本通道发送
-    pub resid: Option<i32>,
+    #[cq(default = 0)]
+    pub resid: i32,
 }
 
 /// [cardimage](https://docs.go-cqhttp.org/cqcode/#cardimage)
@@ -405,7 +566,7 @@ pub struct Json {
 #[derive(Debug, Serialize, Deserialize, CQCode)]
 pub struct CardImage {
     /// 和image的file字段对齐, 支持也是一样的
-    pub file: Option<String>,
+    pub file: MediaSource,
     /// 默认不填为400, 最小width
     pub minwidth: Option<i64>,
     /// 默认不填为400, 最小height
@@ -426,29 +587,29 @@ pub struct CardImage {
 #[derive(Debug, Serialize, Deserialize, CQCode)]
 pub struct Tts {
     /// 内容
-    pub text: Option<String>,
+    pub text: String,
 }
 
 /// [文本](https://docs.go-cqhttp.org/reference/#%E6%95%B0%E7%BB%84%E6%A0%BC%E5%BC%8F%E6%B6%88%E6%81%AF)，不是CQ码，是为了方便构造消息
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Text {
     /// 文本内容
-    pub text: Option<String>,
+    pub text: String,
 }
 
 impl CQCode for Text {
     fn to_string(&self) -> String {
-        self.text.as_ref().unwrap().to_string()
+        self.text.clone()
     }
 
     fn from_string(s: String) -> crate::error::Result<Self> {
-        Ok(Self { text: Some(s) })
+        Ok(Self { text: s })
     }
 
     fn to_json(&self) -> crate::error::Result<String> {
         Ok(format!(
             "{{\"type\":\"text\",\"data\":{{\"text\":\"{}\"}}}}",
-            self.text.as_ref().unwrap()
+            self.text
         ))
     }
 
@@ -457,7 +618,7 @@ impl CQCode for Text {
         match v["type"].as_str() {
             Some("text") => match v["data"]["text"].as_str() {
                 Some(s) => Ok(Self {
-                    text: Some(s.to_string()),
+                    text: s.to_string(),
                 }),
                 None => Err(Error::new(ErrorKind::InvalidData, "没有找到text字段").into()),
             },
@@ -475,19 +636,51 @@ mod test {
 
     #[test]
     fn test_face_to_string() {
-        let t = Face { id: Some(123) };
+        let t = Face { id: 123 };
         assert_eq!(t.to_string(), "[CQ:face,id=123]");
     }
 
     #[test]
     fn test_face_from_string() {
         let t = Face::from_string("[CQ:face,id=123]".to_string()).unwrap();
+        assert_eq!(t.id, 123);
+    }
+
+    #[test]
+    fn test_face_from_string_invalid() {
+        let e = Face::from_string("[CQ:face,id=abc]".to_string())
+            .unwrap_err()
+            .to_string();
+        assert!(
+            e.contains("id") && e.contains("abc") && e.contains("i32"),
+            "{}",
+            e
+        );
+        assert!(Face::from_string("hello".to_string()).is_err());
+        assert!(Face::from_string("[CQ:face,id=1]hello".to_string()).is_err());
+        assert!(Face::from_string("[CQ:at,qq=1]".to_string()).is_err());
+        assert!(Face::from_string("[CQ:face,id=1,foo=2]".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_face_from_json_invalid() {
+        assert!(Face::from_json("hello").is_err());
+        assert!(Face::from_json(r#"{"type":1,"data":{"id":1}}"#).is_err());
+        assert!(Face::from_json(r#"{"type":"face"}"#).is_err());
+        assert!(Face::from_json(r#"{"type":"face","data":{"id":"abc"}}"#).is_err());
+    }
+
+    #[test]
+    fn test_node_from_string() {
+        let t = Node::from_string("[CQ:node,id=123]".to_string()).unwrap();
         assert_eq!(t.id, Some(123));
+        assert!(Node::from_string("[CQ:node,id=abc]".to_string()).is_err());
+        assert!(Node::from_string("[CQ:node,foo]".to_string()).is_err());
     }
 
     #[test]
     fn test_face_to_json() {
-        let t = Face { id: Some(123) };
+        let t = Face { id: 123 };
         assert_eq!(
             t.to_json().unwrap().as_str(),
             r#"{"type":"face","data":{"id":123}}"#
@@ -497,13 +690,13 @@ mod test {
     #[test]
     fn test_face_from_json() {
         let t = Face::from_json(r#"{"type":"face","data":{"id":123}}"#).unwrap();
-        assert_eq!(t.id, Some(123));
+        assert_eq!(t.id, 123);
     }
 
     #[test]
     fn test_record_to_string0() {
         let t = Record {
-            file: Some(r"file:///C:\\Users\Alice\Pictures\1.png".to_string()),
+            file: r"file:///C:\\Users\Alice\Pictures\1.png".parse().unwrap(),
             magic: Some(1),
             url: Some(
                 "https://www.baidu.com/img/PCtm_d9c8750bed0b3c7d089fa7d55720d6cf.png".to_string(),
@@ -521,7 +714,7 @@ mod test {
     #[test]
     fn test_record_to_string1() {
         let t = Record {
-            file: Some(r"file:///C:\\Users\Alice\Pictures\1.png".to_string()),
+            file: r"file:///C:\\Users\Alice\Pictures\1.png".parse().unwrap(),
             magic: Some(1),
             url: None,
             cache: None,
@@ -537,22 +730,22 @@ mod test {
     #[test]
     fn test_record_to_string2() {
         let t = Record {
-            file: None,
+            file: "1.mp3".parse().unwrap(),
             magic: None,
             url: None,
             cache: None,
             proxy: None,
             timeout: None,
         };
-        assert_eq!(t.to_string(), r"[CQ:record]");
+        assert_eq!(t.to_string(), r"[CQ:record,file=1.mp3]");
     }
 
     #[test]
     fn test_record_from_string0() {
         let t = Record::from_string(r"[CQ:record,file=file:///C:\\Users\Alice\Pictures\1.png,magic=1,url=https://www.baidu.com/img/PCtm_d9c8750bed0b3c7d089fa7d55720d6cf.png,cache=1,proxy=1,timeout=1]".to_string()).unwrap();
         assert_eq!(
-            t.file,
-            Some(r"file:///C:\\Users\Alice\Pictures\1.png".to_string())
+            t.file.to_string(),
+            r"file:///C:\\Users\Alice\Pictures\1.png".to_string()
         );
         assert_eq!(t.magic, Some(1));
         assert_eq!(
@@ -571,8 +764,8 @@ mod test {
         )
         .unwrap();
         assert_eq!(
-            t.file,
-            Some(r"file:///C:\\Users\Alice\Pictures\1.png".to_string())
+            t.file.to_string(),
+            r"file:///C:\\Users\Alice\Pictures\1.png".to_string()
         );
         assert_eq!(t.magic, Some(1));
         assert_eq!(t.url, None);
@@ -583,19 +776,13 @@ mod test {
 
     #[test]
     fn test_record_from_string2() {
-        let t = Record::from_string(r"[CQ:record]".to_string()).unwrap();
-        assert_eq!(t.file, None);
-        assert_eq!(t.magic, None);
-        assert_eq!(t.url, None);
-        assert_eq!(t.cache, None);
-        assert_eq!(t.proxy, None);
-        assert_eq!(t.timeout, None);
+        assert!(Record::from_string(r"[CQ:record]".to_string()).is_err());
     }
 
     #[test]
     fn test_record_to_json0() {
         let t = Record {
-            file: Some(r"file:///C:\Users\User\Pictures\1.png".to_string()),
+            file: r"file:///C:\Users\User\Pictures\1.png".parse().unwrap(),
             magic: Some(1),
             url: Some("https://www.baidu.com/img/1.png".to_string()),
             cache: Some(1),
@@ -611,7 +798,7 @@ mod test {
     #[test]
     fn test_record_to_json1() {
         let t = Record {
-            file: Some(r"file:///C:\Users\User\Pictures\1.png".to_string()),
+            file: r"file:///C:\Users\User\Pictures\1.png".parse().unwrap(),
             magic: Some(1),
             url: None,
             cache: None,
@@ -627,7 +814,7 @@ mod test {
     #[test]
     fn test_record_to_json2() {
         let t = Record {
-            file: None,
+            file: "1.mp3".parse().unwrap(),
             magic: None,
             url: None,
             cache: None,
@@ -636,7 +823,7 @@ mod test {
         };
         assert_eq!(
             t.to_json().unwrap().as_str(),
-            r#"{"type":"record","data":{}}"#
+            r#"{"type":"record","data":{"file":"1.mp3"}}"#
         );
     }
 
@@ -644,8 +831,8 @@ mod test {
     fn test_record_from_json0() {
         let t = Record::from_json(r#"{"type":"record","data":{"file":"file:///C:\\Users\\User\\Pictures\\1.png","magic":1,"url":"https://www.baidu.com/img/1.png","cache":1,"proxy":1,"timeout":1}}"#).unwrap();
         assert_eq!(
-            t.file,
-            Some(r"file:///C:\Users\User\Pictures\1.png".to_string())
+            t.file.to_string(),
+            r"file:///C:\Users\User\Pictures\1.png".to_string()
         );
         assert_eq!(t.magic, Some(1));
         assert_eq!(t.url, Some("https://www.baidu.com/img/1.png".to_string()));
@@ -658,8 +845,8 @@ mod test {
     fn test_record_from_json1() {
         let t = Record::from_json(r#"{"type":"record","data":{"file":"file:///C:\\Users\\User\\Pictures\\1.png","magic":1}}"#).unwrap();
         assert_eq!(
-            t.file,
-            Some(r"file:///C:\Users\User\Pictures\1.png".to_string())
+            t.file.to_string(),
+            r"file:///C:\Users\User\Pictures\1.png".to_string()
         );
         assert_eq!(t.magic, Some(1));
         assert_eq!(t.url, None);
@@ -670,13 +857,77 @@ mod test {
 
     #[test]
     fn test_record_from_json2() {
-        let t = Record::from_json(r#"{"type":"record","data":{}}"#).unwrap();
-        assert_eq!(t.file, None);
-        assert_eq!(t.magic, None);
-        assert_eq!(t.url, None);
-        assert_eq!(t.cache, None);
-        assert_eq!(t.proxy, None);
-        assert_eq!(t.timeout, None);
+        assert!(Record::from_json(r#"{"type":"record","data":{}}"#).is_err());
+    }
+
+    #[test]
+    fn test_contact_from_string() {
+        let t = Contact::from_string("[CQ:contact,type=group,id=100100]".to_string()).unwrap();
+        assert_eq!(t.type_, "group");
+        assert_eq!(t.id, "100100");
+    }
+
+    #[test]
+    fn test_music_platform() {
+        let t = Music::from_string("[CQ:music,type=163,id=28949129]".to_string()).unwrap();
+        match &t {
+            Music::Platform { kind, id } => {
+                assert_eq!(*kind, MusicPlatform::NetEase);
+                assert_eq!(id, "28949129");
+            }
+            _ => panic!("expected Music::Platform"),
+        }
+        assert_eq!(t.to_string(), "[CQ:music,type=163,id=28949129]");
+        assert_eq!(
+            t.to_json().unwrap(),
+            r#"{"type":"music","data":{"type":"163","id":"28949129"}}"#
+        );
+        assert!(Music::from_string("[CQ:music,type=qq]".to_string()).is_err());
+        assert!(Music::from_string("[CQ:music,type=kugou,id=1]".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_music_custom() {
+        let t = Music::from_json(
+            r#"{"type":"music","data":{"type":"custom","url":"a","audio":"b","title":"c"}}"#,
+        )
+        .unwrap();
+        match &t {
+            Music::Custom {
+                url,
+                audio,
+                title,
+                content,
+                image,
+            } => {
+                assert_eq!(url, "a");
+                assert_eq!(audio, "b");
+                assert_eq!(title, "c");
+                assert_eq!(*content, None);
+                assert_eq!(*image, None);
+            }
+            _ => panic!("expected Music::Custom"),
+        }
+        assert_eq!(
+            t.to_string(),
+            "[CQ:music,type=custom,url=a,audio=b,title=c]"
+        );
+        assert!(Music::from_string("[CQ:music,type=custom,url=a,audio=b]".to_string()).is_err());
+        assert!(Music::from_string(
+            "[CQ:music,type=custom,url=a,audio=b,title=c,id=1]".to_string()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_image_type() {
+        let t = Image::from_string("[CQ:image,file=1.png,type=flash]".to_string()).unwrap();
+        assert_eq!(t.type_, Some(ImageType::Flash));
+        assert_eq!(t.to_string(), "[CQ:image,file=1.png,type=flash]");
+        assert_eq!(
+            t.to_json().unwrap(),
+            r#"{"type":"image","data":{"file":"1.png","type":"flash"}}"#
+        );
     }
 
     #[test]
@@ -700,8 +951,8 @@ mod test {
             id: None,
             name: Some("hello".to_string()),
             uin: Some(456),
-            content: Some(message_from_jsons!(Face { id: Some(123) })),
-            seq: Some(message_from_jsons!(Face { id: Some(456) })),
+            content: Some(message_from_jsons!(Face { id: 123 })),
+            seq: Some(message_from_jsons!(Face { id: 456 })),
         };
         assert_eq!(
             t.to_json().unwrap().as_str(),
@@ -716,11 +967,11 @@ mod test {
             name: Some("hello".to_string()),
             uin: Some(456),
             content: Some(message_from_jsons!(
-                Face { id: Some(123) },
+                Face { id: 123 },
                 Text {
-                    text: Some("world".to_string())
+                    text: "world".to_string()
                 },
-                Face { id: Some(456) }
+                Face { id: 456 }
             )),
             seq: None,
         };
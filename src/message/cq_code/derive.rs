@@ -0,0 +1,133 @@
+//! 供`cq_code_derive`生成的代码使用的辅助函数，不属于公开API
+
+use super::CQCode;
+use crate::error::{Error, Result};
+use regex::Regex;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::fmt::Display;
+use std::io::ErrorKind;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+pub use serde;
+pub use serde_json;
+
+fn invalid(msg: String) -> Error {
+    Box::new(std::io::Error::new(ErrorKind::InvalidData, msg))
+}
+
+/// 解析字符串格式的CQ码，检查其类型为`name`，返回反转义后的字段
+pub fn parse<T: CQCode>(name: &str, s: &str) -> Result<Vec<(String, String)>> {
+    static CODE: OnceLock<Regex> = OnceLock::new();
+    static FIELD: OnceLock<Regex> = OnceLock::new();
+    let code = CODE
+        .get_or_init(|| Regex::new(r"^\[CQ:(?P<name>\w+)(?P<fields>(,\w+=[^,\]]+)*)\]$").unwrap());
+    let field = FIELD.get_or_init(|| Regex::new(r",(?P<field>\w+)=(?P<value>[^,\]]+)").unwrap());
+    let caps = code
+        .captures(s)
+        .ok_or_else(|| invalid(format!("`{}`不是一个CQ码, 期望`[CQ:{},...]`", s, name)))?;
+    if &caps["name"] != name {
+        return Err(invalid(format!(
+            "CQ码类型不匹配: 期望`{}`, 实际为`{}`",
+            name, &caps["name"]
+        )));
+    }
+    Ok(field
+        .captures_iter(&caps["fields"])
+        .map(|cap| (cap["field"].to_string(), T::anti_escape(&cap["value"])))
+        .collect())
+}
+
+/// 将字符串格式CQ码中字段的值解析为`V`
+pub fn parse_value<V>(name: &str, field: &str, value: &str) -> Result<V>
+where
+    V: FromStr,
+    V::Err: Display,
+{
+    value.parse().map_err(|e| {
+        invalid(format!(
+            "CQ码`{}`的字段`{}`的值`{}`无法解析为`{}`: {}",
+            name,
+            field,
+            value,
+            std::any::type_name::<V>(),
+            e
+        ))
+    })
+}
+
+/// 将数组格式CQ码中字段的值反序列化为`V`
+pub fn json_value<V: DeserializeOwned>(name: &str, field: &str, value: Value) -> Result<V> {
+    serde_json::from_value(value.clone()).map_err(|e| {
+        invalid(format!(
+            "CQ码`{}`的字段`{}`的值`{}`无法解析为`{}`: {}",
+            name,
+            field,
+            value,
+            std::any::type_name::<V>(),
+            e
+        ))
+    })
+}
+
+pub fn missing_field(name: &str, field: &str) -> Error {
+    invalid(format!("CQ码`{}`缺少必填字段`{}`", name, field))
+}
+
+pub fn unknown_field(name: &str, field: &str) -> Error {
+    invalid(format!("CQ码`{}`不存在字段`{}`", name, field))
+}
+
+pub fn unknown_tag(name: &str, tag: &str, value: &str) -> Error {
+    invalid(format!(
+        "CQ码`{}`的标签字段`{}`的值`{}`不属于任何变体",
+        name, tag, value
+    ))
+}
+
+/// 解析数组格式的CQ码，检查其类型为`name`，返回`data`字段
+pub fn json_data(name: &str, s: &str) -> Result<Map<String, Value>> {
+    let v: Value =
+        serde_json::from_str(s).map_err(|e| invalid(format!("`{}`不是合法的JSON: {}", s, e)))?;
+    match v.get("type").and_then(Value::as_str) {
+        Some(t) if t == name => {}
+        Some(t) => {
+            return Err(invalid(format!(
+                "CQ码类型不匹配: 期望`{}`, 实际为`{}`",
+                name, t
+            )))
+        }
+        None => return Err(invalid(format!("`{}`没有找到type字段", s))),
+    }
+    match v.get("data") {
+        Some(Value::Object(data)) => Ok(data.clone()),
+        _ => Err(invalid(format!("`{}`没有找到data字段", s))),
+    }
+}
+
+/// 将`data`字段反序列化为CQ码
+pub fn from_data<T: DeserializeOwned>(name: &str, data: Map<String, Value>) -> Result<T> {
+    serde_json::from_value(Value::Object(data))
+        .map_err(|e| invalid(format!("CQ码`{}`的data字段不合法: {}", name, e)))
+}
+
+/// 转义字符串格式消息中的文本，文本中的`,`不需要转义
+pub fn escape_text(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('[', "&#91;")
+        .replace(']', "&#93;")
+}
+
+/// 将CQ码序列化为数组格式，省略值为`null`的字段
+pub fn to_json<T: Serialize>(name: &str, code: &T) -> Result<String> {
+    static NULL: OnceLock<Regex> = OnceLock::new();
+    let null = NULL.get_or_init(|| Regex::new(r#"(,"[^"]+":null|"[^"]+":null,?)"#).unwrap());
+    let data = serde_json::to_string(code)?;
+    Ok(format!(
+        "{{\"type\":\"{}\",\"data\":{}}}",
+        name,
+        null.replace_all(&data, "")
+    ))
+}
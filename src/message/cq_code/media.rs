@@ -0,0 +1,172 @@
+use crate::error::Result;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::convert::Infallible;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// 图片、语音、视频等CQ码中`file`字段的来源
+///
+/// 字符串形式与go-cqhttp一致：
+/// - 本地文件：`file:///C:\Users\Alice\Pictures\1.png`, `file:///home/alice/1.png`
+/// - 网络URL：`http://...`, `https://...`
+/// - Base64编码：`base64://iVBORw0KGg==`
+/// - 其余视为go-cqhttp缓存中的文件名，例如收到的图片的`file`字段
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum MediaSource {
+    /// 本地文件，go-cqhttp需要能访问到该路径
+    Path(PathBuf),
+    /// 网络URL
+    Url(String),
+    /// Base64编码后的文件内容，不含`base64://`前缀
+    Base64(String),
+    /// go-cqhttp缓存中的文件名
+    Cached(String),
+}
+
+impl MediaSource {
+    /// 本地文件，相对路径会基于当前工作目录转为绝对路径
+    pub fn from_path(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref();
+        Self::Path(std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf()))
+    }
+
+    /// 网络URL
+    pub fn from_url(url: impl Into<String>) -> Self {
+        Self::Url(url.into())
+    }
+
+    /// 将文件内容编码为Base64
+    pub fn from_bytes(bytes: impl AsRef<[u8]>) -> Self {
+        Self::Base64(STANDARD.encode(bytes))
+    }
+
+    /// go-cqhttp缓存中的文件名
+    pub fn from_cached_name(name: impl Into<String>) -> Self {
+        Self::Cached(name.into())
+    }
+
+    /// 异步读取本地文件并编码为Base64，适用于go-cqhttp与本程序不在同一台机器上的情况
+    pub async fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self::from_bytes(tokio::fs::read(path).await?))
+    }
+
+    /// 将`Path`异步读取并转换为`Base64`，其余来源保持不变
+    pub async fn embed(self) -> Result<Self> {
+        match self {
+            Self::Path(path) => Self::from_file(path).await,
+            other => Ok(other),
+        }
+    }
+
+    /// 解码`Base64`来源的内容，其余来源返回`None`
+    pub fn bytes(&self) -> Option<Result<Vec<u8>>> {
+        match self {
+            Self::Base64(data) => Some(STANDARD.decode(data).map_err(Into::into)),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for MediaSource {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Ok(if let Some(path) = s.strip_prefix("file://") {
+            // `file:///C:\...`去掉`file://`后为`/C:\...`，需要再去掉开头的`/`
+            let bytes = path.as_bytes();
+            let windows = bytes.len() >= 3
+                && bytes[0] == b'/'
+                && bytes[1].is_ascii_alphabetic()
+                && bytes[2] == b':';
+            Self::Path(PathBuf::from(if windows { &path[1..] } else { path }))
+        } else if let Some(data) = s.strip_prefix("base64://") {
+            Self::Base64(data.to_string())
+        } else if s.starts_with("http://") || s.starts_with("https://") {
+            Self::Url(s.to_string())
+        } else {
+            Self::Cached(s.to_string())
+        })
+    }
+}
+
+impl fmt::Display for MediaSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Path(path) => {
+                let path = path.to_string_lossy();
+                if path.starts_with('/') {
+                    write!(f, "file://{}", path)
+                } else {
+                    write!(f, "file:///{}", path)
+                }
+            }
+            Self::Url(url) => f.write_str(url),
+            Self::Base64(data) => write!(f, "base64://{}", data),
+            Self::Cached(name) => f.write_str(name),
+        }
+    }
+}
+
+impl Serialize for MediaSource {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for MediaSource {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        let Ok(source) = s.parse();
+        Ok(source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MediaSource;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_media_source_from_str() {
+        let s: MediaSource = r"file:///C:\Users\Alice\1.png".parse().unwrap();
+        assert_eq!(s, MediaSource::Path(PathBuf::from(r"C:\Users\Alice\1.png")));
+        assert_eq!(s.to_string(), r"file:///C:\Users\Alice\1.png");
+        let s: MediaSource = "file:///home/alice/1.png".parse().unwrap();
+        assert_eq!(s, MediaSource::Path(PathBuf::from("/home/alice/1.png")));
+        assert_eq!(s.to_string(), "file:///home/alice/1.png");
+        let s: MediaSource = "https://a.com/1.png".parse().unwrap();
+        assert_eq!(s, MediaSource::from_url("https://a.com/1.png"));
+        let s: MediaSource = "base64://aGVsbG8=".parse().unwrap();
+        assert_eq!(s, MediaSource::from_bytes("hello"));
+        assert_eq!(s.bytes().unwrap().unwrap(), b"hello");
+        let s: MediaSource = "abc.image".parse().unwrap();
+        assert_eq!(s, MediaSource::from_cached_name("abc.image"));
+        assert_eq!(s.to_string(), "abc.image");
+    }
+
+    #[test]
+    fn test_media_source_from_path() {
+        let s = MediaSource::from_path("1.png");
+        let MediaSource::Path(path) = &s else {
+            panic!("{:?}", s)
+        };
+        assert!(path.is_absolute());
+        assert!(s.to_string().starts_with("file:///"));
+    }
+
+    #[tokio::test]
+    async fn test_media_source_from_file() {
+        let path = std::env::temp_dir().join("rust_gocqhttp_media_source.txt");
+        tokio::fs::write(&path, "hello").await.unwrap();
+        let s = MediaSource::from_path(&path).embed().await.unwrap();
+        assert_eq!(s.to_string(), "base64://aGVsbG8=");
+        tokio::fs::remove_file(&path).await.unwrap();
+        assert!(MediaSource::from_file(&path).await.is_err());
+    }
+}
@@ -1,6 +1,10 @@
-pub(crate) mod code;
+pub mod code;
+#[doc(hidden)]
+pub mod derive;
+pub mod media;
 
 use crate::Result;
+pub use cq_code_derive::CQCode;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 
@@ -26,11 +30,16 @@ pub trait CQCode: Serialize + DeserializeOwned {
     fn to_json(&self) -> Result<String>;
 
     fn from_json(s: &str) -> Result<Self>;
+
+    /// 检查字段之间的约束，例如某些字段仅在特定`type`下必填
+    fn validate(&self) -> Result<()> {
+        Ok(())
+    }
 }
 
+#[cfg(test)]
 mod tests {
     use super::CQCode;
-    use cq_code_derive::CQCode;
     use serde::{Deserialize, Serialize};
 
     #[derive(Debug, Serialize, Deserialize, CQCode)]
@@ -39,6 +48,31 @@ mod tests {
         pub b: Option<String>,
     }
 
+    #[derive(Debug, Serialize, Deserialize, CQCode)]
+    struct U {
+        pub id: i32,
+        #[cq(default = 3)]
+        pub level: i32,
+        #[serde(rename = "type")]
+        pub type_: Option<String>,
+        #[cq(required_if(type_ = "custom"))]
+        pub url: Option<String>,
+    }
+
+    #[derive(Debug, PartialEq, CQCode)]
+    #[cq(tag = "kind")]
+    enum V {
+        #[cq(tag = "a")]
+        A { x: i32 },
+        B {
+            #[cq(tag)]
+            kind: i32,
+            #[cq(default = 5)]
+            y: i32,
+            z: Option<String>,
+        },
+    }
+
     #[test]
     fn test_to_string() {
         let t = T {
@@ -108,4 +142,91 @@ mod tests {
         assert_eq!(t.a, Some(0f64));
         assert_eq!(t.b, Some("你好".to_string()));
     }
+
+    #[test]
+    fn test_required_field_to_string() {
+        let u = U {
+            id: 1,
+            level: 2,
+            type_: None,
+            url: None,
+        };
+        assert_eq!(u.to_string().as_str(), "[CQ:u,id=1,level=2]")
+    }
+
+    #[test]
+    fn test_required_field_missing() {
+        assert!(U::from_string("[CQ:u,level=2]".to_string()).is_err());
+        assert!(U::from_json(r#"{"type":"u","data":{"level":2}}"#).is_err());
+    }
+
+    #[test]
+    fn test_default_field() {
+        let u = U::from_string("[CQ:u,id=1,type=custom,url=a]".to_string()).unwrap();
+        assert_eq!(u.id, 1);
+        assert_eq!(u.level, 3);
+        assert_eq!(u.type_, Some("custom".to_string()));
+        assert_eq!(u.url, Some("a".to_string()));
+        let u = U::from_json(r#"{"type":"u","data":{"id":1}}"#).unwrap();
+        assert_eq!(u.level, 3);
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut u = U {
+            id: 1,
+            level: 2,
+            type_: Some("custom".to_string()),
+            url: None,
+        };
+        assert!(u.validate().is_err());
+        u.url = Some("a".to_string());
+        assert!(u.validate().is_ok());
+        u.type_ = None;
+        u.url = None;
+        assert!(u.validate().is_ok());
+    }
+
+    #[test]
+    fn test_enum_to_string() {
+        assert_eq!(V::A { x: 1 }.to_string().as_str(), "[CQ:v,kind=a,x=1]");
+        let v = V::B {
+            kind: 2,
+            y: 3,
+            z: None,
+        };
+        assert_eq!(v.to_string().as_str(), "[CQ:v,kind=2,y=3]");
+    }
+
+    #[test]
+    fn test_enum_from_string() {
+        let v = V::from_string("[CQ:v,kind=a,x=1]".to_string()).unwrap();
+        assert_eq!(v, V::A { x: 1 });
+        let v = V::from_string("[CQ:v,kind=2,z=c]".to_string()).unwrap();
+        assert_eq!(
+            v,
+            V::B {
+                kind: 2,
+                y: 5,
+                z: Some("c".to_string())
+            }
+        );
+        assert!(V::from_string("[CQ:v,kind=b]".to_string()).is_err());
+        assert!(V::from_string("[CQ:v,x=1]".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_enum_json() {
+        let v = V::B {
+            kind: 2,
+            y: 3,
+            z: None,
+        };
+        let json = v.to_json().unwrap();
+        assert_eq!(json.as_str(), r#"{"type":"v","data":{"kind":2,"y":3}}"#);
+        assert_eq!(V::from_json(&json).unwrap(), v);
+        assert_eq!(serde_json::to_string(&v).unwrap(), r#"{"kind":2,"y":3}"#);
+        let v: V = serde_json::from_str(r#"{"kind":"a","x":1}"#).unwrap();
+        assert_eq!(v, V::A { x: 1 });
+    }
 }
@@ -0,0 +1,248 @@
+use crate::field::option_inner;
+use crate::implement::krate;
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Attribute, Data, DeriveInput, Fields, Ident, LitStr, Result, Type};
+
+/// `#[command(...)]`：命令（结构体或枚举变体）的名称、别名和说明
+#[derive(Default)]
+struct CommandAttrs {
+    name: Option<LitStr>,
+    aliases: Vec<LitStr>,
+    help: Option<LitStr>,
+}
+
+/// 命令的一个参数
+struct CommandArg {
+    ident: Ident,
+    ty: Type,
+    /// `#[command(name = "...")]`：帮助中显示的参数名，默认为字段名
+    name: LitStr,
+    /// `#[command(help = "...")]`：参数的说明
+    help: LitStr,
+}
+
+/// 一个命令，`path`为构造该命令的路径，即`Type`或`Type::Variant`
+struct CommandDef {
+    path: TokenStream,
+    names: Vec<LitStr>,
+    help: Option<LitStr>,
+    args: Vec<CommandArg>,
+}
+
+fn parse_command_attrs(attrs: &[Attribute]) -> Result<CommandAttrs> {
+    let mut parsed = CommandAttrs::default();
+    for attr in attrs.iter().filter(|a| a.path().is_ident("command")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("name") {
+                parsed.name = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("alias") {
+                parsed.aliases.push(meta.value()?.parse()?);
+            } else if meta.path.is_ident("help") {
+                parsed.help = Some(meta.value()?.parse()?);
+            } else {
+                return Err(
+                    meta.error("unsupported command attribute, expected `name`, `alias` or `help`")
+                );
+            }
+            Ok(())
+        })?;
+    }
+    Ok(parsed)
+}
+
+/// 类型的最后一段路径名，例如`Vec<T>`为`Vec`
+fn type_name(ty: &Type) -> Option<String> {
+    match ty {
+        Type::Path(p) => p.path.segments.last().map(|s| s.ident.to_string()),
+        _ => None,
+    }
+}
+
+fn parse_args(fields: &Fields) -> Result<Vec<CommandArg>> {
+    let named = match fields {
+        Fields::Named(n) => &n.named,
+        Fields::Unit => return Ok(Vec::new()),
+        Fields::Unnamed(_) => {
+            return Err(syn::Error::new_spanned(
+                fields,
+                "Command can only be derived for named fields",
+            ))
+        }
+    };
+    let mut args = Vec::with_capacity(named.len());
+    let mut optional = None;
+    for (i, f) in named.iter().enumerate() {
+        let ident = f.ident.clone().unwrap();
+        let mut name = LitStr::new(&ident.to_string(), ident.span());
+        let mut help = LitStr::new("", ident.span());
+        for attr in f.attrs.iter().filter(|a| a.path().is_ident("command")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("name") {
+                    name = meta.value()?.parse()?;
+                } else if meta.path.is_ident("help") {
+                    help = meta.value()?.parse()?;
+                } else {
+                    return Err(
+                        meta.error("unsupported command attribute, expected `name` or `help`")
+                    );
+                }
+                Ok(())
+            })?;
+        }
+        // 取出所有剩余参数的类型只能是最后一个参数
+        let greedy = matches!(type_name(&f.ty).as_deref(), Some("Vec" | "Rest"))
+            || option_inner(&f.ty).is_some_and(|t| type_name(&t).as_deref() == Some("Rest"));
+        if greedy && i + 1 != named.len() {
+            return Err(syn::Error::new_spanned(
+                &f.ty,
+                "an argument that takes all remaining input must be the last one",
+            ));
+        }
+        if option_inner(&f.ty).is_some() || greedy {
+            optional = Some(ident.clone());
+        } else if let Some(prev) = optional {
+            return Err(syn::Error::new_spanned(
+                &ident,
+                format!(
+                    "required argument `{}` after optional argument `{}`",
+                    ident, prev
+                ),
+            ));
+        }
+        args.push(CommandArg {
+            ty: f.ty.clone(),
+            ident,
+            name,
+            help,
+        });
+    }
+    Ok(args)
+}
+
+/// 命令的默认名称：类型名去掉`Command`后缀后转为小写，例如`BanCommand`为`ban`
+fn default_name(ident: &Ident) -> LitStr {
+    let name = ident.to_string();
+    let name = match name.strip_suffix("Command") {
+        Some(stripped) if !stripped.is_empty() => stripped,
+        _ => &name,
+    };
+    LitStr::new(&name.to_lowercase(), ident.span())
+}
+
+fn command_def(
+    path: TokenStream,
+    ident: &Ident,
+    attrs: &[Attribute],
+    fields: &Fields,
+) -> Result<CommandDef> {
+    let attrs = parse_command_attrs(attrs)?;
+    let mut names = vec![attrs.name.unwrap_or_else(|| default_name(ident))];
+    names.extend(attrs.aliases);
+    Ok(CommandDef {
+        path,
+        names,
+        help: attrs.help,
+        args: parse_args(fields)?,
+    })
+}
+
+pub fn expand(ast: &DeriveInput) -> Result<TokenStream> {
+    let ident = &ast.ident;
+    if !ast.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &ast.generics,
+            "Command cannot be derived for generic types",
+        ));
+    }
+    let defs = match &ast.data {
+        Data::Struct(s) => vec![command_def(quote!(#ident), ident, &ast.attrs, &s.fields)?],
+        Data::Enum(e) => {
+            if let Some(attr) = ast.attrs.iter().find(|a| a.path().is_ident("command")) {
+                return Err(syn::Error::new_spanned(
+                    attr,
+                    "`#[command(...)]` should be applied to each variant of an enum",
+                ));
+            }
+            e.variants
+                .iter()
+                .map(|v| {
+                    let variant = &v.ident;
+                    command_def(quote!(#ident::#variant), variant, &v.attrs, &v.fields)
+                })
+                .collect::<Result<Vec<_>>>()?
+        }
+        Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                ident,
+                "Command can only be derived for structs and enums",
+            ))
+        }
+    };
+    let mut seen: Vec<&LitStr> = Vec::new();
+    for name in defs.iter().flat_map(|d| &d.names) {
+        if seen.iter().any(|s| s.value() == name.value()) {
+            return Err(syn::Error::new_spanned(name, "duplicate command name"));
+        }
+        seen.push(name);
+    }
+
+    let krate = krate();
+    let command = quote! { #krate::command };
+    let specs = defs.iter().map(|def| {
+        let name = &def.names[0];
+        let aliases = &def.names[1..];
+        let help = def.help.iter();
+        let args = def.args.iter().map(|arg| {
+            let (ty, name, help) = (&arg.ty, &arg.name, &arg.help);
+            quote! { .arg::<#ty>(#name, #help) }
+        });
+        quote! {
+            #command::CommandSpec::new(#name)
+                #(.alias(#aliases))*
+                #(.help(#help))*
+                #(#args)*
+        }
+    });
+    // 每个命令的解析函数，以便在其中使用`?`
+    let parsers = defs.iter().enumerate().map(|(i, def)| {
+        let parser = format_ident!("parse_{}", i);
+        let path = &def.path;
+        let fields = def.args.iter().map(|arg| {
+            let (ident, name) = (&arg.ident, &arg.name);
+            quote! { #ident: args.parse(#name)? }
+        });
+        quote! {
+            #[allow(unused_variables)]
+            fn #parser(
+                args: &mut #command::Args,
+            ) -> ::std::result::Result<#ident, #command::ParseError> {
+                ::std::result::Result::Ok(#path { #(#fields,)* })
+            }
+        }
+    });
+    let arms = defs.iter().enumerate().map(|(i, def)| {
+        let parser = format_ident!("parse_{}", i);
+        let names = &def.names;
+        quote! { #(#names)|* => ::std::option::Option::Some(#parser(args)), }
+    });
+    Ok(quote! {
+        impl #command::Command for #ident {
+            fn specs() -> ::std::vec::Vec<#command::CommandSpec> {
+                ::std::vec![#(#specs),*]
+            }
+
+            fn from_args(
+                name: &str,
+                args: &mut #command::Args,
+            ) -> ::std::option::Option<::std::result::Result<Self, #command::ParseError>> {
+                #(#parsers)*
+
+                match name {
+                    #(#arms)*
+                    _ => ::std::option::Option::None,
+                }
+            }
+        }
+    })
+}
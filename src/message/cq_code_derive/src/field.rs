@@ -0,0 +1,171 @@
+use syn::{
+    Attribute, Expr, Fields, GenericArgument, Ident, LitStr, PathArguments, Result, Type, Variant,
+};
+
+/// CQ码结构体（或枚举变体）的一个字段
+pub struct CQField {
+    /// 字段名
+    pub ident: Ident,
+    /// CQ码中的字段名，去掉了用于规避关键字的`_`后缀，例如`type_`对应`type`
+    pub wire: String,
+    /// 字段类型
+    pub ty: Type,
+    /// 如果字段类型为`Option<T>`，则为`T`
+    pub inner: Option<Type>,
+    /// `#[cq(default = ...)]`：字段缺失时使用的默认值
+    pub default: Option<Expr>,
+    /// `#[cq(required_if(field = "value", ...))]`：当任一条件成立时，`Option`字段必须有值
+    pub required_if: Vec<(Ident, LitStr)>,
+    /// `#[cq(tag)]`：仅用于枚举变体，该字段的值即为标签字段的值
+    pub tag: bool,
+}
+
+/// 枚举变体的标签
+pub enum VariantTag {
+    /// `#[cq(tag = "...")]`：标签字段为固定值
+    Fixed(LitStr),
+    /// 变体中被`#[cq(tag)]`标记的字段
+    Field(Box<CQField>),
+}
+
+/// CQ码枚举的一个变体
+pub struct CQVariant {
+    /// 变体名
+    pub ident: Ident,
+    /// 变体的标签
+    pub tag: VariantTag,
+    /// 除标签字段外的字段
+    pub fields: Vec<CQField>,
+}
+
+pub fn parse_fields(fields: &Fields) -> Result<Vec<CQField>> {
+    let named = match fields {
+        Fields::Named(n) => &n.named,
+        Fields::Unit => return Ok(Vec::new()),
+        _ => {
+            return Err(syn::Error::new_spanned(
+                fields,
+                "CQCode can only be implemented for named fields",
+            ))
+        }
+    };
+    let mut fields = Vec::with_capacity(named.len());
+    for f in named {
+        let ident = f.ident.clone().unwrap();
+        let mut field = CQField {
+            wire: ident.to_string().trim_end_matches('_').to_string(),
+            ident,
+            ty: f.ty.clone(),
+            inner: option_inner(&f.ty),
+            default: None,
+            required_if: Vec::new(),
+            tag: false,
+        };
+        for attr in f.attrs.iter().filter(|a| a.path().is_ident("cq")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("default") {
+                    field.default = Some(meta.value()?.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("required_if") {
+                    meta.parse_nested_meta(|cond| {
+                        let key = cond.path.require_ident()?.clone();
+                        let value: LitStr = cond.value()?.parse()?;
+                        field.required_if.push((key, value));
+                        Ok(())
+                    })
+                } else if meta.path.is_ident("tag") {
+                    field.tag = true;
+                    Ok(())
+                } else {
+                    Err(meta.error(
+                        "unsupported cq attribute, expected `default`, `required_if` or `tag`",
+                    ))
+                }
+            })?;
+        }
+        if !field.required_if.is_empty() && field.inner.is_none() {
+            return Err(syn::Error::new_spanned(
+                &field.ident,
+                "`required_if` can only be applied to `Option` fields",
+            ));
+        }
+        fields.push(field);
+    }
+    for field in &fields {
+        for (key, _) in &field.required_if {
+            if !fields.iter().any(|f| f.ident == *key) {
+                return Err(syn::Error::new_spanned(key, "no such field in this struct"));
+            }
+        }
+    }
+    Ok(fields)
+}
+
+pub fn parse_variant(variant: &Variant) -> Result<CQVariant> {
+    let mut fields = parse_fields(&variant.fields)?;
+    for field in &fields {
+        if !field.required_if.is_empty() {
+            return Err(syn::Error::new_spanned(
+                &field.ident,
+                "`required_if` is not supported in enum variants, split them into variants instead",
+            ));
+        }
+    }
+    let fixed = parse_tag_name(&variant.attrs)?;
+    let tagged: Vec<usize> = (0..fields.len()).filter(|&i| fields[i].tag).collect();
+    let tag =
+        match (fixed, tagged.as_slice()) {
+            (Some(lit), []) => VariantTag::Fixed(lit),
+            (None, [i]) => {
+                let field = fields.remove(*i);
+                if field.inner.is_some() || field.default.is_some() {
+                    return Err(syn::Error::new_spanned(
+                        &field.ident,
+                        "the `tag` field must be required",
+                    ));
+                }
+                VariantTag::Field(Box::new(field))
+            }
+            _ => return Err(syn::Error::new_spanned(
+                variant,
+                "each variant needs exactly one of `#[cq(tag = \"...\")]` or a `#[cq(tag)]` field",
+            )),
+        };
+    Ok(CQVariant {
+        ident: variant.ident.clone(),
+        tag,
+        fields,
+    })
+}
+
+/// 解析`#[cq(tag = "...")]`，用于枚举本身（标签字段名）或枚举变体（标签值）
+pub fn parse_tag_name(attrs: &[Attribute]) -> Result<Option<LitStr>> {
+    let mut tag = None;
+    for attr in attrs.iter().filter(|a| a.path().is_ident("cq")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("tag") {
+                tag = Some(meta.value()?.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("unsupported cq attribute, expected `tag`"))
+            }
+        })?;
+    }
+    Ok(tag)
+}
+
+/// 如果`ty`为`Option<T>`，返回`T`
+pub fn option_inner(ty: &Type) -> Option<Type> {
+    let Type::Path(p) = ty else { return None };
+    let last = p.path.segments.last()?;
+    if last.ident != "Option" {
+        return None;
+    }
+    let PathArguments::AngleBracketed(args) = &last.arguments else {
+        return None;
+    };
+    match args.args.first()? {
+        GenericArgument::Type(t) => Some(t.clone()),
+        _ => None,
+    }
+}
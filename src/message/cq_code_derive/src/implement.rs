@@ -1,100 +1,182 @@
+use crate::field::CQField;
 use proc_macro2::TokenStream;
-use quote::quote;
-use syn::{Ident, Type};
+use quote::{format_ident, quote};
+use syn::{Ident, LitStr};
 
-pub fn impl_to_string(name: &Ident, fields: &Vec<Ident>) -> TokenStream {
+/// 生成代码中`rust_gocqhttp`的路径
+pub fn krate() -> TokenStream {
+    quote! { ::rust_gocqhttp }
+}
+
+/// 生成代码使用的辅助函数模块的路径
+pub fn helper() -> TokenStream {
+    let krate = krate();
+    quote! { #krate::message::cq_code::derive }
+}
+
+/// CQ码类型名，即类型名的小写
+pub fn cq_name(name: &Ident) -> LitStr {
+    LitStr::new(&name.to_string().to_lowercase(), name.span())
+}
+
+/// 从`take`（`Option<V>`）得到字段的值，`convert`将`value: V`转换为字段的（内部）类型，可以使用`?`
+pub fn extract(name: &LitStr, f: &CQField, take: TokenStream, convert: TokenStream) -> TokenStream {
+    let helper = helper();
+    let wire = &f.wire;
+    match (&f.inner, &f.default) {
+        (Some(_), None) => quote! {
+            match #take {
+                Some(value) => Some(#convert),
+                None => None,
+            }
+        },
+        (Some(_), Some(default)) => quote! {
+            match #take {
+                Some(value) => Some(#convert),
+                None => Some(#default),
+            }
+        },
+        (None, Some(default)) => quote! {
+            match #take {
+                Some(value) => #convert,
+                None => #default,
+            }
+        },
+        (None, None) => quote! {
+            match #take {
+                Some(value) => #convert,
+                None => return Err(#helper::missing_field(#name, #wire)),
+            }
+        },
+    }
+}
+
+pub fn impl_to_string(name: &LitStr, fields: &[CQField]) -> TokenStream {
+    let append = fields.iter().map(|f| {
+        let ident = &f.ident;
+        let wire = &f.wire;
+        if f.inner.is_some() {
+            quote! {
+                if let Some(ref value) = self.#ident {
+                    result += &format!(",{}={}", #wire, Self::escape(value.to_string()));
+                }
+            }
+        } else {
+            quote! {
+                result += &format!(",{}={}", #wire, Self::escape(self.#ident.to_string()));
+            }
+        }
+    });
     quote! {
         fn to_string(&self) -> String {
-            let mut result = format!("[CQ:{}", stringify!(#name).to_lowercase());
-            #({
-                let mut f_name = stringify!(#fields);
-                if f_name == "type_" {
-                    f_name = "type";
-                }
-                if let Some(ref fields) = self.#fields {
-                    result += &format!(
-                        ",{}={}",
-                        f_name,
-                        Self::escape(fields.to_string())
-                    );
-                }
-            })*
+            let mut result = format!("[CQ:{}", #name);
+            #(#append)*
             result += "]";
             result
         }
     }
 }
 
-pub fn impl_from_string(name: &Ident, fields: &Vec<Ident>, ty: &Vec<Type>) -> TokenStream {
+pub fn impl_from_string(name: &LitStr, fields: &[CQField]) -> TokenStream {
+    let krate = krate();
+    let helper = helper();
+    let temps: Vec<Ident> = fields
+        .iter()
+        .map(|f| format_ident!("__{}", f.ident))
+        .collect();
+    let declare = fields.iter().zip(&temps).map(|(f, temp)| {
+        let ty = f.inner.as_ref().unwrap_or(&f.ty);
+        quote! { let mut #temp: Option<#ty> = None; }
+    });
+    let wires = fields.iter().map(|f| &f.wire);
+    let finish = fields.iter().zip(&temps).map(|(f, temp)| {
+        let ident = &f.ident;
+        let value = extract(name, f, quote! { #temp }, quote! { value });
+        quote! { #ident: #value, }
+    });
     quote! {
-        fn from_string(s: String) -> crate::Result<Self> {
-            let re = regex::Regex::new(r"\[CQ:(?P<name>\w+)(?P<fields>(,\w+=[^,\]]+)*)\]").unwrap();
-            let caps = re.captures(&s).unwrap();
-            let name = caps.name("name").unwrap().as_str();
-            if name != stringify!(#name).to_lowercase() {
-                return Err(Box::new(std::io::Error::new(
-                    std::io::ErrorKind::InvalidData,
-                    "CQCode类型不匹配"
-                )));
-            }
-            let fields = caps.name("fields").unwrap().as_str();
-            let mut result = #name {
-                #(
-                    #fields: None,
-                )*
-            };
-            let re = regex::Regex::new(r"(?P<field>\w+)=(?P<value>[^,\]]+)")?;
-            for cap in re.captures_iter(fields) {
-                let field = cap.name("field").unwrap().as_str();
-                let value = Self::anti_escape(cap.name("value").unwrap().as_str());
-                match field {
+        fn from_string(s: String) -> #krate::error::Result<Self> {
+            #(#declare)*
+            for (field, value) in #helper::parse::<Self>(#name, &s)? {
+                match field.as_str() {
                     #(
-                        stringify!(#fields) => {
-                            let field: #ty = Some(value.parse().unwrap());
-                            result.#fields = field;
-                        }
+                        #wires => #temps = Some(#helper::parse_value(#name, &field, &value)?),
                     )*
-                    _ => return Err(Box::new(std::io::Error::new(
-                        std::io::ErrorKind::InvalidData,
-                        "CQCode字段不匹配"
-                    ))),
+                    _ => return Err(#helper::unknown_field(#name, &field)),
                 }
             }
-            Ok(result)
+            Ok(Self {
+                #(#finish)*
+            })
         }
     }
 }
 
-pub fn impl_to_json(name: &Ident) -> TokenStream {
+pub fn impl_to_json(name: &LitStr) -> TokenStream {
+    let krate = krate();
+    let helper = helper();
     quote! {
-        fn to_json(&self) -> crate::Result<String> {
-            let data = serde_json::to_string(self)?;
-            let re = regex::Regex::new(r#","[^"]+":null"#)?;
-            let data = re.replace_all(&data, "");
-            let re = regex::Regex::new(r#""[^"]+":null"#)?;
-            let data = re.replace_all(&data, "");
-            Ok(format!("{{\"type\":\"{}\",\"data\":{}}}", stringify!(#name).to_lowercase(), data))
+        fn to_json(&self) -> #krate::error::Result<String> {
+            #helper::to_json(#name, self)
         }
     }
 }
 
-pub fn impl_from_json(name: &Ident) -> TokenStream {
+pub fn impl_from_json(name: &LitStr, fields: &[CQField]) -> TokenStream {
+    let krate = krate();
+    let helper = helper();
+    let defaults = fields.iter().filter_map(|f| {
+        let wire = &f.wire;
+        f.default.as_ref().map(|default| {
+            quote! {
+                if !data.contains_key(#wire) {
+                    data.insert(#wire.to_string(), #helper::serde_json::to_value(#default)?);
+                }
+            }
+        })
+    });
     quote! {
-        fn from_json(s: &str) -> crate::Result<Self> {
-            let v: serde_json::Value = serde_json::from_str(s)?;
-            let name = v.get("type").ok_or_else(|| {
-                std::io::Error::new(std::io::ErrorKind::InvalidData, "没有找到type字段")
-            })?;
-            if name.as_str().unwrap() != stringify!(#name).to_lowercase() {
-                return Err(Box::new(std::io::Error::new(
-                    std::io::ErrorKind::InvalidData,
-                    "CQCode类型不匹配",
-                )));
+        fn from_json(s: &str) -> #krate::error::Result<Self> {
+            let mut data = #helper::json_data(#name, s)?;
+            #(#defaults)*
+            #helper::from_data(#name, data)
+        }
+    }
+}
+
+pub fn impl_validate(name: &LitStr, fields: &[CQField]) -> TokenStream {
+    let krate = krate();
+    let checks = fields.iter().flat_map(|f| {
+        let ident = &f.ident;
+        let wire = &f.wire;
+        f.required_if.iter().map(move |(key, value)| {
+            let key_field = fields.iter().find(|k| k.ident == *key).unwrap();
+            let key_wire = &key_field.wire;
+            let matched = if key_field.inner.is_some() {
+                quote! { self.#key.as_ref().map(|v| v.to_string()).as_deref() == Some(#value) }
+            } else {
+                quote! { self.#key.to_string().as_str() == #value }
+            };
+            quote! {
+                if #matched && self.#ident.is_none() {
+                    return Err(Box::new(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!(
+                            "CQ码`{}`的字段`{}`在`{}={}`时必须有值",
+                            #name,
+                            #wire,
+                            #key_wire,
+                            #value,
+                        ),
+                    )));
+                }
             }
-            let data = v.get("data").ok_or_else(|| {
-                std::io::Error::new(std::io::ErrorKind::InvalidData, "没有找到data字段")
-            })?;
-            Ok(serde_json::from_value(data.to_owned())?)
+        })
+    });
+    quote! {
+        fn validate(&self) -> #krate::error::Result<()> {
+            #(#checks)*
+            Ok(())
         }
     }
 }
@@ -0,0 +1,296 @@
+use crate::field::{CQField, CQVariant, VariantTag};
+use crate::implement::{extract, helper, krate};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Ident, LitStr};
+
+/// 变体的匹配模式，字段绑定为`__<字段名>`，以免与生成代码中的局部变量冲突
+fn pattern(v: &CQVariant) -> TokenStream {
+    let ident = &v.ident;
+    let fields = v.fields.iter().chain(tag_field(v)).map(|f| {
+        let name = &f.ident;
+        let bind = format_ident!("__{}", f.ident);
+        quote! { #name: #bind }
+    });
+    quote! { Self::#ident { #(#fields),* } }
+}
+
+fn tag_field(v: &CQVariant) -> Option<&CQField> {
+    match &v.tag {
+        VariantTag::Fixed(_) => None,
+        VariantTag::Field(f) => Some(f),
+    }
+}
+
+pub fn impl_to_string(name: &LitStr, tag: &LitStr, variants: &[CQVariant]) -> TokenStream {
+    let arms = variants.iter().map(|v| {
+        let pattern = pattern(v);
+        let tag_value = match &v.tag {
+            VariantTag::Fixed(lit) => quote! { #lit.to_string() },
+            VariantTag::Field(f) => {
+                let bind = format_ident!("__{}", f.ident);
+                quote! { #bind.to_string() }
+            }
+        };
+        let append = v.fields.iter().map(|f| {
+            let wire = &f.wire;
+            let bind = format_ident!("__{}", f.ident);
+            if f.inner.is_some() {
+                quote! {
+                    if let Some(value) = #bind {
+                        result += &format!(",{}={}", #wire, Self::escape(value.to_string()));
+                    }
+                }
+            } else {
+                quote! {
+                    result += &format!(",{}={}", #wire, Self::escape(#bind.to_string()));
+                }
+            }
+        });
+        quote! {
+            #pattern => {
+                result += &format!(",{}={}", #tag, Self::escape(#tag_value));
+                #(#append)*
+            }
+        }
+    });
+    quote! {
+        fn to_string(&self) -> String {
+            let mut result = format!("[CQ:{}", #name);
+            match self {
+                #(#arms)*
+            }
+            result += "]";
+            result
+        }
+    }
+}
+
+/// 生成根据标签选择变体的代码，`data`为字段名到值的映射，`tag`为标签值
+///
+/// `field_value`生成从`data`中取出字段值并转换的代码，`tag_matches`和`tag_parse`分别生成固定标签和标签字段的判断代码，
+/// `tag_display`生成标签值的字符串表示，用于错误信息
+fn select_variant(
+    name: &LitStr,
+    tag: &LitStr,
+    variants: &[CQVariant],
+    field_value: impl Fn(&CQField) -> TokenStream,
+    tag_matches: impl Fn(&LitStr) -> TokenStream,
+    tag_parse: impl Fn(&CQField) -> TokenStream,
+    tag_display: TokenStream,
+) -> TokenStream {
+    let helper = helper();
+    let build = |v: &CQVariant, tag_field: TokenStream| {
+        let ident = &v.ident;
+        let fields = v.fields.iter().map(|f| {
+            let field = &f.ident;
+            let value = field_value(f);
+            quote! { #field: #value, }
+        });
+        quote! {
+            let result = Self::#ident { #(#fields)* #tag_field };
+            if let Some((field, _)) = data.into_iter().next() {
+                return Err(#helper::unknown_field(#name, &field));
+            }
+            return Ok(result);
+        }
+    };
+    let fixed = variants.iter().filter_map(|v| match &v.tag {
+        VariantTag::Fixed(lit) => {
+            let matches = tag_matches(lit);
+            let build = build(v, quote! {});
+            Some(quote! {
+                if #matches {
+                    #build
+                }
+            })
+        }
+        VariantTag::Field(_) => None,
+    });
+    let parsed = variants.iter().filter_map(|v| match &v.tag {
+        VariantTag::Fixed(_) => None,
+        VariantTag::Field(f) => {
+            let parse = tag_parse(f);
+            let field = &f.ident;
+            let build = build(v, quote! { #field: tag_value, });
+            Some(quote! {
+                if let Ok(tag_value) = #parse {
+                    #build
+                }
+            })
+        }
+    });
+    quote! {
+        #(#fixed)*
+        #(#parsed)*
+        Err(#helper::unknown_tag(#name, #tag, &#tag_display))
+    }
+}
+
+pub fn impl_from_string(name: &LitStr, tag: &LitStr, variants: &[CQVariant]) -> TokenStream {
+    let krate = krate();
+    let helper = helper();
+    let select = select_variant(
+        name,
+        tag,
+        variants,
+        |f| {
+            let wire = &f.wire;
+            extract(
+                name,
+                f,
+                quote! { data.remove(#wire) },
+                quote! { #helper::parse_value(#name, #wire, &value)? },
+            )
+        },
+        |lit| quote! { tag == #lit },
+        |f| {
+            let ty = &f.ty;
+            quote! { tag.parse::<#ty>() }
+        },
+        quote! { tag },
+    );
+    quote! {
+        fn from_string(s: String) -> #krate::error::Result<Self> {
+            let mut data: std::collections::HashMap<String, String> =
+                #helper::parse::<Self>(#name, &s)?.into_iter().collect();
+            let tag = data
+                .remove(#tag)
+                .ok_or_else(|| #helper::missing_field(#name, #tag))?;
+            #select
+        }
+    }
+}
+
+pub fn impl_to_json(name: &LitStr) -> TokenStream {
+    let krate = krate();
+    quote! {
+        fn to_json(&self) -> #krate::error::Result<String> {
+            let data: Vec<String> = self
+                .__cq_to_data()?
+                .into_iter()
+                .map(|(k, v)| format!("\"{}\":{}", k, v))
+                .collect();
+            Ok(format!("{{\"type\":\"{}\",\"data\":{{{}}}}}", #name, data.join(",")))
+        }
+    }
+}
+
+pub fn impl_from_json(name: &LitStr) -> TokenStream {
+    let krate = krate();
+    let helper = helper();
+    quote! {
+        fn from_json(s: &str) -> #krate::error::Result<Self> {
+            Self::__cq_from_data(#helper::json_data(#name, s)?)
+        }
+    }
+}
+
+/// 生成枚举与数组格式消息段`data`之间的转换函数，以及基于它们的`Serialize`, `Deserialize`实现
+pub fn impl_data(
+    ident: &Ident,
+    name: &LitStr,
+    tag: &LitStr,
+    variants: &[CQVariant],
+) -> TokenStream {
+    let krate = krate();
+    let helper = helper();
+    let to_arms = variants.iter().map(|v| {
+        let pattern = pattern(v);
+        let tag_value = match &v.tag {
+            VariantTag::Fixed(lit) => {
+                quote! { #helper::serde_json::Value::String(#lit.to_string()) }
+            }
+            VariantTag::Field(f) => {
+                let bind = format_ident!("__{}", f.ident);
+                quote! { #helper::serde_json::to_value(#bind)? }
+            }
+        };
+        let push = v.fields.iter().map(|f| {
+            let wire = &f.wire;
+            let bind = format_ident!("__{}", f.ident);
+            quote! {
+                let value = #helper::serde_json::to_value(#bind)?;
+                if !value.is_null() {
+                    data.push((#wire, value));
+                }
+            }
+        });
+        quote! {
+            #pattern => {
+                data.push((#tag, #tag_value));
+                #(#push)*
+            }
+        }
+    });
+    let select = select_variant(
+        name,
+        tag,
+        variants,
+        |f| {
+            let wire = &f.wire;
+            extract(
+                name,
+                f,
+                quote! { data.remove(#wire) },
+                quote! { #helper::json_value(#name, #wire, value)? },
+            )
+        },
+        |lit| quote! { tag.as_str() == Some(#lit) },
+        |f| {
+            let ty = &f.ty;
+            quote! { #helper::serde_json::from_value::<#ty>(tag.clone()) }
+        },
+        quote! { tag.to_string() },
+    );
+    quote! {
+        impl #ident {
+            #[doc(hidden)]
+            fn __cq_to_data(
+                &self,
+            ) -> #krate::error::Result<Vec<(&'static str, #helper::serde_json::Value)>> {
+                let mut data = Vec::new();
+                match self {
+                    #(#to_arms)*
+                }
+                Ok(data)
+            }
+
+            #[doc(hidden)]
+            fn __cq_from_data(
+                mut data: #helper::serde_json::Map<String, #helper::serde_json::Value>,
+            ) -> #krate::error::Result<Self> {
+                let tag = data
+                    .remove(#tag)
+                    .ok_or_else(|| #helper::missing_field(#name, #tag))?;
+                #select
+            }
+        }
+
+        impl #helper::serde::Serialize for #ident {
+            fn serialize<S>(&self, serializer: S) -> ::core::result::Result<S::Ok, S::Error>
+            where
+                S: #helper::serde::Serializer,
+            {
+                use #helper::serde::ser::{Error, SerializeMap};
+                let data = self.__cq_to_data().map_err(S::Error::custom)?;
+                let mut map = serializer.serialize_map(Some(data.len()))?;
+                for (k, v) in &data {
+                    map.serialize_entry(k, v)?;
+                }
+                map.end()
+            }
+        }
+
+        impl<'de> #helper::serde::Deserialize<'de> for #ident {
+            fn deserialize<D>(deserializer: D) -> ::core::result::Result<Self, D::Error>
+            where
+                D: #helper::serde::Deserializer<'de>,
+            {
+                use #helper::serde::de::Error;
+                let data = #helper::serde_json::Map::deserialize(deserializer)?;
+                Self::__cq_from_data(data).map_err(D::Error::custom)
+            }
+        }
+    }
+}
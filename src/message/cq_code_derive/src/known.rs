@@ -0,0 +1,230 @@
+//! `cq!`宏已知的CQ码，需与`rust_gocqhttp::message::cq_code::code`保持一致
+//!
+//! 过程宏无法获取类型定义，所以这里单独记录每种CQ码对应的类型和字段，`__known_codes!`展开的测试用例检查二者是否一致
+
+/// 字段值的种类，决定模板中的字面值如何在编译期检查并生成代码
+pub enum Kind {
+    /// 整数，包括`BoolInCQCode`
+    Int,
+    /// 浮点数
+    Float,
+    /// 字符串
+    Str,
+    /// `MediaSource`，字面值按go-cqhttp的格式解析
+    Media,
+    /// `code`模块中的枚举，依次为类型名和`(CQ码中的值, 变体名)`
+    Enum(&'static str, &'static [(&'static str, &'static str)]),
+}
+
+pub struct FieldSpec {
+    /// CQ码中的字段名
+    pub wire: &'static str,
+    /// 结构体中的字段名
+    pub ident: &'static str,
+    pub kind: Kind,
+    /// 字段类型是否为`Option`
+    pub optional: bool,
+    /// 必填字段缺失时使用的默认值，与`#[cq(default = ...)]`一致
+    pub default: Option<&'static str>,
+}
+
+pub struct CodeSpec {
+    /// CQ码类型名
+    pub name: &'static str,
+    /// `code`模块中的类型（或枚举变体）路径
+    pub path: &'static str,
+    /// 由变体决定的固定字段，例如`Music::Custom`的`type=custom`
+    pub fixed: Option<(&'static str, &'static str)>,
+    pub fields: &'static [FieldSpec],
+}
+
+const fn field(wire: &'static str, ident: &'static str, kind: Kind, optional: bool) -> FieldSpec {
+    FieldSpec {
+        wire,
+        ident,
+        kind,
+        optional,
+        default: None,
+    }
+}
+
+const fn required(wire: &'static str, kind: Kind) -> FieldSpec {
+    field(wire, wire, kind, false)
+}
+
+const fn optional(wire: &'static str, kind: Kind) -> FieldSpec {
+    field(wire, wire, kind, true)
+}
+
+const fn code(name: &'static str, path: &'static str, fields: &'static [FieldSpec]) -> CodeSpec {
+    CodeSpec {
+        name,
+        path,
+        fixed: None,
+        fields,
+    }
+}
+
+const IMAGE_TYPE: Kind = Kind::Enum("ImageType", &[("flash", "Flash"), ("show", "Show")]);
+const MUSIC_PLATFORM: Kind = Kind::Enum(
+    "MusicPlatform",
+    &[("qq", "QQ"), ("163", "NetEase"), ("xm", "Xiami")],
+);
+
+pub static CODES: &[CodeSpec] = &[
+    code("face", "Face", &[required("id", Kind::Int)]),
+    code(
+        "record",
+        "Record",
+        &[
+            required("file", Kind::Media),
+            optional("magic", Kind::Int),
+            optional("url", Kind::Str),
+            optional("cache", Kind::Int),
+            optional("proxy", Kind::Int),
+            optional("timeout", Kind::Int),
+        ],
+    ),
+    code(
+        "video",
+        "Video",
+        &[
+            required("file", Kind::Media),
+            optional("cover", Kind::Media),
+            optional("c", Kind::Int),
+        ],
+    ),
+    code(
+        "at",
+        "At",
+        &[required("qq", Kind::Str), optional("name", Kind::Str)],
+    ),
+    code("rps", "Rps", &[]),
+    code("dice", "Dice", &[]),
+    code("shake", "Shake", &[]),
+    code("anonymous", "Anonymous", &[optional("ignore", Kind::Int)]),
+    code(
+        "share",
+        "Share",
+        &[
+            required("url", Kind::Str),
+            required("title", Kind::Str),
+            optional("content", Kind::Str),
+            optional("image", Kind::Str),
+        ],
+    ),
+    code(
+        "contact",
+        "Contact",
+        &[
+            field("type", "type_", Kind::Str, false),
+            required("id", Kind::Str),
+        ],
+    ),
+    code(
+        "location",
+        "Location",
+        &[
+            required("lon", Kind::Float),
+            required("lat", Kind::Float),
+            optional("title", Kind::Str),
+            optional("content", Kind::Str),
+        ],
+    ),
+    CodeSpec {
+        name: "music",
+        path: "Music::Custom",
+        fixed: Some(("type", "custom")),
+        fields: &[
+            required("url", Kind::Str),
+            required("audio", Kind::Str),
+            required("title", Kind::Str),
+            optional("content", Kind::Str),
+            optional("image", Kind::Str),
+        ],
+    },
+    code(
+        "music",
+        "Music::Platform",
+        &[
+            field("type", "kind", MUSIC_PLATFORM, false),
+            required("id", Kind::Str),
+        ],
+    ),
+    code(
+        "image",
+        "Image",
+        &[
+            required("file", Kind::Media),
+            field("type", "type_", IMAGE_TYPE, true),
+            optional("subType", Kind::Str),
+            optional("url", Kind::Str),
+            optional("cache", Kind::Int),
+            optional("id", Kind::Int),
+            optional("c", Kind::Int),
+        ],
+    ),
+    code(
+        "reply",
+        "Reply",
+        &[
+            optional("id", Kind::Int),
+            optional("text", Kind::Str),
+            optional("qq", Kind::Int),
+            optional("time", Kind::Int),
+            optional("seq", Kind::Int),
+        ],
+    ),
+    code("redbag", "RedBag", &[required("title", Kind::Str)]),
+    code("poke", "Poke", &[required("qq", Kind::Int)]),
+    code(
+        "gift",
+        "Gift",
+        &[required("qq", Kind::Int), required("id", Kind::Int)],
+    ),
+    code("forward", "Forward", &[required("id", Kind::Int)]),
+    code(
+        "xml",
+        "Xml",
+        &[required("data", Kind::Str), optional("resid", Kind::Int)],
+    ),
+    code(
+        "json",
+        "Json",
+        &[
+            required("data", Kind::Str),
+            FieldSpec {
+                default: Some("0"),
+                ..required("resid", Kind::Int)
+            },
+        ],
+    ),
+    code(
+        "cardimage",
+        "CardImage",
+        &[
+            required("file", Kind::Media),
+            optional("minwidth", Kind::Int),
+            optional("minheight", Kind::Int),
+            optional("maxwidth", Kind::Int),
+            optional("maxheight", Kind::Int),
+            optional("source", Kind::Str),
+            optional("icon", Kind::Str),
+        ],
+    ),
+    code("tts", "Tts", &[required("text", Kind::Str)]),
+];
+
+/// 查找CQ码类型`name`对应的类型，`fixed`返回某个字段在模板中的字面值，用于区分变体
+pub fn lookup<'a>(
+    name: &str,
+    fixed: impl Fn(&str) -> Option<&'a str>,
+) -> Option<&'static CodeSpec> {
+    CODES.iter().find(|c| {
+        c.name == name
+            && match c.fixed {
+                Some((wire, value)) => fixed(wire) == Some(value),
+                None => true,
+            }
+    })
+}
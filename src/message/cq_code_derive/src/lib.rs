@@ -1,46 +1,96 @@
+mod command;
+mod field;
 mod implement;
+mod implement_enum;
+mod known;
+mod template;
 
-use implement::{impl_from_json, impl_from_string, impl_to_json, impl_to_string};
+use field::{parse_fields, parse_tag_name, parse_variant, CQVariant};
+use implement::{
+    cq_name, impl_from_json, impl_from_string, impl_to_json, impl_to_string, impl_validate, krate,
+};
+use proc_macro2::{Span, TokenStream};
 use quote::quote;
-use syn::{parse_macro_input, Data, DeriveInput, Field, Fields, Ident, Type};
+use syn::{parse_macro_input, Data, DataEnum, DataStruct, DeriveInput, Ident, LitStr, Result};
 
-#[proc_macro_derive(CQCode)]
+/// 为结构体或枚举实现`CQCode`
+///
+/// 生成的代码通过`::rust_gocqhttp`引用所需的类型，解析失败时返回错误而不会panic
+///
+/// 结构体：
+/// - `Option<T>`类型的字段是可选字段，其余字段是必填字段，解析时缺失必填字段会返回错误
+/// - `#[cq(default = expr)]`：字段缺失时使用`expr`作为值，`Option`字段的`expr`为其内部类型的值
+/// - `#[cq(required_if(field = "value", ...))]`：仅用于`Option`字段，
+///   当任一`field`的值等于`value`时，`validate`要求该字段有值
+/// - 字段名末尾的`_`会在CQ码中被去掉，例如`type_`对应`type`
+///
+/// 枚举：用一个标签字段区分变体，变体的字段规则同结构体
+/// - `#[cq(tag = "...")]`：标在枚举上时指定标签字段名，默认为`type`
+/// - `#[cq(tag = "...")]`：标在变体上时指定该变体的标签值
+/// - `#[cq(tag)]`：标在变体的字段上时，该字段的值即为标签值，字段类型需实现`FromStr`和`Display`，
+///   解析时先匹配固定标签值的变体，再依次尝试将标签值解析为各变体的标签字段
+/// - 枚举的`Serialize`, `Deserialize`由本宏一并生成，其格式为数组格式消息段的`data`
+#[proc_macro_derive(CQCode, attributes(cq))]
 pub fn cq_code_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let ast = parse_macro_input!(input as DeriveInput);
-    impl_cq_code(&ast)
+    let result = match &ast.data {
+        Data::Struct(s) => impl_cq_code_struct(&ast.ident, s),
+        Data::Enum(e) => impl_cq_code_enum(&ast, e),
+        Data::Union(_) => Err(syn::Error::new_spanned(
+            &ast.ident,
+            "CQCode can only be implemented for structs and enums",
+        )),
+    };
+    result.unwrap_or_else(|e| e.to_compile_error()).into()
 }
 
-fn mapping_input<T>(ast: &DeriveInput, f: impl FnMut(&Field) -> T) -> Vec<T> {
-    let data = &ast.data;
-    if let Data::Struct(s) = data {
-        if let Fields::Named(n) = &s.fields {
-            Vec::from_iter(n.named.iter().map(f))
-        } else {
-            panic!("CQCode can only be implemented for structs with named fields")
-        }
-    } else {
-        panic!("CQCode can only be implemented for structs")
+fn impl_cq_code_struct(ident: &Ident, s: &DataStruct) -> Result<TokenStream> {
+    let name = &cq_name(ident);
+    let krate = krate();
+    let fields = parse_fields(&s.fields)?;
+    if let Some(f) = fields.iter().find(|f| f.tag) {
+        return Err(syn::Error::new_spanned(
+            &f.ident,
+            "`#[cq(tag)]` can only be used in enum variants",
+        ));
     }
-}
+    let fn_to_string = impl_to_string(name, &fields);
+    let fn_from_string = impl_from_string(name, &fields);
+    let fn_to_json = impl_to_json(name);
+    let fn_from_json = impl_from_json(name, &fields);
+    let fn_validate = impl_validate(name, &fields);
+    Ok(quote! {
+        impl #krate::message::cq_code::CQCode for #ident {
+            #fn_to_string
 
-fn get_field(ast: &DeriveInput) -> Vec<Ident> {
-    mapping_input(ast, |f| f.ident.clone().unwrap())
-}
+            #fn_from_string
+
+            #fn_to_json
+
+            #fn_from_json
 
-fn get_type(ast: &DeriveInput) -> Vec<Type> {
-    mapping_input(ast, |f| f.ty.clone())
+            #fn_validate
+        }
+    })
 }
 
-fn impl_cq_code(ast: &DeriveInput) -> proc_macro::TokenStream {
-    let name = &ast.ident;
-    let ty = get_type(ast);
-    let fields = get_field(ast);
-    let fn_to_string = impl_to_string(name, &fields);
-    let fn_from_string = impl_from_string(name, &fields, &ty);
-    let fn_to_json = impl_to_json(name);
-    let fn_from_json = impl_from_json(name);
-    let gen = quote! {
-        impl CQCode for #name {
+fn impl_cq_code_enum(ast: &DeriveInput, e: &DataEnum) -> Result<TokenStream> {
+    let ident = &ast.ident;
+    let name = &cq_name(ident);
+    let krate = krate();
+    let tag = parse_tag_name(&ast.attrs)?.unwrap_or_else(|| LitStr::new("type", Span::call_site()));
+    let variants = e
+        .variants
+        .iter()
+        .map(parse_variant)
+        .collect::<Result<Vec<CQVariant>>>()?;
+    let fn_to_string = implement_enum::impl_to_string(name, &tag, &variants);
+    let fn_from_string = implement_enum::impl_from_string(name, &tag, &variants);
+    let fn_to_json = implement_enum::impl_to_json(name);
+    let fn_from_json = implement_enum::impl_from_json(name);
+    let data = implement_enum::impl_data(ident, name, &tag, &variants);
+    Ok(quote! {
+        impl #krate::message::cq_code::CQCode for #ident {
             #fn_to_string
 
             #fn_from_string
@@ -49,6 +99,60 @@ fn impl_cq_code(ast: &DeriveInput) -> proc_macro::TokenStream {
 
             #fn_from_json
         }
-    };
-    gen.into()
+
+        #data
+    })
+}
+
+/// 为结构体或枚举实现`command::Command`，结构体为一个命令，枚举的每个变体为一个命令
+///
+/// ```ignore
+/// #[derive(Command)]
+/// #[command(alias = "禁言", help = "禁言群成员")]
+/// struct BanCommand {
+///     #[command(help = "被禁言的成员")]
+///     target: At,
+///     duration: Duration,
+///     reason: Option<String>,
+/// }
+/// ```
+///
+/// - 每个字段为一个参数，按字段顺序解析，字段类型需实现`command::Arg`
+/// - `Option<T>`, `Vec<T>`, `Rest`类型的参数可以省略，其后不能有必填参数；`Vec<T>`和`Rest`会取出所有剩余参数，只能是最后一个参数
+/// - 结构体或变体上的`#[command(name = "...")]`：命令名，默认为类型名（去掉`Command`后缀）或变体名的小写
+/// - 结构体或变体上的`#[command(alias = "...")]`：别名，可以有多个；`#[command(help = "...")]`：命令的说明
+/// - 字段上的`#[command(name = "...")]`：帮助中的参数名，默认为字段名；`#[command(help = "...")]`：参数的说明
+#[proc_macro_derive(Command, attributes(command))]
+pub fn command_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+    command::expand(&ast)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+/// 在编译期解析消息模板，构造字符串格式的`Message`
+///
+/// ```ignore
+/// let message = cq!("你好[CQ:at,qq={}] [CQ:face,id=14]", uid);
+/// ```
+///
+/// - CQ码的类型和字段在编译期检查，未知的类型、字段，缺失的必填字段，以及无法解析的字面值都会导致编译错误
+/// - `{}`依次替换为参数：可以出现在文本中，或作为CQ码字段的完整值，`{{`, `}}`表示花括号
+/// - 文本中的参数通过`ToString`转换，字符串字段的参数同样通过`ToString`转换，其余字段的参数需为字段的类型
+/// - 模板中的文本和参数的值都会被转义，字段的字面值可以使用`&#44;`等转义序列
+#[proc_macro]
+pub fn cq(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as template::Template);
+    template::expand(input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+/// 展开为所有`cq!`已知的CQ码的示例模板，仅用于检查`cq!`记录的字段与CQ码类型一致
+#[doc(hidden)]
+#[proc_macro]
+pub fn __known_codes(_input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    template::known_codes()
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
 }
@@ -0,0 +1,382 @@
+//! `cq!`宏：在编译期解析消息模板，展开为构造`Message`的代码
+
+use crate::implement::{helper, krate};
+use crate::known::{lookup, CodeSpec, FieldSpec, Kind, CODES};
+use proc_macro2::{Literal, Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{Expr, LitStr, Result, Token};
+
+/// `cq!`的输入：模板字符串和依次替换`{}`的参数
+pub struct Template {
+    template: LitStr,
+    args: Vec<Expr>,
+}
+
+impl Parse for Template {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let template = input.parse()?;
+        let mut args = Vec::new();
+        if !input.is_empty() {
+            input.parse::<Token![,]>()?;
+            args = Punctuated::<Expr, Token![,]>::parse_terminated(input)?
+                .into_iter()
+                .collect();
+        }
+        Ok(Self { template, args })
+    }
+}
+
+/// 模板中的一个值
+enum Value {
+    /// 字面值，已反转义
+    Literal(String),
+    /// `{}`，对应第几个参数
+    Arg(usize),
+}
+
+/// 模板中的一个消息段
+enum Segment {
+    Text(Vec<Value>),
+    Code(String, Vec<(String, Value)>),
+}
+
+fn anti_escape(s: &str) -> String {
+    s.replace("&#44;", ",")
+        .replace("&#93;", "]")
+        .replace("&#91;", "[")
+        .replace("&amp;", "&")
+}
+
+/// 与`rust_gocqhttp::message::cq_code::derive::escape_text`一致
+fn escape_text(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('[', "&#91;")
+        .replace(']', "&#93;")
+}
+
+/// 将模板拆分为消息段，错误信息为字符串，由调用者附上模板的位置
+fn split(template: &str) -> std::result::Result<(Vec<Segment>, usize), String> {
+    let mut segments = Vec::new();
+    let mut text = Vec::new();
+    let mut literal = String::new();
+    let mut args = 0;
+    let mut rest = template;
+    while let Some(c) = rest.chars().next() {
+        if let Some(after) = rest.strip_prefix("[CQ:") {
+            let end = after
+                .find(']')
+                .ok_or_else(|| format!("unclosed CQ code `{}`", rest))?;
+            if !literal.is_empty() {
+                text.push(Value::Literal(std::mem::take(&mut literal)));
+            }
+            if !text.is_empty() {
+                segments.push(Segment::Text(std::mem::take(&mut text)));
+            }
+            segments.push(parse_code(&after[..end], &mut args)?);
+            rest = &after[end + 1..];
+            continue;
+        }
+        if let Some(after) = rest.strip_prefix("{}") {
+            if !literal.is_empty() {
+                text.push(Value::Literal(std::mem::take(&mut literal)));
+            }
+            text.push(Value::Arg(args));
+            args += 1;
+            rest = after;
+            continue;
+        }
+        if let Some(after) = rest.strip_prefix("{{").or_else(|| rest.strip_prefix("}}")) {
+            literal.push(c);
+            rest = after;
+            continue;
+        }
+        if c == '{' || c == '}' {
+            return Err(format!(
+                "invalid `{}` in template, use `{{}}` for an argument or `{{{{`, `}}}}` for braces",
+                c
+            ));
+        }
+        literal.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    if !literal.is_empty() {
+        text.push(Value::Literal(literal));
+    }
+    if !text.is_empty() {
+        segments.push(Segment::Text(text));
+    }
+    Ok((segments, args))
+}
+
+/// 解析`[CQ:`与`]`之间的部分
+fn parse_code(body: &str, args: &mut usize) -> std::result::Result<Segment, String> {
+    let mut parts = body.split(',');
+    let name = parts.next().unwrap_or_default();
+    if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        return Err(format!("invalid CQ code name `{}`", name));
+    }
+    let mut fields: Vec<(String, Value)> = Vec::new();
+    for part in parts {
+        let (field, value) = part.split_once('=').ok_or_else(|| {
+            format!(
+                "expected `field=value` in CQ code `{}`, found `{}`",
+                name, part
+            )
+        })?;
+        if fields.iter().any(|(f, _)| f == field) {
+            return Err(format!("duplicate field `{}` in CQ code `{}`", field, name));
+        }
+        let value = if value == "{}" {
+            *args += 1;
+            Value::Arg(*args - 1)
+        } else if value.contains(['{', '}']) {
+            return Err(format!(
+                "the value of field `{}` in CQ code `{}` must be either a literal or exactly `{{}}`",
+                field, name
+            ));
+        } else if value.is_empty() {
+            return Err(format!(
+                "empty value of field `{}` in CQ code `{}`",
+                field, name
+            ));
+        } else {
+            Value::Literal(anti_escape(value))
+        };
+        fields.push((field.to_string(), value));
+    }
+    Ok(Segment::Code(name.to_string(), fields))
+}
+
+pub fn expand(input: Template) -> Result<TokenStream> {
+    let krate = krate();
+    let helper = helper();
+    let span = input.template.span();
+    let error = |msg: String| syn::Error::new(span, msg);
+    let (segments, count) = split(&input.template.value()).map_err(error)?;
+    if count != input.args.len() {
+        return Err(error(format!(
+            "the template has {} `{{}}` but {} arguments were given",
+            count,
+            input.args.len()
+        )));
+    }
+    let args: Vec<_> = input.args.iter().map(|arg| quote! { (#arg) }).collect();
+    let mut pushes = Vec::with_capacity(segments.len());
+    for segment in &segments {
+        let value = match segment {
+            Segment::Text(values) => {
+                let parts = values.iter().map(|v| match v {
+                    Value::Literal(s) => {
+                        let s = escape_text(s);
+                        quote! { text.push_str(#s); }
+                    }
+                    Value::Arg(i) => {
+                        let arg = &args[*i];
+                        quote! {
+                            text.push_str(&#helper::escape_text(
+                                &::std::string::ToString::to_string(&#arg),
+                            ));
+                        }
+                    }
+                });
+                quote! {
+                    {
+                        let mut text = ::std::string::String::new();
+                        #(#parts)*
+                        text
+                    }
+                }
+            }
+            Segment::Code(name, fields) => {
+                let value = |wire: &str| {
+                    fields.iter().find_map(|(f, v)| match v {
+                        Value::Literal(s) if f == wire => Some(s.as_str()),
+                        _ => None,
+                    })
+                };
+                let spec = lookup(name, value)
+                    .ok_or_else(|| error(format!("unknown CQ code `{}`", name)))?;
+                let code = construct(spec, fields, &args).map_err(error)?;
+                quote! { #krate::message::cq_code::CQCode::to_string(&#code) }
+            }
+        };
+        pushes.push(quote! { messages.push(#value); });
+    }
+    if pushes.is_empty() {
+        // 与`Message::from_string("")`一致
+        pushes.push(quote! { messages.push(::std::string::String::new()); });
+    }
+    Ok(quote! {
+        {
+            let mut messages: ::std::vec::Vec<::std::string::String> = ::std::vec::Vec::new();
+            #(#pushes)*
+            #krate::message::Message { messages }
+        }
+    })
+}
+
+/// 生成CQ码类型的结构体表达式
+fn construct(
+    spec: &CodeSpec,
+    fields: &[(String, Value)],
+    args: &[TokenStream],
+) -> std::result::Result<TokenStream, String> {
+    for (field, _) in fields {
+        let known = spec.fields.iter().any(|f| f.wire == field)
+            || spec.fixed.map(|(wire, _)| wire) == Some(field.as_str());
+        if !known {
+            return Err(format!("CQ code `{}` has no field `{}`", spec.name, field));
+        }
+    }
+    let mut inits = Vec::with_capacity(spec.fields.len());
+    for f in spec.fields {
+        let ident = format_ident!("{}", f.ident);
+        let value = match fields.iter().find(|(wire, _)| wire == f.wire) {
+            Some((_, value)) => {
+                let value = field_value(spec, f, value, args)?;
+                if f.optional {
+                    quote! { ::core::option::Option::Some(#value) }
+                } else {
+                    value
+                }
+            }
+            None if f.optional => quote! { ::core::option::Option::None },
+            None => match f.default {
+                Some(default) => default.parse().unwrap(),
+                None => {
+                    return Err(format!(
+                        "CQ code `{}` requires field `{}`",
+                        spec.name, f.wire
+                    ))
+                }
+            },
+        };
+        inits.push(quote! { #ident: #value });
+    }
+    let path = code_path(spec.path);
+    Ok(quote! { #path { #(#inits),* } })
+}
+
+/// `code`模块中`path`的完整路径
+fn code_path(path: &str) -> TokenStream {
+    let krate = krate();
+    let segments = path.split("::").map(|s| format_ident!("{}", s));
+    quote! { #krate::message::cq_code::code #(::#segments)* }
+}
+
+/// 生成字段的值（`Option`字段为其内部类型的值），字面值在此检查
+fn field_value(
+    spec: &CodeSpec,
+    f: &FieldSpec,
+    value: &Value,
+    args: &[TokenStream],
+) -> std::result::Result<TokenStream, String> {
+    let s = match value {
+        Value::Arg(i) => {
+            let arg = &args[*i];
+            return Ok(match f.kind {
+                Kind::Str => quote! { ::std::string::ToString::to_string(&#arg) },
+                _ => arg.clone(),
+            });
+        }
+        Value::Literal(s) => s,
+    };
+    let invalid = |expected: &str| {
+        format!(
+            "the value `{}` of field `{}` in CQ code `{}` is not {}",
+            s, f.wire, spec.name, expected
+        )
+    };
+    Ok(match &f.kind {
+        Kind::Int => {
+            let lit = Literal::i64_unsuffixed(s.parse().map_err(|_| invalid("an integer"))?);
+            quote! { #lit }
+        }
+        Kind::Float => {
+            let v = s
+                .parse::<f64>()
+                .ok()
+                .filter(|v| v.is_finite())
+                .ok_or_else(|| invalid("a number"))?;
+            let lit = Literal::f64_unsuffixed(v);
+            quote! { #lit }
+        }
+        Kind::Str => quote! { ::std::string::String::from(#s) },
+        Kind::Media => {
+            let path = code_path("MediaSource");
+            quote! {
+                match <#path as ::core::str::FromStr>::from_str(#s) {
+                    ::core::result::Result::Ok(source) => source,
+                }
+            }
+        }
+        Kind::Enum(ty, variants) => {
+            let variant = variants
+                .iter()
+                .find(|(wire, _)| wire == s)
+                .map(|(_, variant)| variant)
+                .ok_or_else(|| {
+                    let expected: Vec<String> =
+                        variants.iter().map(|(w, _)| format!("`{}`", w)).collect();
+                    invalid(&format!("one of {}", expected.join(", ")))
+                })?;
+            code_path(&format!("{}::{}", ty, variant))
+        }
+    })
+}
+
+/// 字段的示例字面值，用于`known_codes`
+fn sample(kind: &Kind) -> &'static str {
+    match kind {
+        Kind::Int => "1",
+        Kind::Float => "1.5",
+        Kind::Str => "a",
+        Kind::Media => "file:///a.png",
+        Kind::Enum(_, variants) => variants[0].0,
+    }
+}
+
+/// 为每种已知的CQ码生成两个模板：包含所有字段的，和只包含必填字段（没有默认值）的，
+/// 展开为`(模板, cq!(模板), 用对应类型的from_string解析模板再to_string)`的数组，
+/// 用于检查`known`中的记录与`code`模块中的类型一致
+pub fn known_codes() -> Result<TokenStream> {
+    let krate = krate();
+    let mut items = Vec::new();
+    for spec in CODES {
+        let ty = code_path(spec.path.split("::").next().unwrap());
+        for all in [true, false] {
+            let mut fields: Vec<String> = spec
+                .fixed
+                .map(|(w, v)| format!("{}={}", w, v))
+                .into_iter()
+                .collect();
+            fields.extend(
+                spec.fields
+                    .iter()
+                    .filter(|f| all || (!f.optional && f.default.is_none()))
+                    .map(|f| format!("{}={}", f.wire, sample(&f.kind))),
+            );
+            let template = match fields.is_empty() {
+                true => format!("[CQ:{}]", spec.name),
+                false => format!("[CQ:{},{}]", spec.name, fields.join(",")),
+            };
+            let message = expand(Template {
+                template: LitStr::new(&template, Span::call_site()),
+                args: Vec::new(),
+            })?;
+            items.push(quote! {
+                (
+                    #template,
+                    #message,
+                    (|s: &str| {
+                        <#ty as #krate::message::cq_code::CQCode>::from_string(s.to_string())
+                            .map(|code| #krate::message::cq_code::CQCode::to_string(&code))
+                    }) as fn(&str) -> #krate::error::Result<::std::string::String>,
+                )
+            });
+        }
+    }
+    Ok(quote! { [#(#items),*] })
+}
@@ -0,0 +1,131 @@
+use crate::error::Result;
+use crate::message::cq_code::code::Node;
+use crate::message::cq_code::CQCode;
+use crate::message::Message;
+use serde::ser::{Error, SerializeSeq};
+use serde::Serialize;
+use serde_json::Value;
+
+/// 合并转发消息，由若干`Node`组成
+///
+/// 用于`send_group_forward_msg`, `send_private_forward_msg`和`send_forward_msg`，序列化为节点的数组
+///
+/// ```
+/// use rust_gocqhttp::cq;
+/// use rust_gocqhttp::message::forward::ForwardMessage;
+///
+/// let inner = ForwardMessage::new().custom("Alice", 10001, cq!("你好"));
+/// let forward = ForwardMessage::new()
+///     .reference(123)
+///     .custom("Bob", 10002, cq!("[CQ:face,id=14]"))
+///     .nested("Carol", 10003, &inner)
+///     .unwrap();
+/// assert_eq!(forward.nodes.len(), 3);
+/// ```
+#[derive(Debug, Default)]
+pub struct ForwardMessage {
+    pub nodes: Vec<Node>,
+}
+
+impl ForwardMessage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 添加一个节点
+    pub fn node(mut self, node: Node) -> Self {
+        self.nodes.push(node);
+        self
+    }
+
+    /// 添加一个引用已有消息的节点
+    pub fn reference(self, id: i32) -> Self {
+        self.node(Node::reference(id))
+    }
+
+    /// 添加一个自定义消息节点
+    pub fn custom(self, name: impl Into<String>, uin: i64, content: Message) -> Self {
+        self.node(Node::custom(name, uin, content))
+    }
+
+    /// 添加一个内容为嵌套合并转发的自定义消息节点
+    pub fn nested(
+        self,
+        name: impl Into<String>,
+        uin: i64,
+        forward: &ForwardMessage,
+    ) -> Result<Self> {
+        Ok(self.node(Node::nested(name, uin, forward)?))
+    }
+
+    /// 转换为数组格式的消息，每个节点为一个消息段
+    pub fn to_message(&self) -> Result<Message> {
+        Ok(Message {
+            messages: self
+                .nodes
+                .iter()
+                .map(Node::to_json)
+                .collect::<Result<Vec<String>>>()?,
+        })
+    }
+}
+
+impl From<Vec<Node>> for ForwardMessage {
+    fn from(nodes: Vec<Node>) -> Self {
+        Self { nodes }
+    }
+}
+
+impl FromIterator<Node> for ForwardMessage {
+    fn from_iter<I: IntoIterator<Item = Node>>(iter: I) -> Self {
+        Self {
+            nodes: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl Serialize for ForwardMessage {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.nodes.len()))?;
+        for node in &self.nodes {
+            let json = node.to_json().map_err(S::Error::custom)?;
+            let value: Value = serde_json::from_str(&json).map_err(S::Error::custom)?;
+            seq.serialize_element(&value)?;
+        }
+        seq.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ForwardMessage;
+    use crate::cq;
+
+    #[test]
+    fn test_forward_message_serialize() {
+        let forward = ForwardMessage::new().reference(123).custom(
+            "Alice",
+            10001,
+            cq!("你好\"世界\"[CQ:face,id=14]"),
+        );
+        assert_eq!(
+            serde_json::to_value(&forward).unwrap(),
+            serde_json::from_str::<serde_json::Value>(r#"[{"type":"node","data":{"id":123}},{"type":"node","data":{"name":"Alice","uin":10001,"content":"你好\"世界\"[CQ:face,id=14]"}}]"#)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_forward_message_nested() {
+        let inner = ForwardMessage::new().custom("Alice", 10001, cq!("你好"));
+        let forward = ForwardMessage::new().nested("Bob", 10002, &inner).unwrap();
+        assert_eq!(
+            serde_json::to_value(&forward).unwrap(),
+            serde_json::from_str::<serde_json::Value>(r#"[{"type":"node","data":{"name":"Bob","uin":10002,"content":[{"type":"node","data":{"name":"Alice","uin":10001,"content":"你好"}}]}}]"#)
+                .unwrap()
+        );
+    }
+}
@@ -1,8 +1,10 @@
 pub mod cq_code;
+pub mod forward;
 
 use crate::Result;
 use regex::Regex;
 use serde::{Deserialize, Deserializer, Serialize};
+use std::fmt;
 use std::str::FromStr;
 
 pub enum MessageType {
@@ -29,25 +31,38 @@ impl<'de> Deserialize<'de> for MessageType {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Message {
     pub messages: Vec<String>,
 }
 
+/// 字符串格式序列化为字符串，数组格式序列化为消息段的数组
 impl Serialize for Message {
     fn serialize<S: serde::Serializer>(
         &self,
         serializer: S,
     ) -> std::result::Result<S::Ok, S::Error> {
-        serializer.serialize_str(&self.to_string())
+        use serde::ser::{Error, SerializeSeq};
+        match self.message_type() {
+            MessageType::String => serializer.serialize_str(&self.to_string()),
+            MessageType::Array => {
+                let mut seq = serializer.serialize_seq(Some(self.messages.len()))?;
+                for segment in &self.messages {
+                    let segment: serde_json::Value =
+                        serde_json::from_str(segment).map_err(S::Error::custom)?;
+                    seq.serialize_element(&segment)?;
+                }
+                seq.end()
+            }
+        }
     }
 }
 
-impl ToString for Message {
-    fn to_string(&self) -> String {
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self.message_type() {
-            MessageType::String => self.messages.join(""),
-            MessageType::Array => format!("[{}]", self.messages.join(",")),
+            MessageType::String => f.write_str(&self.messages.join("")),
+            MessageType::Array => write!(f, "[{}]", self.messages.join(",")),
         }
     }
 }
@@ -57,9 +72,20 @@ impl<'de> Deserialize<'de> for Message {
     where
         D: Deserializer<'de>,
     {
-        String::deserialize(deserializer)?
-            .parse()
-            .map_err(serde::de::Error::custom)
+        /// API返回的消息可能是字符串格式，也可能是数组格式
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            String(String),
+            Array(Vec<serde_json::Value>),
+        }
+
+        match Raw::deserialize(deserializer)? {
+            Raw::String(s) => s.parse().map_err(serde::de::Error::custom),
+            Raw::Array(segments) => Ok(Self {
+                messages: segments.iter().map(ToString::to_string).collect(),
+            }),
+        }
     }
 }
 
@@ -289,7 +315,7 @@ mod tests {
 
     #[test]
     fn test_macro_message_from_strings2() {
-        let m = message_from_strings!("你好世界", Face { id: Some(1) });
+        let m = message_from_strings!("你好世界", Face { id: 1 });
         assert_eq!(m.messages.len(), 2);
         assert_eq!(m.messages[0], "你好世界");
         assert_eq!(m.messages[1], "[CQ:face,id=1]");
@@ -305,7 +331,7 @@ mod tests {
 
     #[test]
     fn test_macro_message_from_jsons1() {
-        let m = message_from_jsons!(Face { id: Some(1) });
+        let m = message_from_jsons!(Face { id: 1 });
         assert_eq!(m.messages.len(), 1);
         assert_eq!(m.messages[0], "{\"type\":\"face\",\"data\":{\"id\":1}}");
         assert_eq!(m.to_string(), "[{\"type\":\"face\",\"data\":{\"id\":1}}]");
@@ -313,7 +339,7 @@ mod tests {
 
     #[test]
     fn test_macro_message_from_jsons2() {
-        let m = message_from_jsons!(Face { id: Some(1) }, Face { id: Some(2) });
+        let m = message_from_jsons!(Face { id: 1 }, Face { id: 2 });
         assert_eq!(m.messages.len(), 2);
         assert_eq!(m.messages[0], "{\"type\":\"face\",\"data\":{\"id\":1}}");
         assert_eq!(m.messages[1], "{\"type\":\"face\",\"data\":{\"id\":2}}");
@@ -322,4 +348,72 @@ mod tests {
             "[{\"type\":\"face\",\"data\":{\"id\":1}},{\"type\":\"face\",\"data\":{\"id\":2}}]"
         );
     }
+
+    #[test]
+    fn test_message_deserialize() {
+        let m: Message = serde_json::from_str(r#""你好[CQ:face,id=1]""#).unwrap();
+        assert_eq!(m.messages, vec!["你好", "[CQ:face,id=1]"]);
+        let m: Message = serde_json::from_str(
+            r#"[{"type":"face","data":{"id":1}},{"type":"text","data":{"text":"a"}}]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            m.messages,
+            vec![
+                r#"{"data":{"id":1},"type":"face"}"#,
+                r#"{"data":{"text":"a"},"type":"text"}"#
+            ]
+        );
+        assert!(Face::from_json(&m.messages[0]).is_ok());
+    }
+
+    #[test]
+    fn test_macro_cq0() {
+        let m = crate::cq!("");
+        assert_eq!(m.messages, vec![""]);
+        let m = crate::cq!("你好[CQ:at,qq={}] [CQ:face,id=14]", 123);
+        assert_eq!(
+            m.messages,
+            vec!["你好", "[CQ:at,qq=123]", " ", "[CQ:face,id=14]"]
+        );
+        assert_eq!(m.to_string(), "你好[CQ:at,qq=123] [CQ:face,id=14]");
+    }
+
+    #[test]
+    fn test_macro_cq1() {
+        let name = "a,b";
+        let m = crate::cq!("[{}]{{}}[CQ:at,qq=all,name={}]", "&", name);
+        assert_eq!(m.messages[0], "&#91;&amp;&#93;{}");
+        assert_eq!(m.messages[1], "[CQ:at,qq=all,name=a&#44;b]");
+        let m = crate::cq!("[CQ:share,url=https://a.com,title=a&#44;b]");
+        assert_eq!(m.messages[0], "[CQ:share,url=https://a.com,title=a&#44;b]");
+    }
+
+    #[test]
+    fn test_macro_cq2() {
+        let id: i32 = 1;
+        let m = crate::cq!(
+            "[CQ:image,file=1.jpg,type=flash][CQ:music,type=163,id=28949129][CQ:music,type=custom,url=u,audio=a,title=t][CQ:json,data=x][CQ:reply,id={}]",
+            id
+        );
+        assert_eq!(
+            m.messages,
+            vec![
+                "[CQ:image,file=1.jpg,type=flash]",
+                "[CQ:music,type=163,id=28949129]",
+                "[CQ:music,type=custom,url=u,audio=a,title=t]",
+                "[CQ:json,data=x,resid=0]",
+                "[CQ:reply,id=1]",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_macro_cq_known_codes() {
+        // `cq!`单独记录了每种CQ码的字段，这里确认其与CQ码类型的解析结果一致
+        for (template, message, parse) in cq_code_derive::__known_codes!() {
+            let parsed = parse(template).unwrap_or_else(|e| panic!("{}: {}", template, e));
+            assert_eq!(message.messages, [parsed], "{}", template);
+        }
+    }
 }
@@ -0,0 +1,381 @@
+//! 权限检查：限制处理器和命令的使用者
+//!
+//! `Permission`可以用`and`、`or`、`not`组合，通过`guard`包装处理器，或通过`CommandHandler::permission`附加到命令。
+//! 群角色优先使用群消息事件中的`sender.role`，事件中没有时通过`get_group_member_info`获取。
+//! `Dispatcher::superusers`设置的超级用户不受任何规则限制
+//!
+//! ```no_run
+//! # fn f(handler: impl rust_gocqhttp::dispatcher::Handler + 'static) {
+//! use rust_gocqhttp::api::client::HttpClient;
+//! use rust_gocqhttp::dispatcher::Dispatcher;
+//! use rust_gocqhttp::permission::Permission;
+//!
+//! // 超级用户不受限制，其他人需要是测试群中的管理员或群主，且不在黑名单中
+//! let permission = Permission::admin()
+//!     .and(Permission::groups([123456]))
+//!     .and(Permission::deny_users([20000]));
+//! let dispatcher = Dispatcher::new(HttpClient::new("http://127.0.0.1:5700"))
+//!     .superusers([10000])
+//!     .handler(permission.guard(handler));
+//! # }
+//! ```
+
+use crate::api::data::GroupRole;
+use crate::api::mode::Failed;
+use crate::api::params::GetGroupMemberInfo;
+use crate::api::service::Service;
+use crate::api::GoCqhttpAPI;
+use crate::dispatcher::{Context, Handler};
+use crate::Result;
+use async_trait::async_trait;
+use serde_json::Value;
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+enum Rule {
+    Everyone,
+    Superusers,
+    Users(HashSet<i64>),
+    Groups(HashSet<i64>),
+    Role(GroupRole),
+    Private,
+    All(Vec<Permission>),
+    Any(Vec<Permission>),
+    Not(Box<Permission>),
+}
+
+/// 检查时使用的事件信息
+struct Facts {
+    user_id: Option<i64>,
+    group_id: Option<i64>,
+    private: bool,
+    role: Option<GroupRole>,
+}
+
+/// 角色的等级，群主最高
+fn rank(role: GroupRole) -> u8 {
+    match role {
+        GroupRole::Owner => 2,
+        GroupRole::Admin => 1,
+        GroupRole::Member => 0,
+    }
+}
+
+/// 解析角色，未知的角色返回`None`
+fn parse_role(role: &Value) -> Option<GroupRole> {
+    match role.as_str()? {
+        role @ ("owner" | "admin" | "member") => Some(GroupRole::from(role)),
+        _ => None,
+    }
+}
+
+/// 可组合的权限规则
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Permission {
+    rule: Rule,
+}
+
+impl Permission {
+    fn new(rule: Rule) -> Self {
+        Self { rule }
+    }
+
+    /// 所有人
+    pub fn everyone() -> Self {
+        Self::new(Rule::Everyone)
+    }
+
+    /// 只有`Dispatcher::superusers`设置的超级用户，不通过`Context`检查时没有人满足
+    pub fn superusers() -> Self {
+        Self::new(Rule::Superusers)
+    }
+
+    /// 用户白名单
+    pub fn users(user_ids: impl IntoIterator<Item = i64>) -> Self {
+        Self::new(Rule::Users(user_ids.into_iter().collect()))
+    }
+
+    /// 用户黑名单
+    pub fn deny_users(user_ids: impl IntoIterator<Item = i64>) -> Self {
+        Self::users(user_ids).not()
+    }
+
+    /// 只在这些群中
+    pub fn groups(group_ids: impl IntoIterator<Item = i64>) -> Self {
+        Self::new(Rule::Groups(group_ids.into_iter().collect()))
+    }
+
+    /// 不在这些群中，私聊不受限制
+    pub fn deny_groups(group_ids: impl IntoIterator<Item = i64>) -> Self {
+        Self::groups(group_ids).not()
+    }
+
+    /// 群角色不低于`role`，只在群中
+    pub fn role(role: GroupRole) -> Self {
+        Self::new(Rule::Role(role))
+    }
+
+    /// 群主
+    pub fn owner() -> Self {
+        Self::role(GroupRole::Owner)
+    }
+
+    /// 群管理员或群主
+    pub fn admin() -> Self {
+        Self::role(GroupRole::Admin)
+    }
+
+    /// 只在私聊中
+    pub fn private() -> Self {
+        Self::new(Rule::Private)
+    }
+
+    /// 同时满足两者
+    pub fn and(self, other: Permission) -> Self {
+        match self.rule {
+            Rule::All(mut rules) => {
+                rules.push(other);
+                Self::new(Rule::All(rules))
+            }
+            _ => Self::new(Rule::All(vec![self, other])),
+        }
+    }
+
+    /// 满足任一
+    pub fn or(self, other: Permission) -> Self {
+        match self.rule {
+            Rule::Any(mut rules) => {
+                rules.push(other);
+                Self::new(Rule::Any(rules))
+            }
+            _ => Self::new(Rule::Any(vec![self, other])),
+        }
+    }
+
+    /// 不满足
+    #[allow(clippy::should_implement_trait)]
+    pub fn not(self) -> Self {
+        match self.rule {
+            Rule::Not(inner) => *inner,
+            _ => Self::new(Rule::Not(Box::new(self))),
+        }
+    }
+
+    /// 是否包含群角色规则
+    fn needs_role(&self) -> bool {
+        match &self.rule {
+            Rule::Role(_) => true,
+            Rule::All(rules) | Rule::Any(rules) => rules.iter().any(Permission::needs_role),
+            Rule::Not(inner) => inner.needs_role(),
+            _ => false,
+        }
+    }
+
+    fn eval(&self, facts: &Facts) -> bool {
+        match &self.rule {
+            Rule::Everyone => true,
+            // 超级用户在检查规则之前已经放行
+            Rule::Superusers => false,
+            Rule::Users(ids) => facts.user_id.is_some_and(|id| ids.contains(&id)),
+            Rule::Groups(ids) => facts.group_id.is_some_and(|id| ids.contains(&id)),
+            Rule::Role(role) => facts.role.is_some_and(|r| rank(r) >= rank(*role)),
+            Rule::Private => facts.private,
+            Rule::All(rules) => rules.iter().all(|p| p.eval(facts)),
+            Rule::Any(rules) => rules.iter().any(|p| p.eval(facts)),
+            Rule::Not(inner) => !inner.eval(facts),
+        }
+    }
+
+    /// 事件的发送者是否有权限，需要时通过`api`获取群角色，发送者不在群中（`mode::Failed`）时视为没有角色
+    ///
+    /// 不知道超级用户，处理器中使用`allows`
+    pub async fn check<S: Service + ?Sized>(&self, api: &S, event: &Value) -> Result<bool> {
+        self.check_with(api, event, false).await
+    }
+
+    /// `ctx`中事件的发送者是否有权限，超级用户总是有权限
+    pub async fn allows(&self, ctx: &Context) -> Result<bool> {
+        self.check_with(ctx.api(), ctx.event(), ctx.is_superuser())
+            .await
+    }
+
+    async fn check_with<S: Service + ?Sized>(
+        &self,
+        api: &S,
+        event: &Value,
+        superuser: bool,
+    ) -> Result<bool> {
+        if superuser {
+            return Ok(true);
+        }
+        let user_id = event["user_id"].as_i64();
+        let group_id = event["group_id"].as_i64();
+        let mut role = parse_role(&event["sender"]["role"]);
+        if role.is_none() && self.needs_role() {
+            if let (Some(group_id), Some(user_id)) = (group_id, user_id) {
+                let params = GetGroupMemberInfo::new(group_id, user_id);
+                role = match api.get_group_member_info(params).await {
+                    Ok(info) => Some(info.role),
+                    Err(e) if e.is::<Failed>() => None,
+                    Err(e) => return Err(e),
+                };
+            }
+        }
+        let facts = Facts {
+            user_id,
+            group_id,
+            private: event["message_type"] == "private",
+            role,
+        };
+        Ok(self.eval(&facts))
+    }
+
+    /// 只在有权限时调用`handler`
+    pub fn guard<H: Handler>(self, handler: H) -> Guarded<H> {
+        Guarded {
+            permission: self,
+            handler,
+        }
+    }
+}
+
+/// `Permission::guard`包装后的处理器
+pub struct Guarded<H> {
+    permission: Permission,
+    handler: H,
+}
+
+#[async_trait]
+impl<H: Handler> Handler for Guarded<H> {
+    async fn handle(&self, ctx: Context) -> Result<()> {
+        if self.permission.allows(&ctx).await? {
+            self.handler.handle(ctx).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Permission;
+    use crate::dispatcher::{Context, Dispatcher};
+    use crate::record::{Entry, RecordedCall, Replay};
+    use futures::channel::mpsc::unbounded;
+    use futures::StreamExt;
+    use serde_json::{json, Value};
+    use std::time::Duration;
+
+    fn group_message(group_id: i64, user_id: i64, role: Option<&str>) -> Value {
+        json!({
+            "post_type": "message", "message_type": "group",
+            "group_id": group_id, "user_id": user_id,
+            "sender": { "user_id": user_id, "role": role }
+        })
+    }
+
+    #[tokio::test]
+    async fn test_permission() {
+        let api = Replay::new([]);
+        let permission = Permission::users([1]).or(Permission::admin()
+            .and(Permission::groups([10]))
+            .and(Permission::deny_users([3])));
+        let check = |event: Value| {
+            let permission = permission.clone();
+            let api = &api;
+            async move { permission.check(api, &event).await.unwrap() }
+        };
+        assert!(check(group_message(20, 1, Some("member"))).await);
+        assert!(check(group_message(10, 2, Some("owner"))).await);
+        assert!(!check(group_message(10, 2, Some("member"))).await);
+        assert!(!check(group_message(20, 2, Some("admin"))).await);
+        assert!(!check(group_message(10, 3, Some("admin"))).await);
+        let private = json!({ "post_type": "message", "message_type": "private", "user_id": 2 });
+        assert!(!check(private.clone()).await);
+        assert!(Permission::private().check(&api, &private).await.unwrap());
+        assert_eq!(Permission::private().not().not(), Permission::private());
+    }
+
+    #[tokio::test]
+    async fn test_role_from_api() {
+        let api = Replay::new([Entry::Call(RecordedCall {
+            action: "get_group_member_info".to_string(),
+            params: json!({ "group_id": 10, "user_id": 2 }),
+            data: json!({
+                "group_id": 10, "user_id": 2, "sex": "unknown", "role": "admin",
+                "unfriendly": false, "card_changeable": false
+            }),
+            submitted: false,
+            error: None,
+            retcode: None,
+        })]);
+        let notice = json!({ "post_type": "notice", "group_id": 10, "user_id": 2 });
+        assert!(Permission::admin().check(&api, &notice).await.unwrap());
+        // 不需要群角色时不调用API
+        assert!(Permission::groups([10]).check(&api, &notice).await.unwrap());
+        assert_eq!(api.remaining(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_role_not_member() {
+        let api = Replay::new([Entry::Call(RecordedCall {
+            action: "get_group_member_info".to_string(),
+            params: json!({ "group_id": 10, "user_id": 2 }),
+            data: Value::Null,
+            submitted: false,
+            error: Some("群员不存在".to_string()),
+            retcode: Some(100),
+        })]);
+        let notice = json!({ "post_type": "notice", "group_id": 10, "user_id": 2 });
+        assert!(!Permission::admin().check(&api, &notice).await.unwrap());
+        assert!(Permission::admin()
+            .not()
+            .check(&Replay::new([]), &notice)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_superusers() {
+        let (sender, receiver) = unbounded();
+        let guarded = sender.clone();
+        let dispatcher = Dispatcher::new(Replay::new([]))
+            .superusers([1])
+            .handler(Permission::admin().guard(move |ctx: Context| {
+                let _ = guarded.unbounded_send(("admin", ctx.event()["user_id"].clone()));
+                async { Ok(()) }
+            }))
+            .handler(move |ctx: Context| {
+                let sender = sender.clone();
+                async move {
+                    if Permission::superusers().allows(&ctx).await? {
+                        let _ =
+                            sender.unbounded_send(("superusers", ctx.event()["user_id"].clone()));
+                    }
+                    Ok(())
+                }
+            });
+        dispatcher.dispatch(group_message(10, 1, Some("member")));
+        dispatcher.dispatch(group_message(10, 2, Some("member")));
+        dispatcher.dispatch(group_message(10, 3, Some("admin")));
+        // 处理器随分发器丢弃后接收结束
+        drop(dispatcher);
+        let mut passed: Vec<_> = tokio::time::timeout(Duration::from_secs(1), receiver.collect())
+            .await
+            .unwrap();
+        passed.sort_by_key(|(rule, user_id)| (*rule, user_id.as_i64()));
+        assert_eq!(
+            passed,
+            [
+                ("admin", json!(1)),
+                ("admin", json!(3)),
+                ("superusers", json!(1))
+            ]
+        );
+        // 不通过`Context`检查时不知道超级用户
+        let event = group_message(10, 1, Some("member"));
+        assert!(!Permission::superusers()
+            .check(&Replay::new([]), &event)
+            .await
+            .unwrap());
+    }
+}
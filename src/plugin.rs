@@ -0,0 +1,342 @@
+//! 插件：将相互独立的功能（自动回复、入群欢迎、统计等）放在同一个bot中
+//!
+//! `PluginManager`实现了`Handler`，添加到`Dispatcher`后按优先级从高到低依次将事件交给插件，
+//! 插件返回`Propagation::Block`时不再交给优先级更低的插件。每个插件可以在指定的群中单独停用
+//!
+//! ```no_run
+//! # async fn f() -> rust_gocqhttp::error::Result<()> {
+//! use async_trait::async_trait;
+//! use rust_gocqhttp::api::client::HttpClient;
+//! use rust_gocqhttp::dispatcher::{Context, Dispatcher};
+//! use rust_gocqhttp::error::Result;
+//! use rust_gocqhttp::plugin::{Plugin, PluginManager, Propagation};
+//! use serde_json::{json, Value};
+//!
+//! #[derive(Default)]
+//! struct Welcome {
+//!     text: String,
+//! }
+//!
+//! #[async_trait]
+//! impl Plugin for Welcome {
+//!     fn name(&self) -> &str {
+//!         "welcome"
+//!     }
+//!
+//!     async fn on_load(&mut self, config: &Value) -> Result<()> {
+//!         self.text = config["text"].as_str().unwrap_or("欢迎").to_string();
+//!         Ok(())
+//!     }
+//!
+//!     async fn on_event(&self, ctx: &Context) -> Result<Propagation> {
+//!         if ctx.event()["notice_type"] != "group_increase" {
+//!             return Ok(Propagation::Continue);
+//!         }
+//!         // 发送欢迎消息...
+//!         Ok(Propagation::Block)
+//!     }
+//! }
+//!
+//! let plugins = PluginManager::new(json!({ "welcome": { "text": "欢迎新人" } }));
+//! plugins.load(Welcome::default()).await?;
+//! plugins.disable("welcome", 123456);
+//! let dispatcher = Dispatcher::new(HttpClient::new("http://127.0.0.1:5700")).handler(plugins);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::dispatcher::{Context, Handler};
+use crate::Result;
+use async_trait::async_trait;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard, RwLock};
+
+/// 插件处理事件后是否继续交给优先级更低的插件
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Propagation {
+    Continue,
+    Block,
+}
+
+/// 插件
+#[async_trait]
+pub trait Plugin: Send + Sync {
+    /// 插件名，同时是配置中该插件的字段名，不能重复
+    fn name(&self) -> &str;
+
+    /// 优先级，越大越先处理事件，相同优先级按加载顺序处理
+    fn priority(&self) -> i32 {
+        0
+    }
+
+    /// 加载时调用，`config`为配置中该插件的部分，没有时为`Value::Null`
+    async fn on_load(&mut self, _config: &Value) -> Result<()> {
+        Ok(())
+    }
+
+    /// 处理一个事件，返回的错误会被记录到日志，并继续交给下一个插件
+    async fn on_event(&self, ctx: &Context) -> Result<Propagation>;
+
+    /// 卸载时调用
+    async fn on_unload(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// 已经加载了同名的插件
+#[derive(Debug)]
+pub struct DuplicatePlugin(pub String);
+
+impl fmt::Display for DuplicatePlugin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "插件`{}`已经加载", self.0)
+    }
+}
+
+impl std::error::Error for DuplicatePlugin {}
+
+#[derive(Default)]
+struct Plugins {
+    config: Value,
+    /// 按优先级从高到低排列
+    loaded: RwLock<Vec<Arc<dyn Plugin>>>,
+    /// 停用的插件和群
+    disabled: Mutex<HashSet<(String, i64)>>,
+}
+
+/// 插件管理器，克隆后共享同一组插件
+#[derive(Clone, Default)]
+pub struct PluginManager {
+    plugins: Arc<Plugins>,
+}
+
+impl PluginManager {
+    /// `config`的每个字段为对应插件的配置，例如从YAML配置文件中读取的一个部分
+    pub fn new(config: Value) -> Self {
+        Self {
+            plugins: Arc::new(Plugins {
+                config,
+                ..Default::default()
+            }),
+        }
+    }
+
+    fn disabled(&self) -> MutexGuard<'_, HashSet<(String, i64)>> {
+        self.plugins
+            .disabled
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 按优先级排列的所有插件
+    fn snapshot(&self) -> Vec<Arc<dyn Plugin>> {
+        self.plugins
+            .loaded
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// 调用`on_load`并加载插件，`on_load`失败时不加载
+    pub async fn load(&self, mut plugin: impl Plugin + 'static) -> Result<()> {
+        if self.plugins().iter().any(|name| name == plugin.name()) {
+            return Err(Box::new(DuplicatePlugin(plugin.name().to_string())));
+        }
+        plugin.on_load(&self.plugins.config[plugin.name()]).await?;
+        let mut loaded = self
+            .plugins
+            .loaded
+            .write()
+            .unwrap_or_else(|e| e.into_inner());
+        // 加载期间可能有同名插件完成加载
+        if loaded.iter().any(|p| p.name() == plugin.name()) {
+            return Err(Box::new(DuplicatePlugin(plugin.name().to_string())));
+        }
+        let index = loaded.partition_point(|p| p.priority() >= plugin.priority());
+        log::info!("加载插件`{}`", plugin.name());
+        loaded.insert(index, Arc::new(plugin));
+        Ok(())
+    }
+
+    /// 卸载插件并调用`on_unload`，插件未加载时返回`false`
+    pub async fn unload(&self, name: &str) -> Result<bool> {
+        let plugin = {
+            let mut loaded = self
+                .plugins
+                .loaded
+                .write()
+                .unwrap_or_else(|e| e.into_inner());
+            match loaded.iter().position(|p| p.name() == name) {
+                Some(index) => loaded.remove(index),
+                None => return Ok(false),
+            }
+        };
+        log::info!("卸载插件`{}`", name);
+        plugin.on_unload().await?;
+        Ok(true)
+    }
+
+    /// 按优先级从低到高卸载所有插件
+    pub async fn unload_all(&self) -> Result<()> {
+        for name in self.plugins().iter().rev() {
+            self.unload(name).await?;
+        }
+        Ok(())
+    }
+
+    /// 按优先级排列的所有插件名
+    pub fn plugins(&self) -> Vec<String> {
+        self.snapshot()
+            .iter()
+            .map(|p| p.name().to_string())
+            .collect()
+    }
+
+    /// 在群`group_id`中启用插件
+    pub fn enable(&self, name: &str, group_id: i64) {
+        self.disabled().remove(&(name.to_string(), group_id));
+    }
+
+    /// 在群`group_id`中停用插件，该群的事件不再交给该插件
+    pub fn disable(&self, name: &str, group_id: i64) {
+        self.disabled().insert((name.to_string(), group_id));
+    }
+
+    /// 插件在群`group_id`中是否启用
+    pub fn is_enabled(&self, name: &str, group_id: i64) -> bool {
+        !self.disabled().contains(&(name.to_string(), group_id))
+    }
+}
+
+#[async_trait]
+impl Handler for PluginManager {
+    async fn handle(&self, ctx: Context) -> Result<()> {
+        let group_id = ctx.event()["group_id"].as_i64();
+        for plugin in self.snapshot() {
+            if group_id.is_some_and(|group_id| !self.is_enabled(plugin.name(), group_id)) {
+                continue;
+            }
+            match plugin.on_event(&ctx).await {
+                Ok(Propagation::Continue) => {}
+                Ok(Propagation::Block) => break,
+                Err(e) => log::warn!("插件`{}`处理事件失败: {}", plugin.name(), e),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Plugin, PluginManager, Propagation};
+    use crate::dispatcher::{Context, Dispatcher, Handler};
+    use crate::record::Replay;
+    use crate::Result;
+    use async_trait::async_trait;
+    use futures::channel::mpsc::unbounded;
+    use futures::StreamExt;
+    use serde_json::{json, Value};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    /// 记录收到的事件和生命周期，`block`为`true`时阻止事件继续传递
+    struct Probe {
+        name: &'static str,
+        priority: i32,
+        block: bool,
+        log: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl Probe {
+        fn new(name: &'static str, priority: i32, log: &Arc<Mutex<Vec<String>>>) -> Self {
+            Self {
+                name,
+                priority,
+                block: false,
+                log: log.clone(),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl Plugin for Probe {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn priority(&self) -> i32 {
+            self.priority
+        }
+
+        async fn on_load(&mut self, config: &Value) -> Result<()> {
+            self.block = config["block"].as_bool().unwrap_or_default();
+            self.log.lock().unwrap().push(format!("load {}", self.name));
+            Ok(())
+        }
+
+        async fn on_event(&self, ctx: &Context) -> Result<Propagation> {
+            let event = format!("{} {}", self.name, ctx.event()["group_id"]);
+            self.log.lock().unwrap().push(event);
+            Ok(match self.block {
+                true => Propagation::Block,
+                false => Propagation::Continue,
+            })
+        }
+
+        async fn on_unload(&self) -> Result<()> {
+            self.log
+                .lock()
+                .unwrap()
+                .push(format!("unload {}", self.name));
+            Ok(())
+        }
+    }
+
+    /// 分发`event`并等待所有插件处理完
+    async fn dispatch(plugins: &PluginManager, event: Value) {
+        let (sender, mut done) = unbounded();
+        let plugins = plugins.clone();
+        Dispatcher::new(Replay::new([]))
+            .handler(move |ctx: Context| {
+                let (plugins, sender) = (plugins.clone(), sender.clone());
+                async move {
+                    plugins.handle(ctx).await?;
+                    let _ = sender.unbounded_send(());
+                    Ok(())
+                }
+            })
+            .dispatch(event);
+        tokio::time::timeout(Duration::from_secs(1), done.next())
+            .await
+            .expect("plugins did not finish in time");
+    }
+
+    #[tokio::test]
+    async fn test_plugins() {
+        let log = Arc::default();
+        let plugins = PluginManager::new(json!({ "b": { "block": true } }));
+        plugins.load(Probe::new("a", 0, &log)).await.unwrap();
+        plugins.load(Probe::new("b", 1, &log)).await.unwrap();
+        plugins.load(Probe::new("c", 2, &log)).await.unwrap();
+        assert!(plugins.load(Probe::new("a", 3, &log)).await.is_err());
+        assert_eq!(plugins.plugins(), ["c", "b", "a"]);
+
+        plugins.disable("c", 1);
+        dispatch(&plugins, json!({ "group_id": 1 })).await;
+        plugins.enable("c", 1);
+        plugins.disable("b", 2);
+        dispatch(&plugins, json!({ "group_id": 2 })).await;
+        assert!(plugins.unload("b").await.unwrap());
+        assert!(!plugins.unload("b").await.unwrap());
+        plugins.unload_all().await.unwrap();
+        assert_eq!(
+            *log.lock().unwrap(),
+            [
+                "load a", "load b", "load c", "b 1", "c 2", "a 2", "unload b", "unload a",
+                "unload c"
+            ]
+        );
+    }
+}
@@ -0,0 +1,433 @@
+//! 记录与回放go-cqhttp的API调用和事件
+//!
+//! `Recorder`将API调用（参数与响应）和事件逐行写入JSONL文件，`Replay`读取该文件，
+//! 按顺序重新产生事件，并以记录中的响应回答API调用，用于离线复现线上问题
+//!
+//! ```no_run
+//! # async fn f() -> rust_gocqhttp::error::Result<()> {
+//! use futures::StreamExt;
+//! use rust_gocqhttp::api::client::HttpClient;
+//! use rust_gocqhttp::record::{Recorder, Replay};
+//!
+//! // 记录
+//! let recorder = Recorder::create("traffic.jsonl")?;
+//! let api = recorder.api(HttpClient::new("http://127.0.0.1:5700"));
+//! # let source = futures::stream::empty();
+//! let mut events = recorder.events(source);
+//! # drop((api, events.next().await));
+//!
+//! // 回放：`replay`实现了`Service`，因而也实现了`GoCqhttpAPI`，可以直接交给处理事件的代码
+//! let replay = Replay::open("traffic.jsonl")?;
+//! let mut events = replay.events();
+//! while let Some(event) = events.next().await {
+//!     // handle(&replay, event).await?;
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::api::mode::{Failed, Outcome};
+use crate::api::service::{Layer, Service};
+use crate::Result;
+use async_trait::async_trait;
+use futures::stream::{self, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// 记录文件中的一行
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Entry {
+    /// 一次API调用
+    Call(RecordedCall),
+    /// 收到的事件
+    Event { event: Value },
+}
+
+/// 记录的API调用
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RecordedCall {
+    pub action: String,
+    pub params: Value,
+    /// 调用成功时的返回数据
+    #[serde(default)]
+    pub data: Value,
+    /// 以异步或限速方式调用，只知道调用已提交
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub submitted: bool,
+    /// 调用失败时的错误信息
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// 调用失败且go-cqhttp返回了`status`为`failed`的响应时的返回码
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retcode: Option<i32>,
+}
+
+impl RecordedCall {
+    fn new(action: &str, params: Value, result: &Result<Outcome<Value>>) -> Self {
+        let mut call = Self {
+            action: action.to_string(),
+            params,
+            data: Value::Null,
+            submitted: false,
+            error: None,
+            retcode: None,
+        };
+        match result {
+            Ok(Outcome::Completed(data)) => call.data = data.clone(),
+            Ok(Outcome::Submitted) => call.submitted = true,
+            Err(e) => match e.downcast_ref::<Failed>() {
+                Some(failed) => {
+                    call.error = Some(failed.message.clone());
+                    call.retcode = Some(failed.retcode);
+                }
+                None => call.error = Some(e.to_string()),
+            },
+        }
+        call
+    }
+
+    /// 还原调用的结果
+    fn result(self) -> Result<Outcome<Value>> {
+        match (self.error, self.retcode) {
+            (None, _) if self.submitted => Ok(Outcome::Submitted),
+            (None, _) => Ok(Outcome::Completed(self.data)),
+            (Some(message), Some(retcode)) => Err(Box::new(Failed {
+                action: self.action,
+                retcode,
+                wording: message.clone(),
+                message,
+            })),
+            (Some(message), None) => Err(Box::new(Replayed(message))),
+        }
+    }
+}
+
+/// 回放的调用错误，内容为记录时错误的`to_string()`
+#[derive(Debug)]
+pub struct Replayed(pub String);
+
+impl fmt::Display for Replayed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for Replayed {}
+
+/// 记录中没有与之对应的调用
+#[derive(Debug)]
+pub struct NotRecorded {
+    pub action: String,
+    pub params: Value,
+}
+
+impl fmt::Display for NotRecorded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "记录中没有对API`{}`的调用: {}", self.action, self.params)
+    }
+}
+
+impl std::error::Error for NotRecorded {}
+
+/// 将API调用和事件写入JSONL，克隆后写入同一个文件
+#[derive(Clone)]
+pub struct Recorder {
+    writer: Arc<Mutex<Box<dyn Write + Send>>>,
+}
+
+impl Recorder {
+    pub fn new(writer: impl Write + Send + 'static) -> Self {
+        Self {
+            writer: Arc::new(Mutex::new(Box::new(writer))),
+        }
+    }
+
+    /// 创建（或清空）`path`并写入其中
+    pub fn create(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self::new(File::create(path)?))
+    }
+
+    fn writer(&self) -> MutexGuard<'_, Box<dyn Write + Send>> {
+        self.writer.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 写入一行，失败时只记录日志，不影响被记录的调用
+    pub fn write(&self, entry: &Entry) {
+        let result = serde_json::to_string(entry)
+            .map_err(std::io::Error::from)
+            .and_then(|line| {
+                let mut writer = self.writer();
+                writeln!(writer, "{}", line)?;
+                writer.flush()
+            });
+        if let Err(e) = result {
+            log::warn!("写入记录失败: {}", e);
+        }
+    }
+
+    /// 记录`api`的所有调用，也可以作为中间件使用
+    pub fn api<S: Service>(&self, api: S) -> Recording<S> {
+        self.layer(api)
+    }
+
+    /// 记录`events`产生的每个事件
+    pub fn events<S>(&self, events: S) -> impl Stream<Item = Value>
+    where
+        S: Stream<Item = Value>,
+    {
+        let recorder = self.clone();
+        events.inspect(move |event| {
+            recorder.write(&Entry::Event {
+                event: event.clone(),
+            })
+        })
+    }
+}
+
+impl<S> Layer<S> for Recorder {
+    type Service = Recording<S>;
+
+    fn layer(&self, inner: S) -> Recording<S> {
+        Recording {
+            inner,
+            recorder: self.clone(),
+        }
+    }
+}
+
+/// 记录每次调用的`Service`，由`Recorder::api`创建
+pub struct Recording<S> {
+    inner: S,
+    recorder: Recorder,
+}
+
+impl<S> Recording<S> {
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+#[async_trait]
+impl<S: Service> Service for Recording<S> {
+    async fn call(&self, action: &str, params: Value) -> Result<Outcome<Value>> {
+        let result = self.inner.call(action, params.clone()).await;
+        self.recorder
+            .write(&Entry::Call(RecordedCall::new(action, params, &result)));
+        result
+    }
+}
+
+/// 从记录中回放事件并回答API调用
+///
+/// 对每次调用，使用同一API、参数相同且尚未使用的最早记录，没有时返回`NotRecorded`错误。
+/// 调用`loose`后，没有参数相同的记录时改为使用同一API尚未使用的最早记录
+pub struct Replay {
+    events: Vec<Value>,
+    calls: Mutex<HashMap<String, VecDeque<RecordedCall>>>,
+    loose: bool,
+}
+
+impl Replay {
+    pub fn new(entries: impl IntoIterator<Item = Entry>) -> Self {
+        let mut events = Vec::new();
+        let mut calls: HashMap<String, VecDeque<RecordedCall>> = HashMap::new();
+        for entry in entries {
+            match entry {
+                Entry::Call(call) => calls
+                    .entry(call.action.clone())
+                    .or_default()
+                    .push_back(call),
+                Entry::Event { event } => events.push(event),
+            }
+        }
+        Self {
+            events,
+            calls: Mutex::new(calls),
+            loose: false,
+        }
+    }
+
+    /// 参数不同时也使用同一API的记录，例如参数中含有时间戳等每次运行都不同的值，使用时会记录警告日志
+    pub fn loose(mut self) -> Self {
+        self.loose = true;
+        self
+    }
+
+    /// 读取JSONL格式的记录，忽略空行
+    pub fn from_reader(reader: impl BufRead) -> Result<Self> {
+        let mut entries = Vec::new();
+        for line in reader.lines() {
+            let line = line?;
+            if !line.trim().is_empty() {
+                entries.push(serde_json::from_str(&line)?);
+            }
+        }
+        Ok(Self::new(entries))
+    }
+
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_reader(BufReader::new(File::open(path)?))
+    }
+
+    /// 按记录顺序产生所有事件，可以多次调用
+    pub fn events(&self) -> impl Stream<Item = Value> + Send + 'static {
+        stream::iter(self.events.clone())
+    }
+
+    /// 尚未被使用的调用记录数
+    pub fn remaining(&self) -> usize {
+        self.calls().values().map(VecDeque::len).sum()
+    }
+
+    fn calls(&self) -> MutexGuard<'_, HashMap<String, VecDeque<RecordedCall>>> {
+        self.calls.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[async_trait]
+impl Service for Replay {
+    async fn call(&self, action: &str, params: Value) -> Result<Outcome<Value>> {
+        let call = self.calls().get_mut(action).and_then(|queue| {
+            let index = match queue.iter().position(|c| c.params == params) {
+                Some(index) => index,
+                None if self.loose && !queue.is_empty() => {
+                    log::warn!(
+                        "记录中没有参数相同的API`{}`调用，使用参数为{}的记录",
+                        action,
+                        queue[0].params
+                    );
+                    0
+                }
+                None => return None,
+            };
+            queue.remove(index)
+        });
+        match call {
+            Some(call) => call.result(),
+            None => Err(Box::new(NotRecorded {
+                action: action.to_string(),
+                params,
+            })),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Entry, NotRecorded, Recorder, Replay};
+    use crate::api::mode::Failed;
+    use crate::api::params::{DeleteMsg, GetMsg, GetStrangerInfo};
+    use crate::api::GoCqhttpAPI;
+    use futures::StreamExt;
+    use serde_json::json;
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+
+    /// 可以在写入后读取内容的缓冲区
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn replay() -> Replay {
+        let lines = [
+            json!({ "kind": "event", "event": { "post_type": "message", "message_id": 1 } }),
+            json!({ "kind": "call", "action": "get_stranger_info", "params": { "user_id": 1 },
+                    "data": { "user_id": 1, "nickname": "a", "sex": "male" } }),
+            json!({ "kind": "call", "action": "get_stranger_info", "params": { "user_id": 2 },
+                    "data": { "user_id": 2, "nickname": "b", "sex": "female" } }),
+            json!({ "kind": "call", "action": "delete_msg", "params": { "message_id": 1 },
+                    "error": "failed", "retcode": 100 }),
+            json!({ "kind": "event", "event": { "post_type": "notice" } }),
+        ];
+        let text = lines.map(|l| l.to_string()).join("\n");
+        Replay::from_reader(text.as_bytes()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_replay() {
+        let replay = replay();
+        let events: Vec<_> = replay.events().collect().await;
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[1]["post_type"], "notice");
+        // 参数相同的记录优先，不按记录顺序
+        let info = replay
+            .get_stranger_info(GetStrangerInfo::new(2))
+            .await
+            .unwrap();
+        assert_eq!(info.nickname, "b");
+        let error = replay.delete_msg(DeleteMsg::new(1)).await.unwrap_err();
+        assert_eq!(error.downcast_ref::<Failed>().unwrap().retcode, 100);
+        let Err(error) = replay.get_msg(GetMsg::new(1)).await else {
+            panic!("get_msg was not recorded")
+        };
+        assert!(error.downcast_ref::<NotRecorded>().is_some());
+        assert_eq!(replay.remaining(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_replay_params_mismatch() {
+        let replay = replay();
+        let Err(error) = replay.get_stranger_info(GetStrangerInfo::new(3)).await else {
+            panic!("get_stranger_info(3) was not recorded")
+        };
+        let error = error.downcast_ref::<NotRecorded>().unwrap();
+        assert_eq!(error.params["user_id"], 3);
+        assert_eq!(replay.remaining(), 3);
+        // `loose`时使用同一API最早的记录
+        let replay = replay.loose();
+        let info = replay
+            .get_stranger_info(GetStrangerInfo::new(3))
+            .await
+            .unwrap();
+        assert_eq!(info.nickname, "a");
+        assert_eq!(replay.remaining(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_record_then_replay() {
+        let buffer = Buffer::default();
+        let recorder = Recorder::new(buffer.clone());
+        let api = recorder.api(replay());
+        let events: Vec<_> = recorder.events(api.inner().events()).collect().await;
+        assert_eq!(events.len(), 2);
+        api.get_stranger_info(GetStrangerInfo::new(1))
+            .await
+            .unwrap();
+        assert!(api.delete_msg(DeleteMsg::new(1)).await.is_err());
+
+        let text = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let entries: Vec<Entry> = text
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(entries.len(), 4);
+        let replay = Replay::new(entries);
+        let info = replay
+            .get_stranger_info(GetStrangerInfo::new(1))
+            .await
+            .unwrap();
+        assert_eq!(info.nickname, "a");
+        let error = replay.delete_msg(DeleteMsg::new(1)).await.unwrap_err();
+        assert_eq!(error.downcast_ref::<Failed>().unwrap().retcode, 100);
+    }
+}
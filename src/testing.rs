@@ -0,0 +1,566 @@
+//! 用于离线测试的go-cqhttp模拟服务器，需要启用`testing`特性
+//!
+//! `FakeGoCqhttp`在本进程内监听一个本地端口，同时提供HTTP API和正向WebSocket：
+//! 记录收到的每一次API调用，按API返回预设的响应，并可以向WebSocket连接推送事件
+//!
+//! ```
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() -> rust_gocqhttp::error::Result<()> {
+//! use rust_gocqhttp::api::params::SendGroupMsg;
+//! use rust_gocqhttp::api::GoCqhttpAPI;
+//! use rust_gocqhttp::message::Message;
+//! use rust_gocqhttp::testing::FakeGoCqhttp;
+//! use serde_json::json;
+//!
+//! let fake = FakeGoCqhttp::start().await?;
+//! fake.respond("send_group_msg", json!({ "message_id": 1 }));
+//! let sent = fake
+//!     .client()
+//!     .send_group_msg(SendGroupMsg::new(1, "hi".parse::<Message>()?))
+//!     .await?;
+//! assert_eq!(sent.message_id, 1);
+//! assert_eq!(fake.calls_to("send_group_msg")[0]["message"], "hi");
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::api::client::HttpClient;
+use crate::api::mode::CallMode;
+use crate::Result;
+use futures::channel::{mpsc, oneshot};
+use futures::{SinkExt, StreamExt};
+use hyper::header::{HeaderValue, CONNECTION, SEC_WEBSOCKET_ACCEPT, SEC_WEBSOCKET_KEY, UPGRADE};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Request, Response, Server, StatusCode};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::{HashMap, VecDeque};
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use tokio_tungstenite::tungstenite::handshake::derive_accept_key;
+use tokio_tungstenite::tungstenite::protocol::Role;
+use tokio_tungstenite::tungstenite::Message as WsMessage;
+use tokio_tungstenite::WebSocketStream;
+
+/// 通过哪种连接调用了API
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Transport {
+    Http,
+    WebSocket,
+}
+
+/// 收到的一次API调用
+#[derive(Debug, Clone, PartialEq)]
+pub struct Call {
+    /// API名称，不含`_async`等后缀
+    pub action: String,
+    pub mode: CallMode,
+    pub params: Value,
+    pub transport: Transport,
+    /// HTTP请求头，名称为小写；WebSocket调用为空
+    pub headers: HashMap<String, String>,
+}
+
+/// 预设的响应
+#[derive(Debug, Clone, PartialEq)]
+pub enum Reply {
+    /// 调用成功，返回`data`
+    Ok(Value),
+    /// 调用失败
+    Failed { retcode: i32, message: String },
+}
+
+impl Reply {
+    pub fn ok(data: impl Serialize) -> Self {
+        Reply::Ok(serde_json::to_value(data).unwrap_or(Value::Null))
+    }
+
+    pub fn failed(retcode: i32, message: impl Into<String>) -> Self {
+        Reply::Failed {
+            retcode,
+            message: message.into(),
+        }
+    }
+
+    /// go-cqhttp格式的响应
+    fn to_response(&self, mode: CallMode) -> Value {
+        match (self, mode) {
+            (_, CallMode::Async | CallMode::RateLimited) => {
+                json!({ "status": "async", "retcode": 1, "data": null })
+            }
+            (Reply::Ok(data), CallMode::Normal) => {
+                json!({ "status": "ok", "retcode": 0, "data": data })
+            }
+            (Reply::Failed { retcode, message }, CallMode::Normal) => json!({
+                "status": "failed", "retcode": retcode, "msg": message,
+                "wording": message, "data": null
+            }),
+        }
+    }
+}
+
+type Responder = Arc<dyn Fn(&Value) -> Reply + Send + Sync>;
+
+#[derive(Default)]
+struct State {
+    calls: Vec<Call>,
+    once: HashMap<String, VecDeque<Reply>>,
+    responders: HashMap<String, Responder>,
+    /// 接收事件的WebSocket连接
+    subscribers: Vec<mpsc::UnboundedSender<String>>,
+}
+
+impl State {
+    fn reply(&mut self, call: Call) -> Value {
+        let reply = match self
+            .once
+            .get_mut(&call.action)
+            .and_then(VecDeque::pop_front)
+        {
+            Some(reply) => reply,
+            None => match self.responders.get(&call.action) {
+                Some(responder) => responder(&call.params),
+                None => Reply::Ok(Value::Null),
+            },
+        };
+        let response = reply.to_response(call.mode);
+        self.calls.push(call);
+        response
+    }
+}
+
+#[derive(Default)]
+struct Shared {
+    state: Mutex<State>,
+    access_token: Option<String>,
+}
+
+impl Shared {
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// 进程内的go-cqhttp模拟服务器，离开作用域时关闭
+///
+/// - HTTP：`POST /{action}`，参数为JSON请求体，支持`_async`和`_rate_limited`后缀
+/// - WebSocket：`/`同时提供API和事件，`/api`只提供API，`/event`只推送事件
+///
+/// 没有预设响应的API返回`data`为`null`的成功响应
+pub struct FakeGoCqhttp {
+    addr: SocketAddr,
+    shared: Arc<Shared>,
+    shutdown: Option<oneshot::Sender<()>>,
+}
+
+impl FakeGoCqhttp {
+    /// 在随机端口上启动
+    pub async fn start() -> Result<Self> {
+        Self::serve(Shared::default()).await
+    }
+
+    /// 在随机端口上启动，并要求请求携带`access_token`
+    pub async fn with_access_token(access_token: impl Into<String>) -> Result<Self> {
+        Self::serve(Shared {
+            access_token: Some(access_token.into()),
+            ..Default::default()
+        })
+        .await
+    }
+
+    async fn serve(shared: Shared) -> Result<Self> {
+        let shared = Arc::new(shared);
+        let make_service = make_service_fn({
+            let shared = shared.clone();
+            move |_| {
+                let shared = shared.clone();
+                async move {
+                    Ok::<_, Infallible>(service_fn(move |req| {
+                        let shared = shared.clone();
+                        async move { Ok::<_, Infallible>(handle(shared, req).await) }
+                    }))
+                }
+            }
+        });
+        let server = Server::try_bind(&SocketAddr::from(([127, 0, 0, 1], 0)))?.serve(make_service);
+        let addr = server.local_addr();
+        let (shutdown, signal) = oneshot::channel::<()>();
+        tokio::spawn(server.with_graceful_shutdown(async {
+            signal.await.ok();
+        }));
+        Ok(Self {
+            addr,
+            shared,
+            shutdown: Some(shutdown),
+        })
+    }
+
+    /// HTTP API的地址，例如`http://127.0.0.1:12345`
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// 正向WebSocket的地址，例如`ws://127.0.0.1:12345`
+    pub fn ws_url(&self) -> String {
+        format!("ws://{}", self.addr)
+    }
+
+    /// 连接到该服务器的`HttpClient`
+    pub fn client(&self) -> HttpClient {
+        let client = HttpClient::new(self.url());
+        match &self.shared.access_token {
+            Some(token) => client.access_token(token),
+            None => client,
+        }
+    }
+
+    /// 之后每次调用`action`都返回`data`
+    pub fn respond(&self, action: impl Into<String>, data: impl Serialize) {
+        let reply = Reply::ok(data);
+        self.respond_with(action, move |_| reply.clone());
+    }
+
+    /// 之后每次调用`action`都失败
+    pub fn fail(&self, action: impl Into<String>, retcode: i32, message: impl Into<String>) {
+        let reply = Reply::failed(retcode, message);
+        self.respond_with(action, move |_| reply.clone());
+    }
+
+    /// 根据调用参数生成`action`的响应
+    pub fn respond_with<F>(&self, action: impl Into<String>, responder: F)
+    where
+        F: Fn(&Value) -> Reply + Send + Sync + 'static,
+    {
+        self.shared
+            .state()
+            .responders
+            .insert(action.into(), Arc::new(responder));
+    }
+
+    /// 下一次调用`action`时返回`reply`，优先于其他预设，多次设置时按顺序使用
+    pub fn respond_once(&self, action: impl Into<String>, reply: Reply) {
+        self.shared
+            .state()
+            .once
+            .entry(action.into())
+            .or_default()
+            .push_back(reply);
+    }
+
+    /// 收到的所有调用
+    pub fn calls(&self) -> Vec<Call> {
+        self.shared.state().calls.clone()
+    }
+
+    /// 所有调用`action`的参数
+    pub fn calls_to(&self, action: &str) -> Vec<Value> {
+        self.shared
+            .state()
+            .calls
+            .iter()
+            .filter(|c| c.action == action)
+            .map(|c| c.params.clone())
+            .collect()
+    }
+
+    /// 清空调用记录
+    pub fn clear_calls(&self) {
+        self.shared.state().calls.clear();
+    }
+
+    /// 等待第`n`次（从1开始）调用`action`，超时返回`None`，用于测试异步处理事件的代码
+    pub async fn wait_for_call(&self, action: &str, n: usize, timeout: Duration) -> Option<Call> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let call = self
+                .shared
+                .state()
+                .calls
+                .iter()
+                .filter(|c| c.action == action)
+                .nth(n.saturating_sub(1))
+                .cloned();
+            if call.is_some() || Instant::now() >= deadline {
+                return call;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+
+    /// 接收事件的WebSocket连接数
+    pub fn subscribers(&self) -> usize {
+        let mut state = self.shared.state();
+        state.subscribers.retain(|tx| !tx.is_closed());
+        state.subscribers.len()
+    }
+
+    /// 向所有接收事件的WebSocket连接推送事件，返回收到事件的连接数
+    ///
+    /// `time`和`self_id`缺失时会被补全
+    pub fn push_event(&self, event: impl Serialize) -> Result<usize> {
+        let mut event = serde_json::to_value(event)?;
+        if let Value::Object(map) = &mut event {
+            let now = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs());
+            map.entry("time").or_insert(json!(now));
+            map.entry("self_id").or_insert(json!(10000));
+        }
+        let text = event.to_string();
+        let mut state = self.shared.state();
+        state
+            .subscribers
+            .retain(|tx| tx.unbounded_send(text.clone()).is_ok());
+        Ok(state.subscribers.len())
+    }
+}
+
+impl Drop for FakeGoCqhttp {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            shutdown.send(()).ok();
+        }
+        // 关闭所有WebSocket连接的事件推送
+        self.shared.state().subscribers.clear();
+    }
+}
+
+fn json_response(status: StatusCode, body: &Value) -> Response<Body> {
+    let mut resp = Response::new(Body::from(body.to_string()));
+    *resp.status_mut() = status;
+    resp.headers_mut()
+        .insert("content-type", HeaderValue::from_static("application/json"));
+    resp
+}
+
+fn status_response(status: StatusCode) -> Response<Body> {
+    let mut resp = Response::new(Body::empty());
+    *resp.status_mut() = status;
+    resp
+}
+
+/// 拆分终结点中的调用方式后缀
+fn parse_endpoint(endpoint: &str) -> (String, CallMode) {
+    if let Some(action) = endpoint.strip_suffix("_async") {
+        (action.to_string(), CallMode::Async)
+    } else if let Some(action) = endpoint.strip_suffix("_rate_limited") {
+        (action.to_string(), CallMode::RateLimited)
+    } else {
+        (endpoint.to_string(), CallMode::Normal)
+    }
+}
+
+/// 检查access token，通过请求头或`access_token`查询参数提供
+fn authorize(shared: &Shared, req: &Request<Body>) -> Option<StatusCode> {
+    let expected = shared.access_token.as_deref()?;
+    let header = req
+        .headers()
+        .get("authorization")
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.trim_start_matches("Bearer ").trim_start_matches("Token "));
+    let query = req
+        .uri()
+        .query()
+        .and_then(|q| q.split('&').find_map(|kv| kv.strip_prefix("access_token=")));
+    match header.or(query) {
+        None => Some(StatusCode::UNAUTHORIZED),
+        Some(token) if token != expected => Some(StatusCode::FORBIDDEN),
+        Some(_) => None,
+    }
+}
+
+async fn handle(shared: Arc<Shared>, req: Request<Body>) -> Response<Body> {
+    if let Some(status) = authorize(&shared, &req) {
+        return status_response(status);
+    }
+    if req.headers().contains_key(SEC_WEBSOCKET_KEY) {
+        return upgrade(shared, req);
+    }
+    let endpoint = req.uri().path().trim_start_matches('/').to_string();
+    let headers = req
+        .headers()
+        .iter()
+        .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+        .collect();
+    if endpoint.is_empty() {
+        return status_response(StatusCode::NOT_FOUND);
+    }
+    let body = match hyper::body::to_bytes(req.into_body()).await {
+        Ok(body) => body,
+        Err(_) => return status_response(StatusCode::BAD_REQUEST),
+    };
+    let params = if body.is_empty() {
+        json!({})
+    } else {
+        match serde_json::from_slice(&body) {
+            Ok(params) => params,
+            Err(_) => return status_response(StatusCode::BAD_REQUEST),
+        }
+    };
+    let (action, mode) = parse_endpoint(&endpoint);
+    let response = shared.state().reply(Call {
+        action,
+        mode,
+        params,
+        transport: Transport::Http,
+        headers,
+    });
+    json_response(StatusCode::OK, &response)
+}
+
+/// 完成WebSocket握手，之后的连接在单独的任务中处理
+fn upgrade(shared: Arc<Shared>, req: Request<Body>) -> Response<Body> {
+    let path = req.uri().path().trim_end_matches('/').to_string();
+    let (api, event) = match path.as_str() {
+        "" => (true, true),
+        "/api" => (true, false),
+        "/event" => (false, true),
+        _ => return status_response(StatusCode::NOT_FOUND),
+    };
+    let accept = derive_accept_key(req.headers()[SEC_WEBSOCKET_KEY].as_bytes());
+    let (tx, rx) = mpsc::unbounded();
+    if event {
+        // 在握手完成前注册，握手后推送的事件一定能收到
+        shared.state().subscribers.push(tx.clone());
+    }
+    tokio::spawn(async move {
+        let Ok(upgraded) = hyper::upgrade::on(req).await else {
+            return;
+        };
+        let ws = WebSocketStream::from_raw_socket(upgraded, Role::Server, None).await;
+        serve_ws(shared, ws, api, tx, rx).await;
+    });
+    let mut resp = status_response(StatusCode::SWITCHING_PROTOCOLS);
+    let headers = resp.headers_mut();
+    headers.insert(UPGRADE, HeaderValue::from_static("websocket"));
+    headers.insert(CONNECTION, HeaderValue::from_static("Upgrade"));
+    if let Ok(accept) = HeaderValue::from_str(&accept) {
+        headers.insert(SEC_WEBSOCKET_ACCEPT, accept);
+    }
+    resp
+}
+
+async fn serve_ws<S>(
+    shared: Arc<Shared>,
+    ws: WebSocketStream<S>,
+    api: bool,
+    tx: mpsc::UnboundedSender<String>,
+    mut rx: mpsc::UnboundedReceiver<String>,
+) where
+    S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin + Send + 'static,
+{
+    let (mut sink, mut stream) = ws.split();
+    let writer = tokio::spawn(async move {
+        while let Some(text) = rx.next().await {
+            if sink.send(WsMessage::text(text)).await.is_err() {
+                break;
+            }
+        }
+        sink.close().await.ok();
+    });
+    while let Some(Ok(message)) = stream.next().await {
+        let WsMessage::Text(text) = message else {
+            continue;
+        };
+        if !api {
+            continue;
+        }
+        let Ok(request) = serde_json::from_str::<Value>(text.as_str()) else {
+            continue;
+        };
+        let (action, mode) = parse_endpoint(request["action"].as_str().unwrap_or_default());
+        let params = match &request["params"] {
+            Value::Null => json!({}),
+            params => params.clone(),
+        };
+        let mut response = shared.state().reply(Call {
+            action,
+            mode,
+            params,
+            transport: Transport::WebSocket,
+            headers: HashMap::new(),
+        });
+        if let Some(echo) = request.get("echo") {
+            response["echo"] = echo.clone();
+        }
+        if tx.unbounded_send(response.to_string()).is_err() {
+            break;
+        }
+    }
+    drop(tx);
+    writer.abort();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FakeGoCqhttp, Reply, Transport};
+    use crate::api::mode::{CallMode, Failed};
+    use crate::api::params::{DeleteMsg, GetMsg};
+    use crate::api::GoCqhttpAPI;
+    use futures::{SinkExt, StreamExt};
+    use serde_json::{json, Value};
+    use tokio_tungstenite::connect_async;
+    use tokio_tungstenite::tungstenite::Message;
+
+    #[tokio::test]
+    async fn test_fake_http() {
+        let fake = FakeGoCqhttp::start().await.unwrap();
+        let client = fake.client();
+        fake.respond_once("delete_msg", Reply::failed(100, "no"));
+        let error = client.delete_msg(DeleteMsg::new(1)).await.unwrap_err();
+        assert_eq!(error.downcast_ref::<Failed>().unwrap().retcode, 100);
+        client.delete_msg(DeleteMsg::new(2)).await.unwrap();
+        client
+            .with_mode(CallMode::Async)
+            .delete_msg(DeleteMsg::new(3))
+            .await
+            .unwrap();
+        let calls = fake.calls();
+        assert_eq!(calls.len(), 3);
+        assert_eq!(calls[2].mode, CallMode::Async);
+        assert_eq!(fake.calls_to("delete_msg")[1], json!({ "message_id": 2 }));
+        fake.fail("get_msg", 1404, "not found");
+        assert!(client.get_msg(GetMsg::new(1)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fake_access_token() {
+        let fake = FakeGoCqhttp::with_access_token("secret").await.unwrap();
+        fake.client().delete_msg(DeleteMsg::new(1)).await.unwrap();
+        let wrong = crate::api::client::HttpClient::new(fake.url()).access_token("x");
+        assert!(wrong.delete_msg(DeleteMsg::new(1)).await.is_err());
+        assert_eq!(fake.calls().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_fake_websocket() {
+        let fake = FakeGoCqhttp::start().await.unwrap();
+        fake.respond_with("get_msg", |params| {
+            Reply::ok(json!({ "message_id": params["message_id"] }))
+        });
+        let (_api, _) = connect_async(format!("{}/api", fake.ws_url()))
+            .await
+            .unwrap();
+        assert_eq!(fake.subscribers(), 0);
+        let (mut ws, _) = connect_async(fake.ws_url()).await.unwrap();
+        assert_eq!(fake.subscribers(), 1);
+        assert_eq!(
+            fake.push_event(json!({ "post_type": "meta_event" }))
+                .unwrap(),
+            1
+        );
+        let text = ws.next().await.unwrap().unwrap().into_text().unwrap();
+        let event: Value = serde_json::from_str(text.as_str()).unwrap();
+        assert_eq!(event["post_type"], "meta_event");
+        assert!(event["time"].is_u64());
+        let request = json!({ "action": "get_msg", "params": { "message_id": 5 }, "echo": "e" });
+        ws.send(Message::text(request.to_string())).await.unwrap();
+        let text = ws.next().await.unwrap().unwrap().into_text().unwrap();
+        let response: Value = serde_json::from_str(text.as_str()).unwrap();
+        assert_eq!(response["data"]["message_id"], 5);
+        assert_eq!(response["echo"], "e");
+        assert_eq!(fake.calls()[0].transport, Transport::WebSocket);
+    }
+}
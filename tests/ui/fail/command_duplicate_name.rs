@@ -0,0 +1,11 @@
+use rust_gocqhttp::command::Command;
+
+#[derive(Command)]
+enum Admin {
+    #[command(alias = "k")]
+    Kick,
+    #[command(name = "k")]
+    Kill,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use rust_gocqhttp::command::Command;
+
+#[derive(Command)]
+struct Mute {
+    seconds: Option<i64>,
+    target: i64,
+}
+
+fn main() {}
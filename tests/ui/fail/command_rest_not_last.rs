@@ -0,0 +1,9 @@
+use rust_gocqhttp::command::{Command, Rest};
+
+#[derive(Command)]
+struct Say {
+    text: Rest,
+    times: Option<i64>,
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+use rust_gocqhttp::command::Command;
+
+#[derive(Command)]
+struct Kick(i64);
+
+fn main() {}
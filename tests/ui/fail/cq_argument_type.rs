@@ -0,0 +1,6 @@
+use rust_gocqhttp::cq;
+
+fn main() {
+    let id: i64 = 1;
+    let _ = cq!("[CQ:face,id={}]", id);
+}
@@ -0,0 +1,8 @@
+use rust_gocqhttp::cq;
+
+fn main() {
+    let _ = cq!("[CQ:at,qq={}]");
+    let _ = cq!("hello", 1);
+    let _ = cq!("{0}", 1);
+    let _ = cq!("[CQ:at,qq=1{}]", 1);
+}
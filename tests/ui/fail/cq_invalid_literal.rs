@@ -0,0 +1,6 @@
+use rust_gocqhttp::cq;
+
+fn main() {
+    let _ = cq!("[CQ:face,id=abc]");
+    let _ = cq!("[CQ:image,file=1.jpg,type=big]");
+}
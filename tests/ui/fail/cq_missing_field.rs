@@ -0,0 +1,5 @@
+use rust_gocqhttp::cq;
+
+fn main() {
+    let _ = cq!("[CQ:share,url=https://a.com]");
+}
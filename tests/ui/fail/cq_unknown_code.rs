@@ -0,0 +1,5 @@
+use rust_gocqhttp::cq;
+
+fn main() {
+    let _ = cq!("[CQ:foo,id=1]");
+}
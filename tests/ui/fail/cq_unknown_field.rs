@@ -0,0 +1,5 @@
+use rust_gocqhttp::cq;
+
+fn main() {
+    let _ = cq!("[CQ:face,id=1,size=2]");
+}
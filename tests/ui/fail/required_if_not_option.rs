@@ -0,0 +1,11 @@
+use rust_gocqhttp::message::cq_code::CQCode;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, CQCode)]
+struct Music {
+    kind: String,
+    #[cq(required_if(kind = "qq"))]
+    id: String,
+}
+
+fn main() {}
@@ -0,0 +1,7 @@
+use rust_gocqhttp::message::cq_code::CQCode;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, CQCode)]
+struct Face(i32);
+
+fn main() {}
@@ -0,0 +1,10 @@
+use rust_gocqhttp::message::cq_code::CQCode;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, CQCode)]
+struct Face {
+    #[cq(rename = "face_id")]
+    id: i32,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use rust_gocqhttp::message::cq_code::CQCode;
+
+#[derive(CQCode)]
+enum Music {
+    #[cq(tag = "custom")]
+    Custom { url: String },
+    Platform { id: String },
+}
+
+fn main() {}
@@ -0,0 +1,60 @@
+use rust_gocqhttp::command::{Command, Rest, UserId};
+use rust_gocqhttp::message::cq_code::code::At;
+use rust_gocqhttp::message::Message;
+use std::time::Duration;
+
+#[derive(Command)]
+#[command(alias = "禁言", help = "禁言群成员")]
+struct BanCommand {
+    #[command(help = "被禁言的成员")]
+    target: At,
+    duration: Duration,
+    reason: Option<String>,
+}
+
+#[derive(Command)]
+enum Admin {
+    #[command(name = "kick", alias = "踢")]
+    Kick {
+        #[command(name = "qq")]
+        target: UserId,
+        reason: Option<Rest>,
+    },
+    Help,
+}
+
+fn message(s: &str) -> Message {
+    s.parse().unwrap()
+}
+
+fn main() {
+    let ban = BanCommand::parse(&message("/禁言 [CQ:at,qq=123] 1h \"刷 屏\""), &["/"])
+        .unwrap()
+        .unwrap();
+    assert_eq!(ban.target.qq, "123");
+    assert_eq!(ban.duration, Duration::from_secs(3600));
+    assert_eq!(ban.reason.as_deref(), Some("刷 屏"));
+    assert!(BanCommand::parse(&message("/kick 1"), &["/"]).is_none());
+    assert_eq!(
+        BanCommand::help("/"),
+        "/ban <target:@成员> <duration:时长> [reason:文本]\n  禁言群成员\n  别名：禁言\n  target：被禁言的成员"
+    );
+    let error = BanCommand::parse(&message("/ban 123"), &["/"])
+        .unwrap()
+        .err()
+        .unwrap();
+    assert_eq!(
+        error.to_string(),
+        "参数target应为@成员，而不是“123”\n用法：/ban <target:@成员> <duration:时长> [reason:文本]"
+    );
+
+    let Some(Ok(Admin::Kick { target, reason })) = Admin::parse(&message("!踢 10 a b"), &["!"])
+    else {
+        panic!("not kick")
+    };
+    assert_eq!(target, UserId(10));
+    assert_eq!(reason.map(|r| r.0.to_string()).as_deref(), Some("a b"));
+    assert!(matches!(Admin::parse(&message("!help"), &["!"]), Some(Ok(Admin::Help))));
+    assert!(Admin::parse(&message("!help 1"), &["!"]).unwrap().is_err());
+    assert_eq!(Admin::help("!"), "!kick <qq:QQ号或@成员> [reason:文本...]\n  别名：踢\n!help");
+}
@@ -0,0 +1,17 @@
+use rust_gocqhttp::cq;
+use rust_gocqhttp::message::cq_code::code::MusicPlatform;
+
+fn main() {
+    let uid = 123;
+    let m = cq!("你好[CQ:at,qq={}] [CQ:face,id=14]", uid);
+    assert_eq!(m.to_string(), "你好[CQ:at,qq=123] [CQ:face,id=14]");
+    let m = cq!(
+        "[CQ:location,lat=39.9,lon={}][CQ:music,type={},id=1]",
+        116.3,
+        MusicPlatform::QQ,
+    );
+    assert_eq!(
+        m.to_string(),
+        "[CQ:location,lon=116.3,lat=39.9][CQ:music,type=qq,id=1]"
+    );
+}
@@ -0,0 +1,21 @@
+use rust_gocqhttp::message::cq_code::CQCode;
+
+#[derive(Debug, CQCode)]
+#[cq(tag = "kind")]
+enum Shape {
+    #[cq(tag = "circle")]
+    Circle { r: f64 },
+    Polygon {
+        #[cq(tag)]
+        sides: u32,
+        size: Option<f64>,
+    },
+}
+
+fn main() {
+    let shape = Shape::from_string("[CQ:shape,kind=4,size=2]".to_string()).unwrap();
+    assert_eq!(shape.to_string(), "[CQ:shape,kind=4,size=2]");
+    let shape = Shape::from_json(r#"{"type":"shape","data":{"kind":"circle","r":1.5}}"#).unwrap();
+    assert_eq!(shape.to_string(), "[CQ:shape,kind=circle,r=1.5]");
+    assert!(Shape::from_string("[CQ:shape,kind=square]".to_string()).is_err());
+}
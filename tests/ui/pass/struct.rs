@@ -0,0 +1,24 @@
+use rust_gocqhttp::message::cq_code::CQCode;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, CQCode)]
+struct Face {
+    id: i32,
+    #[cq(default = 1)]
+    size: i32,
+    #[serde(rename = "type")]
+    type_: Option<String>,
+    #[cq(required_if(type_ = "big"))]
+    name: Option<String>,
+}
+
+fn main() {
+    let face = Face::from_string("[CQ:face,id=1,type=big,name=a]".to_string()).unwrap();
+    assert_eq!(face.id, 1);
+    assert_eq!(face.size, 1);
+    assert!(face.validate().is_ok());
+    assert_eq!(face.to_string(), "[CQ:face,id=1,size=1,type=big,name=a]");
+    assert!(Face::from_string("[CQ:face,id=abc]".to_string()).is_err());
+    assert!(Face::from_string("hello".to_string()).is_err());
+    assert!(Face::from_json(r#"{"type":"face","data":{"id":1}}"#).is_ok());
+}